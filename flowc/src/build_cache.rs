@@ -0,0 +1,88 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use flowclib::loader::cache::hash;
+
+/*
+    Determine the directory to keep the cache in, between runs of flowc. It can be set using the
+    'FLOW_CACHE_DIR' environment variable, and if not it is '.flow/cache' in the user's home
+    directory.
+*/
+pub fn cache_dir() -> PathBuf {
+    if let Ok(dir) = env::var("FLOW_CACHE_DIR") {
+        return PathBuf::from(dir);
+    }
+
+    match env::var("HOME") {
+        Ok(home) => PathBuf::from(home).join(".flow").join("cache"),
+        Err(_) => env::temp_dir().join("flow").join("cache")
+    }
+}
+
+/*
+    The file in the cache directory that records what the manifest at 'manifest' was generated from
+*/
+fn record_file(cache_dir: &Path, manifest: &Path) -> PathBuf {
+    cache_dir.join("manifests").join(format!("{:016x}", hash(manifest.to_string_lossy().as_bytes())))
+}
+
+/*
+    The record of a manifest: the hash of everything it was generated from, and the hash of the
+    manifest itself, so that a manifest that has been changed or replaced since is not used
+*/
+fn record_of(manifest: &Path, build_hash: u64) -> Option<String> {
+    let contents = fs::read(manifest).ok()?;
+    Some(format!("{:016x} {:016x}", build_hash, hash(&contents)))
+}
+
+/*
+    Check if the manifest at 'manifest' exists and was generated from the same flow definitions
+    and options as those that 'build_hash' is the hash of, so there is no need to generate it again
+*/
+pub fn is_up_to_date(cache_dir: &Path, manifest: &Path, build_hash: u64) -> bool {
+    match (fs::read_to_string(record_file(cache_dir, manifest)), record_of(manifest, build_hash)) {
+        (Ok(recorded), Some(record)) => recorded == record,
+        _ => false
+    }
+}
+
+/*
+    Record what the manifest at 'manifest' was generated from. Failing to do so is not an error,
+    as the manifest will just be generated again the next time.
+*/
+pub fn record(cache_dir: &Path, manifest: &Path, build_hash: u64) {
+    let file = record_file(cache_dir, manifest);
+    if let Some(record) = record_of(manifest, build_hash) {
+        let written = fs::create_dir_all(file.parent().unwrap())
+            .and_then(|_| fs::write(&file, record));
+        if let Err(e) = written {
+            info!("Could not record manifest '{}' in the cache ({})", manifest.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use tempdir::TempDir;
+
+    #[test]
+    fn manifest_up_to_date_until_changed() {
+        let cache_dir = TempDir::new("cache").unwrap();
+        let out_dir = TempDir::new("flow").unwrap();
+        let manifest = out_dir.path().join("manifest.json");
+
+        assert!(!super::is_up_to_date(cache_dir.path(), &manifest, 1));
+
+        fs::write(&manifest, "{}").unwrap();
+        super::record(cache_dir.path(), &manifest, 1);
+        assert!(super::is_up_to_date(cache_dir.path(), &manifest, 1));
+        assert!(!super::is_up_to_date(cache_dir.path(), &manifest, 2));
+
+        fs::write(&manifest, "{ \"processes\": [] }").unwrap();
+        assert!(!super::is_up_to_date(cache_dir.path(), &manifest, 1));
+    }
+}
@@ -7,16 +7,27 @@ extern crate simplog;
 extern crate tempdir;
 extern crate url;
 
+use std::fs;
+use std::io::Write;
+use std::path::Path;
 use std::path::PathBuf;
 use std::process::Command;
+use std::process::exit;
 use std::process::Stdio;
+use std::thread;
 
-use clap::{App, AppSettings, Arg, ArgMatches};
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
 use flowclib::compiler::compile;
+use flowclib::compiler::lint;
+use flowclib::compiler::optimizer;
 use flowclib::dumper::dump_flow;
 use flowclib::dumper::dump_tables;
 use flowclib::generator::generate;
 use flowclib::info;
+use flowclib::loader::cache;
+use flowclib::loader::cache::Cache;
+use flowclib::loader::error::Severity;
+use flowclib::loader::error::SourceError;
 use flowclib::loader::loader;
 use flowclib::model::flow::Flow;
 use flowclib::model::process::Process::FlowProcess;
@@ -27,11 +38,20 @@ use provider::content::args::url_from_string;
 use provider::content::provider::MetaProvider;
 
 mod source_arg;
+mod build_cache;
+mod watch;
+mod test_runner;
+
+// How many lines of what a flow that fails writes to standard error are shown when testing it
+const STDERR_LINES_SHOWN: usize = 20;
 
 fn main() {
     match run() {
         Ok(message) => info!("{}", message),
-        Err(e) => error!("{}", e)
+        Err(e) => {
+            error!("{}", e);
+            exit(1);
+        }
     }
 }
 
@@ -41,17 +61,79 @@ fn main() {
     a message to display to the user if all went OK
 */
 fn run() -> Result<String, String> {
-    let (url, args, dump, skip_generation, out_dir) = parse_args( get_matches())?;
+    let matches = get_matches();
+    let (url, args, dump, skip_generation, deny_warnings, optimize, refresh, watch, out_dir) = parse_args(&matches)?;
     let meta_provider = MetaProvider {};
+    let cache_dir = build_cache::cache_dir();
+    let cache = Cache::new(&meta_provider, Some(cache_dir.join("content")));
+    if refresh {
+        cache.refresh()?;
+    }
+
+    if let Some(test_matches) = matches.subcommand_matches("test") {
+        let dir = test_matches.value_of("DIR").unwrap_or(".");
+        return test_samples(Path::new(dir), &cache, deny_warnings, optimize, refresh, &cache_dir);
+    }
+
+    if watch {
+        return watch::watch(|| {
+            let flow = match load_flow(&url, &cache) {
+                Ok(flow) => flow,
+                Err(e) => return (Err(e), cache.local_files())
+            };
+            let files = cache.sources_of(&flow).map(|sources| sources.keys()
+                .filter_map(|url| url.to_file_path().ok()).collect()).unwrap_or(vec!());
+            let built = build_flow(flow, &cache, dump, skip_generation, deny_warnings, optimize, refresh,
+                                   out_dir.clone(), &cache_dir);
+            (built.map(|manifest| match manifest {
+                Some(manifest) => format!("Manifest '{}' is up to date", manifest.display()),
+                None => "Code Generation skipped".to_string()
+            }), files)
+        });
+    }
 
-    let process = loader::load_process(&"".to_string(),
-                                        &"context".to_string(), &url, &meta_provider)?;
-    match process {
-        FlowProcess(flow) => run_flow(flow, args, dump, skip_generation, out_dir),
+    let flow = load_flow(&url, &cache)?;
+    // Append flow arguments at the end of the arguments so that are passed on it when it's run
+    match build_flow(flow, &cache, dump, skip_generation, deny_warnings, optimize, refresh, out_dir, &cache_dir)? {
+        Some(manifest) => execute_flow(manifest, args),
+        None => Ok("Code Generation and Running skipped".to_string())
+    }
+}
+
+/*
+    Load the flow from 'url' using the cache
+*/
+fn load_flow(url: &Url, cache: &Cache) -> Result<Flow, String> {
+    match loader::load_cached_process(&"".to_string(), &"context".to_string(), url, cache)
+        .map_err(|errors| {
+            report(&errors);
+            format!("Could not load flow '{}', {} found", url, count(errors.len(), "error"))
+        })? {
+        FlowProcess(flow) => Ok(flow),
         _ => Err(format!("Process loaded was not of type 'Flow' and cannot be executed"))
     }
 }
 
+/*
+    Report all the problems found in a flow, each with it's severity and the part of the definition
+    it is about, so they are seen whatever the log level is
+*/
+fn report(problems: &[SourceError]) {
+    for problem in problems {
+        eprintln!("{}\n", problem);
+    }
+}
+
+/*
+    Describe a number of things, e.g. "1 error" or "3 errors"
+*/
+fn count(number: usize, thing: &str) -> String {
+    match number {
+        1 => format!("1 {}", thing),
+        _ => format!("{} {}s", number, thing)
+    }
+}
+
 /*
     Parse the command line arguments using clap
 */
@@ -67,6 +149,20 @@ fn get_matches<'a>() -> ArgMatches<'a> {
             .short("d")
             .long("dump")
             .help("Dump the flow to standard output after loading it"))
+        .arg(Arg::with_name("deny-warnings")
+            .long("deny-warnings")
+            .help("Fail if any warnings are found in the flow (e.g. inputs that are not connected)"))
+        .arg(Arg::with_name("optimize")
+            .short("O")
+            .long("optimize")
+            .help("Optimize the flow, by inlining static values and removing functions and values that are not used"))
+        .arg(Arg::with_name("watch")
+            .short("w")
+            .long("watch")
+            .help("Compile the flow and generate it's manifest again each time a file it uses changes, instead of running it"))
+        .arg(Arg::with_name("refresh")
+            .long("refresh")
+            .help("Fetch all definitions again and generate the manifest again, instead of using the cache"))
         .arg(Arg::with_name("output")
             .short("o")
             .long("output")
@@ -85,13 +181,19 @@ fn get_matches<'a>() -> ArgMatches<'a> {
             .index(1))
         .arg(Arg::with_name("flow_args")
             .multiple(true))
+        .subcommand(SubCommand::with_name("test")
+            .about("Compile and run each sample in a directory, checking it produces the output expected")
+            .arg(Arg::with_name("DIR")
+                .help("the sample directory, or a directory of sample directories")
+                .required(true)
+                .index(1)))
         .get_matches()
 }
 
 /*
     Parse the command line arguments
 */
-fn parse_args(matches: ArgMatches) -> Result<(Url, Vec<String>, bool, bool, PathBuf), String> {
+fn parse_args(matches: &ArgMatches) -> Result<(Url, Vec<String>, bool, bool, bool, bool, bool, bool, PathBuf), String> {
     let mut args: Vec<String> = vec!();
     if let Some(flow_args) = matches.values_of("flow_args") {
         args = flow_args.map(|a| a.to_string()).collect();
@@ -106,17 +208,58 @@ fn parse_args(matches: ArgMatches) -> Result<(Url, Vec<String>, bool, bool, Path
 
     let dump = matches.is_present("dump");
     let skip_generation = matches.is_present("skip");
-    let out_dir_option = matches.value_of("OUTPUT_DIR");
+    let deny_warnings = matches.is_present("deny-warnings");
+    let optimize = matches.is_present("optimize");
+    let refresh = matches.is_present("refresh");
+    let watch = matches.is_present("watch");
+    let out_dir_option = matches.value_of("output");
     let output_dir = source_arg::get_output_dir(&url, out_dir_option)?;
 
-    Ok((url, args, dump, skip_generation, output_dir))
+    Ok((url, args, dump, skip_generation, deny_warnings, optimize, refresh, watch, output_dir))
 }
 
-fn run_flow(flow: Flow, args: Vec<String>, dump: bool, skip_generation: bool, mut out_dir: PathBuf)
-    -> Result<String, String> {
+/*
+    Calculate a hash of everything the manifest for a flow is generated from: the definitions it
+    was loaded from, the version of flowclib that compiles it and the options that change what is
+    generated or checked (so a flow built without '--deny-warnings' is checked again when it is used)
+*/
+fn build_hash(cache: &Cache, flow: &Flow, optimize: bool, deny_warnings: bool) -> Result<u64, String> {
+    let build = format!("{:016x} {} {} {}", cache.hash_of(flow)?, info::version(), optimize, deny_warnings);
+    Ok(cache::hash(build.as_bytes()))
+}
+
+/*
+    Compile the flow and generate it's manifest, returning the path of the manifest (unless code
+    generation is skipped). If the manifest was generated before from the same definitions and
+    options, as recorded in the cache, and has not changed since, then it is used as it is.
+*/
+fn build_flow(flow: Flow, cache: &Cache, dump: bool, skip_generation: bool, deny_warnings: bool,
+              optimize: bool, refresh: bool, mut out_dir: PathBuf, cache_dir: &Path)
+              -> Result<Option<PathBuf>, String> {
     info!("flow loaded with alias '{}'\n", flow.alias);
 
-    let tables = compile::compile(&flow)?;
+    let build_hash = build_hash(cache, &flow, optimize, deny_warnings)?;
+    let manifest = out_dir.join("manifest.json");
+    if !refresh && !dump && !skip_generation && build_cache::is_up_to_date(cache_dir, &manifest, build_hash) {
+        info!("Flow has not changed since manifest '{}' was generated, so using it", manifest.display());
+        return Ok(Some(manifest));
+    }
+
+    let mut tables = compile::compile(&flow).map_err(|errors| {
+        let errors: Vec<SourceError> = errors.into_iter().map(|e| SourceError::in_flow(e, &flow, cache)).collect();
+        report(&errors);
+        format!("Could not compile flow '{}', {} found", flow.source_url, count(errors.len(), "error"))
+    })?;
+
+    let findings = lint::lint(&tables);
+    report(&findings.iter().map(|finding| SourceError::in_flow(finding.message.clone(), &flow, cache)
+        .with_severity(finding.severity)).collect::<Vec<_>>());
+    check_findings(findings, deny_warnings)?;
+
+    if optimize {
+        optimizer::optimize(&mut tables)
+            .map_err(|e| SourceError::in_flow(e, &flow, cache).to_string())?;
+    }
 
     if dump {
         info!("Dumping flow, compiler tables and runnable descriptions in '{}'", out_dir.display());
@@ -127,14 +270,91 @@ fn run_flow(flow: Flow, args: Vec<String>, dump: bool, skip_generation: bool, mu
     }
 
     if skip_generation {
-        return Ok("Code Generation and Running skipped".to_string());
+        return Ok(None);
     }
 
     let filename = generate::create_manifest(&flow, &out_dir, &tables).map_err(|e| e.to_string())?;
     out_dir.push(filename);
+    build_cache::record(cache_dir, &out_dir, build_hash);
 
-    // Append flow arguments at the end of the arguments so that are passed on it when it's run
-    execute_flow(out_dir, args)
+    Ok(Some(out_dir))
+}
+
+/*
+    Fail if there are warnings in the problems found in the flow by lint, and warnings are denied
+*/
+fn check_findings(findings: Vec<lint::Finding>, deny_warnings: bool) -> Result<(), String> {
+    let warnings = findings.iter().filter(|finding| finding.severity == Severity::Warning).count();
+    if deny_warnings && warnings > 0 {
+        return Err(format!("{} found in the flow, and warnings are denied", count(warnings, "warning")));
+    }
+
+    Ok(())
+}
+
+/*
+    Test the samples in 'dir' (see test_runner), compiling each one's flow and generating it's
+    manifest in the sample's directory, and then running it using 'flowr'
+*/
+fn test_samples(dir: &Path, cache: &Cache, deny_warnings: bool, optimize: bool, refresh: bool,
+                cache_dir: &Path) -> Result<String, String> {
+    let samples = test_runner::find_samples(dir)?;
+    test_runner::test_samples(&samples, |sample, args, input| {
+        let sample = fs::canonicalize(sample).map_err(|e| e.to_string())?;
+        let url = Url::from_directory_path(&sample)
+            .map_err(|_| format!("Could not form a Url for the directory '{}'", sample.display()))?;
+        let out_dir = source_arg::get_output_dir(&url, None)?;
+        let flow = load_flow(&url, cache)?;
+        match build_flow(flow, cache, false, false, deny_warnings, optimize, refresh, out_dir, cache_dir)? {
+            Some(manifest) => run_flow(manifest, args, input),
+            None => Err("No manifest was generated".to_string())
+        }
+    })
+}
+
+/*
+    The command to run the flow with the manifest at 'filepath' using 'flowr', passing it 'args'.
+    The package is given so that it can be run from anywhere in the workspace, not just it's root.
+*/
+fn flowr_command(filepath: PathBuf, mut args: Vec<String>) -> Command {
+    let mut command_args = vec!("run".to_string(), "--package".to_string(), "flowr".to_string(),
+                                "--bin".to_string(), "flowr".to_string());
+    command_args.push(filepath.to_str().unwrap().to_string());
+    command_args.append(&mut args);
+    info!("Running flow using 'cargo {:?}'", &command_args);
+    let mut command = Command::new("cargo");
+    command.args(command_args);
+    command
+}
+
+/*
+    Run flow using 'flowr', giving it 'input' on it's standard input and returning what it writes
+    to standard output. If the process fails then return an Err() with the end of what was written
+    to standard error (after the output of building 'flowr', if it was built).
+*/
+fn run_flow(filepath: PathBuf, args: Vec<String>, input: Vec<u8>) -> Result<String, String> {
+    let mut child = flowr_command(filepath, args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn().map_err(|e| e.to_string())?;
+
+    // Write the input while the output is read, so neither can fill up and block the flow
+    let mut stdin = child.stdin.take().ok_or("Could not write to the flow's standard input")?;
+    let writer = thread::spawn(move || stdin.write_all(&input));
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    let _ = writer.join();
+
+    match output.status.code() {
+        Some(0) => Ok(String::from_utf8_lossy(&output.stdout).to_string()),
+        Some(code) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let lines: Vec<&str> = stderr.lines().collect();
+            let end = &lines[lines.len().saturating_sub(STDERR_LINES_SHOWN)..];
+            Err(format!("Exited with status code: {}\n{}", code, end.join("\n")))
+        }
+        None => Err("Process terminated by signal".to_string())
+    }
 }
 
 /*
@@ -144,13 +364,8 @@ fn run_flow(flow: Flow, args: Vec<String>, dump: bool, skip_generation: bool, mu
     If the process exits correctly then just return an Ok() with message and no log
     If the process fails then return an Err() with message and log stderr in an ERROR level message
 */
-fn execute_flow(filepath: PathBuf, mut args: Vec<String>) -> Result<String, String> {
-    let command = "cargo".to_string();
-    let mut command_args = vec!("run".to_string(), "--bin".to_string(), "flowr".to_string());
-    command_args.push(filepath.to_str().unwrap().to_string());
-    command_args.append(&mut args);
-    info!("Running flow using '{} {:?}'", &command, &command_args);
-    let output = Command::new(&command).args(command_args)
+fn execute_flow(filepath: PathBuf, args: Vec<String>) -> Result<String, String> {
+    let output = flowr_command(filepath, args)
         .stdin(Stdio::inherit())
         .stdout(Stdio::inherit())
         .stderr(Stdio::piped())
@@ -172,8 +387,17 @@ mod test {
     extern crate provider;
 
     use std::env;
+    use std::fs;
+    use std::path::Path;
+
+    use tempdir::TempDir;
 
     use flowclib::compiler::compile;
+    use flowclib::compiler::lint;
+    use flowclib::compiler::optimizer;
+    use flowclib::loader::cache::Cache;
+    use flowclib::loader::error::Severity;
+    use flowclib::loader::error::SourceError;
     use flowclib::loader::loader;
     use flowclib::model::name::Name;
     use flowclib::model::process::Process::FlowProcess;
@@ -215,6 +439,212 @@ mod test {
         }
     }
 
+    #[test]
+    fn lint_echo_finds_nothing() {
+        let meta_provider = MetaProvider {};
+        let parent_route = &"".to_string();
+        let process = loader::load_process(parent_route, &"echo".to_string(),
+                                           &url_from_rel_path("flowc/test-flows/echo.toml"),
+                                           &meta_provider).unwrap();
+        if let FlowProcess(ref flow) = process {
+            let tables = compile::compile(flow).unwrap();
+            assert!(lint::lint(&tables).is_empty());
+        } else {
+            assert!(false, "Process loaded was not a flow");
+        }
+    }
+
+    #[test]
+    fn lint_detects_unconnected_input() {
+        let meta_provider = MetaProvider {};
+        let parent_route = &"".to_string();
+        let process = loader::load_process(parent_route, &"unconnected".to_string(),
+                                           &url_from_rel_path("flowc/test-flows/unconnected.toml"),
+                                           &meta_provider).unwrap();
+        if let FlowProcess(ref flow) = process {
+            let tables = compile::compile(flow).unwrap();
+            let messages: Vec<String> = lint::lint(&tables).iter()
+                .filter(|finding| finding.severity == Severity::Warning)
+                .map(|finding| finding.message.clone()).collect();
+            assert_eq!(messages, vec!(
+                "Input 'i2' of '/unconnected/add' is not connected to, so it can never run".to_string(),
+                "'/unconnected/print' can never run, as nothing that runs sends to all of its inputs".to_string()));
+            assert!(super::check_findings(lint::lint(&tables), true).is_err());
+        } else {
+            assert!(false, "Process loaded was not a flow");
+        }
+    }
+
+    #[test]
+    fn deny_warnings_checks_flow_built_before() {
+        let meta_provider = MetaProvider {};
+        let cache = Cache::new(&meta_provider, None);
+        let out_dir = TempDir::new("out").unwrap();
+        let cache_dir = TempDir::new("cache").unwrap();
+        let url = url_from_rel_path("flowc/test-flows/unconnected.toml");
+        for &deny_warnings in &[false, true] {
+            let process = loader::load_cached_process(&"".to_string(), &"unconnected".to_string(), &url, &cache)
+                .unwrap();
+            if let FlowProcess(flow) = process {
+                let built = super::build_flow(flow, &cache, false, false, deny_warnings, false, false,
+                                              out_dir.path().to_path_buf(), cache_dir.path());
+                assert_eq!(built.is_err(), deny_warnings);
+            } else {
+                assert!(false, "Process loaded was not a flow");
+            }
+        }
+    }
+
+    /*
+        'flowr' is run using cargo, so this relies on the tests being run from within the workspace
+        (as they are by cargo, from the directory of the flowc package)
+    */
+    #[test]
+    fn sample_run_with_output_expected() {
+        let meta_provider = MetaProvider {};
+        let cache = Cache::new(&meta_provider, None);
+        let out_dir = TempDir::new("out").unwrap();
+        let cache_dir = TempDir::new("cache").unwrap();
+        let url = url_from_rel_path("samples/hello-world-simple/context.toml");
+        let process = loader::load_cached_process(&"".to_string(), &"hello-world-simple".to_string(), &url, &cache)
+            .unwrap();
+        if let FlowProcess(flow) = process {
+            let manifest = super::build_flow(flow, &cache, false, false, true, false, false,
+                                             out_dir.path().to_path_buf(), cache_dir.path()).unwrap().unwrap();
+            let output = super::run_flow(manifest, vec!(), vec!()).unwrap();
+            assert_eq!(output, fs::read_to_string("../samples/hello-world-simple/expected_output.txt").unwrap());
+        } else {
+            assert!(false, "Process loaded was not a flow");
+        }
+    }
+
+    /*
+        Every sample in the samples directory produces the output expected of it. As above, this
+        relies on the tests being run from within the workspace.
+    */
+    #[test]
+    fn samples_produce_output_expected() {
+        let meta_provider = MetaProvider {};
+        let cache = Cache::new(&meta_provider, None);
+        let cache_dir = TempDir::new("cache").unwrap();
+        let result = super::test_samples(Path::new("../samples"), &cache, false, false, false, cache_dir.path());
+        assert!(result.is_ok(), "{:?}", result);
+    }
+
+    #[test]
+    fn lint_fan_out_to_sub_flow_connected() {
+        let meta_provider = MetaProvider {};
+        let parent_route = &"".to_string();
+        let process = loader::load_process(parent_route, &"fan_out".to_string(),
+                                           &url_from_rel_path("flowc/test-flows/fan_out.toml"),
+                                           &meta_provider).unwrap();
+        if let FlowProcess(ref flow) = process {
+            let tables = compile::compile(flow).unwrap();
+            assert_eq!(tables.collapsed_connections.len(), 2);
+            assert!(lint::lint(&tables).iter().all(|finding| finding.severity != Severity::Warning));
+        } else {
+            assert!(false, "Process loaded was not a flow");
+        }
+    }
+
+    #[test]
+    fn optimize_inlines_and_removes() {
+        let meta_provider = MetaProvider {};
+        let parent_route = &"".to_string();
+        let process = loader::load_process(parent_route, &"optimize".to_string(),
+                                           &url_from_rel_path("flowc/test-flows/optimize.toml"),
+                                           &meta_provider).unwrap();
+        if let FlowProcess(ref flow) = process {
+            let mut tables = compile::compile(flow).unwrap();
+            optimizer::optimize(&mut tables).unwrap();
+
+            let routes: Vec<&str> = tables.runnables.iter().map(|runnable| runnable.route().as_str()).collect();
+            assert_eq!(routes, vec!("/optimize/args", "/optimize/to_number", "/optimize/add", "/optimize/print",
+                                    "/optimize/stdin"));
+            for (id, runnable) in tables.runnables.iter().enumerate() {
+                assert_eq!(runnable.get_id(), id);
+            }
+            assert_eq!(tables.runnables[1].get_output_routes(), &vec!(("".to_string(), 2, 0)));
+            let add_inputs = tables.runnables[2].get_inputs().unwrap();
+            assert_eq!(add_inputs[1].initializer().as_ref().map(|initializer| format!("{:?}", initializer)),
+                       Some("Constant(Number(1))".to_string()));
+        } else {
+            assert!(false, "Process loaded was not a flow");
+        }
+    }
+
+    #[test]
+    fn initialized_flow_input_passed_on() {
+        let meta_provider = MetaProvider {};
+        let parent_route = &"".to_string();
+        let process = loader::load_process(parent_route, &"initialized".to_string(),
+                                           &url_from_rel_path("flowc/test-flows/initialized.toml"),
+                                           &meta_provider).unwrap();
+        if let FlowProcess(ref flow) = process {
+            let tables = compile::compile(flow).unwrap();
+            let add = tables.runnables.iter().find(|runnable| runnable.route() == "/initialized/adder/add").unwrap();
+            let add_inputs = add.get_inputs().unwrap();
+            assert!(add_inputs[0].initializer().is_none());
+            assert_eq!(add_inputs[1].initializer().as_ref().map(|initializer| format!("{:?}", initializer)),
+                       Some("Constant(Number(1))".to_string()));
+            assert!(lint::lint(&tables).iter().all(|finding| finding.severity != Severity::Warning));
+        } else {
+            assert!(false, "Process loaded was not a flow");
+        }
+    }
+
+    #[test]
+    fn struct_type_field_connected() {
+        let meta_provider = MetaProvider {};
+        let parent_route = &"".to_string();
+        let process = loader::load_process(parent_route, &"struct_type".to_string(),
+                                           &url_from_rel_path("flowc/test-flows/struct_type.toml"),
+                                           &meta_provider).unwrap();
+        if let FlowProcess(ref flow) = process {
+            let tables = compile::compile(flow).unwrap();
+            let reverse = tables.runnables.iter().find(|runnable| runnable.route() == "/struct_type/reverse").unwrap();
+            assert_eq!(reverse.get_output_routes().len(), 1);
+            assert_eq!(reverse.get_output_routes()[0].0, "/reversed");
+        } else {
+            assert!(false, "Process loaded was not a flow");
+        }
+    }
+
+    #[test]
+    fn parameters_substituted() {
+        let meta_provider = MetaProvider {};
+        let parent_route = &"".to_string();
+        let process = loader::load_process(parent_route, &"parameterized".to_string(),
+                                           &url_from_rel_path("flowc/test-flows/parameterized.toml"),
+                                           &meta_provider).unwrap();
+        if let FlowProcess(ref flow) = process {
+            let tables = compile::compile(flow).unwrap();
+            let constant_added = |route: &str| {
+                let add = tables.runnables.iter().find(|runnable| runnable.route() == route).unwrap();
+                add.get_inputs().unwrap()[1].initializer().as_ref().map(|initializer| format!("{:?}", initializer))
+            };
+            assert_eq!(constant_added("/parameterized/add_five/add"), Some("Constant(Number(5))".to_string()));
+            assert_eq!(constant_added("/parameterized/add_one/add"), Some("Constant(Number(1))".to_string()));
+        } else {
+            assert!(false, "Process loaded was not a flow");
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn compiler_detects_connection_to_constant_input() {
+        let meta_provider = MetaProvider {};
+        let parent_route = &"".to_string();
+        let process = loader::load_process(parent_route, &"constant_connected".to_string(),
+                                           &url_from_rel_path("flowc/test-flows/constant_connected.toml"),
+                                           &meta_provider).unwrap();
+        if let FlowProcess(ref flow) = process {
+            let _tables = compile::compile(flow).unwrap();
+        } else {
+            assert!(false, "Process loaded was not a flow");
+        }
+    }
+
     #[test]
     #[should_panic]
     fn compiled_detects_competing_inputs() {
@@ -245,6 +675,80 @@ mod test {
         }
     }
 
+    #[test]
+    fn load_error_located_in_definition() {
+        let meta_provider = MetaProvider {};
+        let parent_route = &"".to_string();
+        let url = url_from_rel_path("flowc/test-flows/loop.toml");
+        let errors = loader::load_process(parent_route, &"loop".to_string(), &url, &meta_provider).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        let location = errors[0].location.clone().unwrap();
+        assert_eq!(location.url, url);
+        assert_eq!((location.line, location.column), (9, 9));
+    }
+
+    #[test]
+    fn all_load_errors_found() {
+        let meta_provider = MetaProvider {};
+        let parent_route = &"".to_string();
+        let url = url_from_rel_path("flowc/test-flows/many_errors.toml");
+        let errors = loader::load_process(parent_route, &"many_errors".to_string(), &url, &meta_provider).unwrap_err();
+        let found: Vec<(String, usize)> = errors.iter()
+            .map(|error| {
+                let location = error.location.clone().unwrap();
+                (location.url.path_segments().unwrap().last().unwrap().to_string(), location.line)
+            }).collect();
+        // The connection to the process that could not be loaded is not reported as well
+        assert_eq!(found, vec!(("many_errors.toml".to_string(), 10),
+                               ("bad_connection.toml".to_string(), 10),
+                               ("many_errors.toml".to_string(), 22)));
+        assert!(errors.iter().all(|error| error.severity == Severity::Error));
+    }
+
+    #[test]
+    fn flow_using_itself_detected() {
+        let meta_provider = MetaProvider {};
+        let parent_route = &"".to_string();
+        let url = url_from_rel_path("flowc/test-flows/uses_itself.toml");
+        let errors = loader::load_process(parent_route, &"uses_itself".to_string(), &url, &meta_provider).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.ends_with(&format!("'{}' -> '{}'", url, url)), "{}", errors[0].message);
+        assert_eq!(errors[0].location.clone().unwrap().line, 5);
+    }
+
+    #[test]
+    fn reference_cycle_detected() {
+        let meta_provider = MetaProvider {};
+        let parent_route = &"".to_string();
+        let first = url_from_rel_path("flowc/test-flows/cycle_first.toml");
+        let second = url_from_rel_path("flowc/test-flows/cycle_second.toml");
+        let errors = loader::load_process(parent_route, &"cycle".to_string(), &first, &meta_provider).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.ends_with(&format!("'{}' -> '{}' -> '{}'", first, second, first)),
+                "{}", errors[0].message);
+        let location = errors[0].location.clone().unwrap();
+        assert_eq!(location.url, second);
+        assert_eq!(location.line, 5);
+    }
+
+    #[test]
+    fn compile_error_located_in_definition() {
+        let meta_provider = MetaProvider {};
+        let cache = Cache::new(&meta_provider, None);
+        let parent_route = &"".to_string();
+        let url = url_from_rel_path("flowc/test-flows/competing.toml");
+        let process = loader::load_cached_process(parent_route, &"competing".to_string(), &url, &cache).unwrap();
+        if let FlowProcess(ref flow) = process {
+            let errors = compile::compile(flow).err().unwrap();
+            let error = SourceError::in_flow(errors[0].clone(), flow, &cache);
+            let location = error.location.unwrap();
+            assert_eq!(location.url, url);
+            assert_eq!((location.line, location.column), (21, 7));
+        } else {
+            assert!(false, "Process loaded was not a flow");
+        }
+    }
+
     #[test]
     #[should_panic]
     fn compile_double_connection() {
@@ -287,6 +791,15 @@ mod test {
                      &meta_provider).unwrap();
     }
 
+    #[test]
+    fn load_hello_world_yaml() {
+        let meta_provider = MetaProvider {};
+        let parent_route = &"".to_string();
+        loader::load_process(parent_route, &"hello-world-yaml".to_string(),
+                     &url_from_rel_path("samples/hello-world-yaml/context.yaml"),
+                     &meta_provider).unwrap();
+    }
+
     #[test]
     fn load_hello_world_flow1() {
         let meta_provider = MetaProvider {};
@@ -0,0 +1,178 @@
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+const ARGUMENTS: &str = "test_arguments.txt";
+const INPUT: &str = "test_input.txt";
+const EXPECTED_OUTPUT: &str = "expected_output.txt";
+
+// The most differences between the output of a sample and the output expected that are shown
+const MAX_DIFFERENCES: usize = 10;
+
+/*
+    Find the samples to test in 'dir': 'dir' itself if it is a sample, or else the sub-directories
+    of it that are, in order of their names. A sample is a directory with a flow in it, and the
+    output it is expected to produce in 'expected_output.txt'.
+*/
+pub fn find_samples(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    if dir.join(EXPECTED_OUTPUT).is_file() {
+        return Ok(vec!(dir.to_path_buf()));
+    }
+
+    let entries = fs::read_dir(dir).map_err(|e| format!("Could not read directory '{}' ({})", dir.display(), e))?;
+    let mut samples: Vec<PathBuf> = entries.filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.join(EXPECTED_OUTPUT).is_file())
+        .collect();
+    samples.sort();
+
+    if samples.is_empty() {
+        return Err(format!("No samples (directories with an '{}' file) found in '{}'", EXPECTED_OUTPUT, dir.display()));
+    }
+    Ok(samples)
+}
+
+/*
+    Test each of the samples, using 'run' to compile and run the flow of a sample with the
+    arguments and standard input given in it's 'test_arguments.txt' and 'test_input.txt' files
+    (if it has them), returning what it wrote to standard output. That is compared with the output
+    expected, and if they differ (or the sample could not be run) it fails.
+
+    The result of each sample is reported as it is tested, followed by why the ones that failed
+    did. Returns an error if any failed.
+*/
+pub fn test_samples<F>(samples: &[PathBuf], mut run: F) -> Result<String, String>
+    where F: FnMut(&Path, Vec<String>, Vec<u8>) -> Result<String, String> {
+    println!("running {} sample{}", samples.len(), if samples.len() == 1 { "" } else { "s" });
+
+    let mut failures = vec!();
+    for sample in samples {
+        let name = sample.file_name().map_or(sample.display().to_string(),
+                                             |name| name.to_string_lossy().to_string());
+        match test_sample(sample, &mut run) {
+            Ok(()) => println!("sample {} ... ok", name),
+            Err(reason) => {
+                println!("sample {} ... FAILED", name);
+                failures.push((name, reason));
+            }
+        }
+    }
+
+    if !failures.is_empty() {
+        println!("\nfailures:");
+        for &(ref name, ref reason) in &failures {
+            println!("\n---- {} ----\n{}", name, reason);
+        }
+    }
+
+    let result = if failures.is_empty() { "ok" } else { "FAILED" };
+    println!("\nsample result: {}. {} passed; {} failed", result, samples.len() - failures.len(), failures.len());
+
+    if failures.is_empty() {
+        Ok(format!("All {} samples passed", samples.len()))
+    } else {
+        Err(format!("{} of {} samples failed", failures.len(), samples.len()))
+    }
+}
+
+fn test_sample<F>(sample: &Path, run: &mut F) -> Result<(), String>
+    where F: FnMut(&Path, Vec<String>, Vec<u8>) -> Result<String, String> {
+    let arguments = match read_if_present(&sample.join(ARGUMENTS))? {
+        Some(arguments) => String::from_utf8_lossy(&arguments).split_whitespace().map(|arg| arg.to_string()).collect(),
+        None => vec!()
+    };
+    let input = read_if_present(&sample.join(INPUT))?.unwrap_or(vec!());
+    let expected = fs::read_to_string(sample.join(EXPECTED_OUTPUT))
+        .map_err(|e| format!("Could not read '{}' ({})", EXPECTED_OUTPUT, e))?;
+
+    let output = run(sample, arguments, input)?;
+    match differences(&expected, &output) {
+        None => Ok(()),
+        Some(differences) => Err(differences)
+    }
+}
+
+fn read_if_present(file: &Path) -> Result<Option<Vec<u8>>, String> {
+    if !file.exists() {
+        return Ok(None);
+    }
+    fs::read(file).map(Some).map_err(|e| format!("Could not read '{}' ({})", file.display(), e))
+}
+
+/*
+    Describe the lines of the output of a sample that are not the lines expected, if any are not
+*/
+fn differences(expected: &str, output: &str) -> Option<String> {
+    let expected_lines: Vec<&str> = expected.lines().collect();
+    let output_lines: Vec<&str> = output.lines().collect();
+
+    let mut differences = vec!();
+    for number in 0..expected_lines.len().max(output_lines.len()) {
+        let (expected_line, output_line) = (expected_lines.get(number), output_lines.get(number));
+        if expected_line != output_line {
+            differences.push(format!("line {}: expected {}, found {}", number + 1,
+                                     describe_line(expected_line), describe_line(output_line)));
+        }
+    }
+
+    if differences.is_empty() {
+        return None;
+    }
+
+    let count = differences.len();
+    if count > MAX_DIFFERENCES {
+        differences.truncate(MAX_DIFFERENCES);
+        differences.push(format!("... and {} more lines that differ", count - MAX_DIFFERENCES));
+    }
+    Some(format!("The output is not the output expected:\n{}", differences.join("\n")))
+}
+
+fn describe_line(line: Option<&&str>) -> String {
+    match line {
+        Some(line) => format!("'{}'", line),
+        None => "no line".to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use tempdir::TempDir;
+
+    use super::differences;
+    use super::find_samples;
+    use super::test_samples;
+
+    #[test]
+    fn samples_found_and_tested() {
+        let dir = TempDir::new("samples").unwrap();
+        for &(name, expected) in &[("second", "2\n"), ("first", "1\n")] {
+            let sample = dir.path().join(name);
+            fs::create_dir(&sample).unwrap();
+            fs::write(sample.join("expected_output.txt"), expected).unwrap();
+            fs::write(sample.join("test_arguments.txt"), "10  20\n").unwrap();
+        }
+        fs::create_dir(dir.path().join("not-a-sample")).unwrap();
+
+        let samples = find_samples(dir.path()).unwrap();
+        assert_eq!(samples, vec!(dir.path().join("first"), dir.path().join("second")));
+        assert_eq!(find_samples(&samples[0]).unwrap(), vec!(samples[0].clone()));
+
+        let mut arguments = vec!();
+        let result = test_samples(&samples, |_sample, args, input| {
+            assert!(input.is_empty());
+            arguments.push(args);
+            Ok("1\n".to_string())
+        });
+        assert_eq!(result, Err("1 of 2 samples failed".to_string()));
+        assert_eq!(arguments[0], vec!("10", "20"));
+    }
+
+    #[test]
+    fn differing_lines_described() {
+        assert_eq!(differences("1\n2\n", "1\n2\n"), None);
+        assert_eq!(differences("1\n2\n3\n", "1\n4\n"),
+                   Some("The output is not the output expected:\nline 2: expected '2', found '4'\n\
+                         line 3: expected '3', found no line".to_string()));
+    }
+}
@@ -0,0 +1,107 @@
+use std::fs;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+use std::time::SystemTime;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/*
+    Build a flow using 'build' and then build it again each time one of the local files it depends
+    on changes, until flowc is stopped. 'build' returns the result of building and the files the
+    flow depends on. If building fails the error is printed and the files it depended on before are
+    still watched, along with any that were read while trying, so the error can be fixed.
+*/
+pub fn watch<F>(mut build: F) -> Result<String, String>
+    where F: FnMut() -> (Result<String, String>, Vec<PathBuf>) {
+    let mut files: Vec<PathBuf> = vec!();
+
+    loop {
+        let (result, depended_on) = build();
+        match result {
+            Ok(message) => {
+                eprintln!("{}", message);
+                files = depended_on;
+            }
+            Err(e) => {
+                eprintln!("error: {}", e);
+                files.extend(depended_on);
+            }
+        }
+        files.sort();
+        files.dedup();
+
+        if files.is_empty() {
+            return Err("There are no local files that the flow depends on to watch".to_string());
+        }
+
+        eprintln!("Watching {} files for changes", files.len());
+        let states = file_states(&files);
+        wait_for_change(&files, &states);
+    }
+}
+
+/*
+    The time a file was last modified and it's length, or None if it cannot be read (e.g. it has
+    been deleted)
+*/
+fn file_state(file: &PathBuf) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(file).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/*
+    The state of each of 'files', to compare to later to see if they have changed
+*/
+fn file_states(files: &[PathBuf]) -> Vec<Option<(SystemTime, u64)>> {
+    files.iter().map(file_state).collect()
+}
+
+/*
+    Wait until any of 'files' is changed, created or deleted since they were in 'states',
+    checking them regularly
+*/
+fn wait_for_change(files: &[PathBuf], states: &[Option<(SystemTime, u64)>]) {
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        if let Some(changed) = files.iter().zip(states).find(|&(file, state)| &file_state(file) != state) {
+            info!("'{}' changed", changed.0.display());
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    use tempdir::TempDir;
+
+    #[test]
+    fn change_to_file_seen() {
+        let dir = TempDir::new("watch").unwrap();
+        let file = dir.path().join("context.toml");
+        fs::write(&file, "flow = 'watched'").unwrap();
+
+        // the state is taken before the file is changed, so the change is always seen however
+        // long it takes the thread waiting for it to start
+        let files = vec!(file.clone());
+        let states = super::file_states(&files);
+
+        // wait on another thread, so the test fails instead of hanging if the change is not seen
+        let (seen_tx, seen_rx) = mpsc::channel();
+        thread::spawn(move || {
+            super::wait_for_change(&files, &states);
+            let _ = seen_tx.send(());
+        });
+
+        // the content written is a different length, so the change is seen even if the file
+        // system records the time it was modified too coarsely for it to change
+        fs::write(&file, "flow = 'changed again'").unwrap();
+
+        assert!(seen_rx.recv_timeout(Duration::from_secs(10)).is_ok(), "Change to file was not seen");
+    }
+}
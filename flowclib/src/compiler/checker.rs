@@ -0,0 +1,90 @@
+use generator::generate::CodeGenTables;
+use model::connection::Connection;
+use model::datatype::DataType;
+use model::datatype::TypeCheck;
+use model::route::HasRoute;
+
+/*
+    Check that the type of the value sent on each of the (collapsed) connections can be accepted
+    by the input of the runnable it is sent to.
+
+    Each connection inside a flow was checked when it was loaded, but a value can pass through
+    a "Json" flow input or output on it's way to a runnable, so only now that the connections
+    have been collapsed can the two ends be checked against each other. The destination's type is
+    taken from the runnable's input, as a collapsed connection still has the type of the first
+    flow input it was connected to.
+
+    All the mismatches found are added to 'errors'.
+*/
+pub fn check_connection_types(tables: &CodeGenTables, errors: &mut Vec<String>) {
+    for connection in &tables.collapsed_connections {
+        if let Some(to_type) = destination_type(tables, connection) {
+            let from_type = connection.from_io.full_datatype();
+            if !from_type.compatible_with(&to_type) {
+                errors.push(format!("Type mismatch: '{}' of type '{}' is connected to '{}' of type '{}'",
+                                    connection.from_io.route(), from_type,
+                                    connection.to_io.route(), to_type));
+            }
+        }
+    }
+}
+
+/*
+    Find the type of the input of the runnable a connection is to
+*/
+fn destination_type(tables: &CodeGenTables, connection: &Connection) -> Option<DataType> {
+    let &(destination_id, input_index) = tables.destination_routes.get(connection.to_io.route())?;
+    let inputs = tables.runnables.get(destination_id)?.get_inputs()?;
+    inputs.get(input_index).map(|input| input.full_datatype().clone())
+}
+
+#[cfg(test)]
+mod test {
+    use url::Url;
+    use generator::generate::CodeGenTables;
+    use model::connection::Connection;
+    use model::function::Function;
+    use model::io::IO;
+    use super::check_connection_types;
+
+    /*
+        A "Number" that passes through a "Json" flow input to a "String" input of a function
+        in the sub-flow
+    */
+    fn tables(input_type: &str) -> CodeGenTables {
+        let mut tables = CodeGenTables::new();
+        tables.runnables.push(Box::new(Function::new(
+            "Print".to_string(),
+            "print".to_string(),
+            Some(vec!(IO::new(&input_type.to_string(), &"/flow0/sub/print".to_string()))),
+            Some(vec!()),
+            Url::parse("file:///fake/file").unwrap(),
+            "/flow0/sub/print".to_string(),
+            None,
+            vec!(),
+            0)));
+        tables.destination_routes.insert("/flow0/sub/print".to_string(), (0, 0));
+        tables.collapsed_connections.push(Connection {
+            name: None,
+            from: "value/number".to_string(),
+            to: "process/sub/in".to_string(),
+            from_io: IO::new(&"Number".to_string(), &"/flow0/number".to_string()),
+            to_io: IO::new(&"Json".to_string(), &"/flow0/sub/print".to_string()),
+        });
+        tables
+    }
+
+    #[test]
+    fn destination_type_checked() {
+        let mut errors = vec!();
+        check_connection_types(&tables("Number"), &mut errors);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn mismatch_through_flow_detected() {
+        let mut errors = vec!();
+        check_connection_types(&tables("String"), &mut errors);
+        assert_eq!(errors, vec!("Type mismatch: '/flow0/number' of type 'Number' is connected to '/flow0/sub/print' of type 'String'"));
+    }
+}
@@ -1,21 +1,31 @@
 use model::flow::Flow;
 use super::gatherer;
 use super::connector;
+use super::checker;
 use generator::generate::CodeGenTables;
 
 /// Take a hierarchical flow definition in memory and compile it, generating code that implements
 /// the flow, including links to the flowrlib runtime library and library functions used in the
 /// flowstdlib standard library. It takes an optional bool dump option to dump to standard output
 /// some of the intermediate values and operations during the compilation process.
-pub fn compile(flow: &Flow) -> Result<CodeGenTables, String> {
+///
+/// All the errors found in the flow are returned together, rather than just the first one.
+pub fn compile(flow: &Flow) -> Result<CodeGenTables, Vec<String>> {
     let mut tables = CodeGenTables::new();
+    let mut errors = vec!();
 
     gatherer::add_entries(flow, &mut tables);
     gatherer::index_runnables(&mut tables.runnables);
     tables.collapsed_connections = connector::collapse_connections(&tables.connections);
     connector::routes_table(&mut tables);
-    connector::set_runnable_outputs(&mut tables)?;
-    connector::check_connections(&mut tables)?;
+    connector::initialize_inputs(&mut tables, &mut errors);
+    connector::set_runnable_outputs(&mut tables, &mut errors);
+    connector::check_connections(&mut tables, &mut errors);
+    checker::check_connection_types(&tables, &mut errors);
 
-    Ok(tables)
+    if errors.is_empty() {
+        Ok(tables)
+    } else {
+        Err(errors)
+    }
 }
\ No newline at end of file
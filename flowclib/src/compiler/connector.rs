@@ -1,11 +1,13 @@
 use model::route::Route;
-use model::route::Router;
 use model::route::HasRoute;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use generator::generate::CodeGenTables;
 use model::connection::Connection;
 use model::name::HasName;
+use model::shape::Shape;
+use flowrlib::process::is_error_route;
+use flowrlib::input::InputInitializer::Constant;
 
 /*
     Go through all connections, finding:
@@ -15,53 +17,78 @@ use model::name::HasName;
     Then add an output route to the source process's output routes vector
     (according to each runnable's output route in the original description plus each connection from
      that route, which could be to multiple destinations)
+
+    Errors found are added to 'errors', and the other connections are still built.
 */
-pub fn set_runnable_outputs(tables: &mut CodeGenTables) -> Result<(), String> {
+pub fn set_runnable_outputs(tables: &mut CodeGenTables, errors: &mut Vec<String>) {
     debug!("Building connections");
     for connection in &tables.collapsed_connections {
         if let Some((output_route, source_id)) = get_source(&tables.source_routes, &connection.from_io.route()) {
             if let Some(&(destination_id, destination_input_index)) = tables.destination_routes.get(connection.to_io.route()) {
                 let source_runnable = tables.runnables.get_mut(source_id).unwrap();
+                if let Err(e) = check_output_route(&tables.output_shapes, source_runnable.route(), &output_route) {
+                    errors.push(format!("Connection source '{}' cannot exist in the output of '{}': {}",
+                                        connection.from_io.route(), source_runnable.route(), e));
+                    continue;
+                }
                 debug!("Connection built: from '{}' to '{}'", &connection.from_io.route(), &connection.to_io.route());
                 debug!("Output Route: Route = '{}', destination_id = {}, destination_input_index = {})",
                        output_route.to_string(), destination_id, destination_input_index);
                 source_runnable.add_output_route((output_route.to_string(), destination_id, destination_input_index));
             } else {
-                return Err(format!("Connection destination '{}' not found", connection.to_io.route()));
+                errors.push(format!("Connection destination '{}' not found", connection.to_io.route()));
             }
         } else {
-            return Err(format!("Connection source '{}' not found", connection.from_io.route()));
+            errors.push(format!("Connection source '{}' not found", connection.from_io.route()));
         }
     }
     debug!("All connections built");
+}
 
-    Ok(())
+/*
+    Check the route (JSON pointer) that will be used to get the value to send from the output
+    of a runnable can exist in it's output. Errors are sent separately from the output, so routes
+    from the error output are not part of it.
+*/
+fn check_output_route(output_shapes: &HashMap<Route, Shape>, runnable_route: &Route, output_route: &Route)
+    -> Result<(), String> {
+    if is_error_route(output_route) {
+        return Ok(());
+    }
+
+    match output_shapes.get(runnable_route) {
+        Some(shape) => shape.part(output_route).map(|_| ()),
+        None => Ok(())
+    }
 }
 
 /*
-    find a runnable using the route to its output (removing the array index first to find outputs that are arrays)
-    return a tuple of the sub-route to use (possibly with array index included), and the runnable index
+    find a runnable using the route to its output, or to a part of it's output (an element of an
+    array or a field of a struct), by removing the last segment of the route until the route to an
+    output is found.
+    return a tuple of the sub-route to use (including the route to the part of the output), and
+    the runnable index
 */
-fn get_source(source_routes: &HashMap<Route, (Route, usize)>, from_route: &Route) -> Option<(Route, usize)> {
-    let (source_without_index, array_index, is_array_output) = Router::without_trailing_array_index(from_route);
-    let source = source_routes.get(&source_without_index.to_string());
+pub fn get_source(source_routes: &HashMap<Route, (Route, usize)>, from_route: &Route) -> Option<(Route, usize)> {
+    let mut source_route = from_route.clone();
+    let mut part_route = String::new();
 
-    if let Some(&(ref route, runnable_index)) = source {
-        if is_array_output {
+    loop {
+        if let Some(&(ref route, runnable_index)) = source_routes.get(&source_route) {
             if route.is_empty() {
-                return Some((format!("/{}", array_index), runnable_index));
+                return Some((part_route, runnable_index));
             } else {
-                return Some((format!("/{}/{}", route, array_index), runnable_index));
+                return Some((format!("/{}{}", route, part_route), runnable_index));
             }
-        } else {
-            if route.is_empty() {
-                return Some((route.to_string(), runnable_index));
-            } else {
-                return Some((format!("/{}", route.to_string()), runnable_index));
+        }
+
+        match source_route.rfind('/') {
+            Some(position) => {
+                part_route = format!("{}{}", &source_route[position..], part_route);
+                source_route.truncate(position);
             }
+            None => return None
         }
-    } else {
-        return None;
     }
 }
 
@@ -97,7 +124,7 @@ pub fn routes_table(tables: &mut CodeGenTables) {
     As a connection at a flow boundary can connect to multiple destinations, one
     original connection can branch to connect to multiple destinations.
 */
-fn find_destinations(from_route: &Route, connections: &Vec<Connection>) -> Vec<Route> {
+pub fn find_destinations(from_route: &Route, connections: &Vec<Connection>) -> Vec<Route> {
     let mut destinations = vec!();
 
     for connection in connections {
@@ -144,27 +171,66 @@ pub fn collapse_connections(original_connections: &Vec<Connection>) -> Vec<Conne
 }
 
 /*
-    Check for a series of potential problems in connections
+    Give the inputs that an initialized input of a sub-flow is connected to it's initializer, as
+    the sub-flow's input is not part of the collapsed connections. Then check that no input with a
+    constant value is also connected to, as the values sent to it would never be used. Errors
+    found are added to 'errors'.
+*/
+pub fn initialize_inputs(tables: &mut CodeGenTables, errors: &mut Vec<String>) {
+    for (route, initializer) in tables.flow_input_initializers.clone() {
+        let destinations = find_destinations(&route, &tables.connections);
+        if destinations.is_empty() {
+            errors.push(format!("Input '{}' is initialized but it is not connected to anything", route));
+        }
+
+        for destination in destinations {
+            if let Some(&(destination_id, input_number)) = tables.destination_routes.get(&destination) {
+                if let Err(e) = tables.runnables[destination_id].set_input_initializer(input_number, initializer.clone()) {
+                    errors.push(format!("Input '{}' cannot be initialized: {}", route, e));
+                }
+            }
+        }
+    }
+
+    for connection in &tables.collapsed_connections {
+        if let Some(&(destination_id, input_number)) = tables.destination_routes.get(connection.to_io.route()) {
+            let inputs = tables.runnables[destination_id].get_inputs().unwrap_or(vec!());
+            if let Some(&Some(Constant(_))) = inputs.get(input_number).map(|input| input.initializer()) {
+                errors.push(format!("Input '{}' has a constant value, so it cannot also be connected to from '{}'",
+                                    connection.to_io.route(), connection.from_io.route()));
+            }
+        }
+    }
+}
+
+/*
+    Check for a series of potential problems in connections, adding those found to 'errors'
 */
-pub fn check_connections(tables: &mut CodeGenTables) -> Result<(), String> {
+pub fn check_connections(tables: &mut CodeGenTables, errors: &mut Vec<String>) {
     for connection in &tables.collapsed_connections {
-        connection.check_for_loops("Collapsed Connections list")?;
+        if let Err(e) = connection.check_for_loops("Collapsed Connections list") {
+            errors.push(e);
+        }
     }
 
-    check_for_competing_inputs(tables)?;
+    check_for_competing_inputs(tables, errors);
 
-    remove_duplicates(&mut tables.collapsed_connections)
+    if let Err(e) = remove_duplicates(&mut tables.collapsed_connections) {
+        errors.push(e);
+    }
 }
 
 /*
-    Check for duplicate connections
+    Check for duplicate connections. Connections are compared by the routes they join, as
+    collapsed connections that were joined from the same connection to a flow input or output
+    have the same 'from' and 'to' but different destinations.
 */
 pub fn remove_duplicates(connections: &mut Vec<Connection>) -> Result<(), String> {
     let mut uniques = HashSet::<String>::new();
 
     // keep unique connections - dump duplicates
     connections.retain(|conn| {
-        let unique_key = format!("{}->{}", conn.from, conn.to);
+        let unique_key = format!("{}->{}", conn.from_io.route(), conn.to_io.route());
         uniques.insert(unique_key)
     });
 
@@ -176,7 +242,7 @@ pub fn remove_duplicates(connections: &mut Vec<Connection>) -> Result<(), String
     1) Two runnables have output connections to the same input, and one of them is a static value
     2) A single runnable has two output connections to the same destination route.
 */
-fn check_for_competing_inputs(tables: &CodeGenTables) -> Result<(), String> {
+fn check_for_competing_inputs(tables: &CodeGenTables, errors: &mut Vec<String>) {
     // HashMap where key is the Route of the input being sent to
     //               value is  a tuple of (sender_id, static_sender)
     // Use to determine when sending to a route if the same runnable is already sending to it
@@ -191,22 +257,18 @@ fn check_for_competing_inputs(tables: &CodeGenTables) -> Result<(), String> {
                     // this destination is being sent to already - if the existing sender or this sender are
                     // static then it's being used by two senders, at least one of which is static :-(
                     if other_sender_is_static_value || sender.is_static_value() {
-                        return Err(format!("The route '{}' is being sent to by a static value as well as other outputs, causing competition that will fail at run-time",
-                                           connection.to_io.route()));
-                    }
-
-                    // The same runnable is already sending to this route!
-                    if other_sender_id == sender_id {
-                        return Err(format!("The runnable #'{}' has multiple outputs sending to the route '{}'",
-                                           sender_id, connection.to_io.route()));
+                        errors.push(format!("The route '{}' is being sent to by a static value as well as other outputs, causing competition that will fail at run-time",
+                                            connection.to_io.route()));
+                    } else if other_sender_id == sender_id {
+                        // The same runnable is already sending to this route!
+                        errors.push(format!("The runnable #'{}' has multiple outputs sending to the route '{}'",
+                                            sender_id, connection.to_io.route()));
                     }
                 },
                 _ => {}
             }
         }
     }
-
-    Ok(())
 }
 
 #[cfg(test)]
@@ -218,6 +280,8 @@ mod test {
     use model::io::IO;
     use super::collapse_connections;
     use super::remove_duplicates;
+    use super::get_source;
+    use std::collections::HashMap;
 
     #[test]
     fn drop_useless_connections() {
@@ -299,7 +363,7 @@ mod test {
             from: "/r1".to_string(),
             to: "/r2".to_string(),
             from_io: IO::new(&DataType::from("String"), &Route::from("/r1")),
-            to_io: IO::new(&DataType::from("String"), &Route::from("r2")),
+            to_io: IO::new(&DataType::from("String"), &Route::from("/r2")),
         };
 
         let mut connections = vec!(first, second);
@@ -309,6 +373,28 @@ mod test {
         assert_eq!(connections.len(), 1);
     }
 
+    /*
+        Collapsed connections joined from the same connection to a flow input have the same
+        'from' and 'to', but as they go to different routes they are not duplicates
+    */
+    #[test]
+    fn keep_connections_to_different_routes() {
+        let first = Connection {
+            name: None,
+            from: "input/in".to_string(),
+            to: "process/sub/in".to_string(),
+            from_io: IO::new(&DataType::from("String"), &Route::from("/r1")),
+            to_io: IO::new(&DataType::from("String"), &Route::from("/sub/a")),
+        };
+
+        let mut second = first.clone();
+        second.to_io.set_route(Route::from("/sub/b"), false);
+
+        let mut connections = vec!(first, second);
+        remove_duplicates(&mut connections).unwrap();
+        assert_eq!(connections.len(), 2);
+    }
+
     /*
         This tests a connection into a sub-flow, that in the sub-flow branches with two
         connections to different elements in it.
@@ -421,4 +507,19 @@ mod test {
         let collapsed = collapse_connections(&connections);
         assert_eq!(collapsed.len(), 2);
     }
+
+    #[test]
+    fn source_of_part_of_an_output() {
+        let mut source_routes = HashMap::new();
+        source_routes.insert(Route::from("/flow/reverse"), (Route::from(""), 0));
+        source_routes.insert(Route::from("/flow/points"), (Route::from("points"), 1));
+
+        assert_eq!(get_source(&source_routes, &Route::from("/flow/reverse")),
+                   Some((Route::from(""), 0)));
+        assert_eq!(get_source(&source_routes, &Route::from("/flow/reverse/reversed")),
+                   Some((Route::from("/reversed"), 0)));
+        assert_eq!(get_source(&source_routes, &Route::from("/flow/points/1/x")),
+                   Some((Route::from("/points/1/x"), 1)));
+        assert_eq!(get_source(&source_routes, &Route::from("/other/output")), None);
+    }
 }
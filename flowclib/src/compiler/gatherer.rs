@@ -3,10 +3,16 @@ use model::runnable::Runnable;
 use model::process::Process::FlowProcess;
 use model::process::Process::FunctionProcess;
 use generator::generate::CodeGenTables;
+use model::shape::Shape;
+use model::route::HasRoute;
 
 /*
     This module is responsible for parsing the flow tree and gathering information into a set of
     flat tables that the compiler can use for code generation.
+
+    The shape of the output of each runnable is built while the struct types in scope where it
+    is used are known, and the inputs of sub-flows that are initialized are noted so the inputs
+    they are connected to can be initialized once connections are collapsed.
 */
 pub fn add_entries(flow: &Flow, tables: &mut CodeGenTables) {
     // Add Connections from this flow to the connections table
@@ -18,6 +24,8 @@ pub fn add_entries(flow: &Flow, tables: &mut CodeGenTables) {
     // Add Values from this flow to the table of runnables
     if let Some(ref values) = flow.values {
         for value in values {
+            tables.output_shapes.insert(value.route().clone(),
+                                        Shape::of_outputs(&value.get_outputs(), &flow.types));
             tables.runnables.push(Box::new(value.clone()));
         }
     }
@@ -28,9 +36,16 @@ pub fn add_entries(flow: &Flow, tables: &mut CodeGenTables) {
             match process_ref.process {
                 FlowProcess(ref flow) => {
                     add_entries(flow, tables); // recurse
+                    for input in flow.inputs.iter().flatten() {
+                        if let Some(ref initializer) = *input.initializer() {
+                            tables.flow_input_initializers.push((input.route().clone(), initializer.clone()));
+                        }
+                    }
                 }
                 FunctionProcess(ref function) => {
                     // Add Functions from this flow to the table of runnables
+                    tables.output_shapes.insert(function.route().clone(),
+                                                Shape::of_outputs(&function.get_outputs(), function.get_types()));
                     tables.runnables.push(Box::new(function.clone()));
                 }
             }
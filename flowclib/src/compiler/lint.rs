@@ -0,0 +1,232 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use generator::generate::CodeGenTables;
+use model::name::HasName;
+use model::route::HasRoute;
+use model::runnable::Runnable;
+use flowrlib::implementation::ERROR_OUTPUT;
+use flowrlib::process::is_error_route;
+use super::connector::find_destinations;
+use super::connector::get_source;
+
+pub use loader::error::Severity;
+
+/// A problem found in a compiled flow by `lint`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
+
+/// Look for parts of a compiled flow that are not connected up, and so will not do anything when
+/// the flow runs:
+///   - inputs of runnables that nothing is connected to, so the runnable can never run
+///   - runnables whose outputs are not connected to anything, so what they produce is not used
+///   - runnables that can never run, as nothing that can run sends to one of their inputs
+///   - connections to the input or output of a flow that go no further, so are dropped (for info)
+///
+/// The findings are returned in order of the runnables and connections they are about.
+pub fn lint(tables: &CodeGenTables) -> Vec<Finding> {
+    let mut findings = vec!();
+
+    let fed_inputs = fed_inputs(tables);
+    unconnected_inputs(tables, &fed_inputs, &mut findings);
+    unconnected_outputs(tables, &mut findings);
+    unreachable_runnables(tables, &fed_inputs, &mut findings);
+    dropped_connections(tables, &mut findings);
+
+    findings
+}
+
+fn warning(message: String) -> Finding {
+    Finding { severity: Severity::Warning, message }
+}
+
+fn info(message: String) -> Finding {
+    Finding { severity: Severity::Info, message }
+}
+
+/*
+    Describe an IO of a runnable by it's name, if it has one
+*/
+fn io_description(kind: &str, name: &str, runnable_route: &str) -> String {
+    if name.is_empty() {
+        format!("The {} of '{}'", kind.to_lowercase(), runnable_route)
+    } else {
+        format!("{} '{}' of '{}'", kind, name, runnable_route)
+    }
+}
+
+/*
+    An input of a runnable (runnable id and input number) that a connection is to, the id of the
+    runnable the connection is from and if it is from that runnable's error output
+*/
+struct FedInput {
+    destination: (usize, usize),
+    source_id: usize,
+    from_error: bool,
+}
+
+fn fed_inputs(tables: &CodeGenTables) -> Vec<FedInput> {
+    let mut fed = vec!();
+
+    for connection in &tables.collapsed_connections {
+        if let Some(&destination) = tables.destination_routes.get(connection.to_io.route()) {
+            if let Some((output_route, source_id)) = get_source(&tables.source_routes, connection.from_io.route()) {
+                fed.push(FedInput { destination, source_id, from_error: is_error_route(&output_route) });
+            }
+        }
+    }
+
+    fed
+}
+
+fn is_fed(fed_inputs: &[FedInput], destination: (usize, usize)) -> bool {
+    fed_inputs.iter().any(|fed| fed.destination == destination)
+}
+
+/*
+    A function can only run when all of it's inputs have a value, so if nothing is connected to
+    one of them it can never run. A value with an initial value, or an input that is initialized,
+    doesn't need to be connected to.
+*/
+fn unconnected_inputs(tables: &CodeGenTables, fed_inputs: &[FedInput], findings: &mut Vec<Finding>) {
+    for runnable in &tables.runnables {
+        if runnable.get_initial_value().is_some() {
+            continue;
+        }
+
+        for (index, input) in runnable.get_inputs().unwrap_or(vec!()).iter().enumerate() {
+            if input.initializer().is_none() && !is_fed(fed_inputs, (runnable.get_id(), index)) {
+                findings.push(warning(format!("{} is not connected to, so it can never run",
+                                              io_description("Input", input.name(), runnable.route()))));
+            }
+        }
+    }
+}
+
+/*
+    Find the outputs of each runnable that are connected from. The output used by a connection is
+    the named output that is the first part of the route it uses to get the value from the output,
+    or else the whole (unnamed) output.
+*/
+fn unconnected_outputs(tables: &CodeGenTables, findings: &mut Vec<Finding>) {
+    let mut used_outputs = HashSet::new();
+    for connection in &tables.collapsed_connections {
+        if let Some((output_route, source_id)) = get_source(&tables.source_routes, connection.from_io.route()) {
+            let first_part = output_route.split('/').nth(1).unwrap_or("");
+            let is_named_output = tables.runnables.get(source_id)
+                .and_then(|runnable| runnable.get_outputs())
+                .map(|outputs| outputs.iter().any(|output| !first_part.is_empty() && output.name() == first_part))
+                .unwrap_or(false);
+            let output_name = if is_named_output { first_part } else { "" };
+            used_outputs.insert((source_id, output_name.to_string()));
+        }
+    }
+
+    for runnable in &tables.runnables {
+        let outputs: Vec<_> = runnable.get_outputs().unwrap_or(vec!()).into_iter()
+            .filter(|output| output.name() != ERROR_OUTPUT).collect();
+        let id = runnable.get_id();
+        let used = |name: &str| used_outputs.contains(&(id, name.to_string()));
+
+        if outputs.is_empty() {
+            continue;
+        }
+
+        // If the whole output is used then all the parts of it are
+        if used("") {
+            continue;
+        }
+
+        if !outputs.iter().any(|output| used(output.name())) {
+            if runnable.get_type() == "Value" {
+                findings.push(warning(format!("Value '{}' is not connected from, so it is never read",
+                                              runnable.route())));
+            } else {
+                findings.push(warning(format!("No output of '{}' is connected, so what it produces is not used",
+                                              runnable.route())));
+            }
+            continue;
+        }
+
+        for output in &outputs {
+            if !output.name().is_empty() && !used(output.name()) {
+                findings.push(info(format!("{} is not connected",
+                                           io_description("Output", output.name(), runnable.route()))));
+            }
+        }
+    }
+}
+
+/*
+    Find the runnables that can produce an output: those with no inputs (other than initialized
+    ones) or with an initial value, and then repeatedly any with all of their other inputs
+    connected from a runnable that can produce an output, until no more are found. Runnables that
+    have all their inputs connected (an unconnected input is reported already) but cannot produce
+    an output can never run.
+
+    Connections from the error output are not counted, as they only send when a runnable fails.
+*/
+fn unreachable_runnables(tables: &CodeGenTables, fed_inputs: &[FedInput], findings: &mut Vec<Finding>) {
+    let mut can_run = HashSet::new();
+
+    loop {
+        let mut found = false;
+        for runnable in &tables.runnables {
+            let id = runnable.get_id();
+            if !can_run.contains(&id) && (runnable.get_initial_value().is_some() ||
+                sent_to_inputs(runnable).iter().all(|&index| fed_inputs.iter().any(|fed|
+                    fed.destination == (id, index) && !fed.from_error && can_run.contains(&fed.source_id)))) {
+                can_run.insert(id);
+                found = true;
+            }
+        }
+
+        if !found {
+            break;
+        }
+    }
+
+    for runnable in &tables.runnables {
+        let id = runnable.get_id();
+        if !can_run.contains(&id) && sent_to_inputs(runnable).iter().all(|&index| is_fed(fed_inputs, (id, index))) {
+            findings.push(warning(format!("'{}' can never run, as nothing that runs sends to all of its inputs",
+                                          runnable.route())));
+        }
+    }
+}
+
+/*
+    The numbers of the inputs of a runnable that need to be sent a value, as they are not
+    initialized
+*/
+fn sent_to_inputs(runnable: &Box<Runnable>) -> Vec<usize> {
+    runnable.get_inputs().unwrap_or(vec!()).iter().enumerate()
+        .filter(|&(_, input)| input.initializer().is_none())
+        .map(|(index, _)| index).collect()
+}
+
+/*
+    Connections to a flow's input or output that are not connected to anything on the other side
+    of it are dropped when connections are collapsed. Only those that start at a runnable are
+    reported, not each of the connections between flows along the way. If nothing else is
+    connected to the output it is reported as an unconnected output, so this is only for
+    information (e.g. an output of a sub-flow that is not used).
+*/
+fn dropped_connections(tables: &CodeGenTables, findings: &mut Vec<Finding>) {
+    for connection in &tables.connections {
+        if connection.to_io.flow_io() && !connection.from_io.flow_io()
+            && find_destinations(connection.to_io.route(), &tables.connections).is_empty() {
+            findings.push(info(format!("The connection from '{}' to '{}' goes no further, so it is dropped",
+                                          connection.from_io.route(), connection.to_io.route())));
+        }
+    }
+}
@@ -1,4 +1,7 @@
 //! Compile module that generates code from a loaded flow definition
 pub mod compile;
 mod connector;
-mod gatherer;
\ No newline at end of file
+mod gatherer;
+mod checker;
+pub mod lint;
+pub mod optimizer;
@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use serde_json::Value as JsonValue;
+
+use generator::generate::CodeGenTables;
+use model::name::HasName;
+use model::route::HasRoute;
+use model::runnable::Runnable;
+use flowrlib::implementation::ERROR_OUTPUT;
+use flowrlib::input::InputInitializer::Constant;
+use super::connector::get_source;
+use super::gatherer;
+
+/// Optimize the compiled tables of a flow, so that it does the same when it runs but with fewer
+/// processes to dispatch:
+///   - static values with an initial value, that nothing sends to, are replaced by constants on
+///     the inputs of the functions they are connected to
+///   - runnables whose outputs reach nothing are removed, along with the connections to them,
+///     unless they are impure functions
+///   - the remaining runnables are given new ids, in the same order as before
+///
+/// It should be used after the flow has been compiled and checked, as the runnables removed here
+/// are not reported on by the checks (e.g. lint).
+pub fn optimize(tables: &mut CodeGenTables) -> Result<(), String> {
+    let inlined = inline_static_values(tables)?;
+    let removed = remove_unused_runnables(tables);
+    renumber_runnables(tables);
+
+    info!("Optimizing inlined {} static values and removed {} runnables in total", inlined, removed);
+    Ok(())
+}
+
+/*
+    Replace each static value that can be by constants on the inputs it sends to. The value is
+    left with no output routes, so it is then removed as it's output reaches nothing.
+*/
+fn inline_static_values(tables: &mut CodeGenTables) -> Result<usize, String> {
+    let sent_to: HashSet<usize> = tables.runnables.iter()
+        .flat_map(|runnable| runnable.get_output_routes().iter().map(|&(_, destination_id, _)| destination_id))
+        .collect();
+    let mut inlined = 0;
+
+    for index in 0..tables.runnables.len() {
+        if let Some(constants) = inline_constants(&tables.runnables, index, &sent_to) {
+            for (destination_id, input_number, constant) in constants {
+                tables.runnables[destination_id].set_input_initializer(input_number, Constant(constant))?;
+            }
+            debug!("Static value '{}' inlined as a constant on the inputs it is connected to",
+                   tables.runnables[index].route());
+            tables.runnables[index].set_output_routes(vec!());
+            inlined += 1;
+        }
+    }
+
+    Ok(inlined)
+}
+
+/*
+    The constant values that a static value can be replaced by on the inputs of the runnables it
+    sends to (as destination id, input number and value), if it can be. It must have an initial
+    value that is never replaced, as nothing sends to it, and only send to functions, as the input
+    of a value cannot be a constant.
+*/
+fn inline_constants(runnables: &[Box<Runnable>], index: usize, sent_to: &HashSet<usize>)
+                    -> Option<Vec<(usize, usize, JsonValue)>> {
+    let value = &runnables[index];
+    if !value.is_static_value() || sent_to.contains(&value.get_id()) || value.get_output_routes().is_empty() {
+        return None;
+    }
+
+    let initial_value = value.get_initial_value()?;
+    let mut constants = vec!();
+    for &(ref output_route, destination_id, input_number) in value.get_output_routes() {
+        if runnables[destination_id].get_type() != "Function" {
+            return None;
+        }
+        constants.push((destination_id, input_number, initial_value.pointer(output_route)?.clone()));
+    }
+
+    Some(constants)
+}
+
+/*
+    Remove the runnables that have outputs but none of them are connected to anything, as running
+    them has no effect. Removing one can leave the runnables that send to it with outputs that reach
+    nothing, so repeat until no more are found. Functions with no outputs (e.g. one that prints),
+    or that are marked as impure (e.g. one that reads from stdin), are run for what they do, so
+    they are never removed.
+*/
+fn remove_unused_runnables(tables: &mut CodeGenTables) -> usize {
+    let mut removed = HashSet::new();
+
+    loop {
+        let unused: Vec<usize> = tables.runnables.iter()
+            .filter(|runnable| !removed.contains(&runnable.get_id()) && has_outputs(runnable)
+                && !runnable.is_impure() && runnable.get_output_routes().is_empty())
+            .map(|runnable| runnable.get_id()).collect();
+
+        if unused.is_empty() {
+            break;
+        }
+
+        for runnable in tables.runnables.iter_mut() {
+            let output_routes = runnable.get_output_routes().iter()
+                .filter(|&&(_, destination_id, _)| !unused.contains(&destination_id))
+                .cloned().collect();
+            runnable.set_output_routes(output_routes);
+        }
+
+        removed.extend(unused);
+    }
+
+    for runnable in tables.runnables.iter().filter(|runnable| removed.contains(&runnable.get_id())) {
+        debug!("Removed '{}' as it's outputs reach nothing", runnable.route());
+        tables.output_shapes.remove(runnable.route());
+    }
+    tables.runnables.retain(|runnable| !removed.contains(&runnable.get_id()));
+
+    // Remove the connections to and from the runnables removed, before the routes they use
+    let (source_routes, destination_routes) = (&tables.source_routes, &tables.destination_routes);
+    tables.collapsed_connections.retain(|connection| {
+        let source = get_source(source_routes, connection.from_io.route());
+        let destination = destination_routes.get(connection.to_io.route());
+        !source.map_or(false, |(_, source_id)| removed.contains(&source_id)) &&
+            !destination.map_or(false, |&(destination_id, _)| removed.contains(&destination_id))
+    });
+    tables.source_routes.retain(|_, &mut (_, id)| !removed.contains(&id));
+    tables.destination_routes.retain(|_, &mut (id, _)| !removed.contains(&id));
+
+    removed.len()
+}
+
+fn has_outputs(runnable: &Box<Runnable>) -> bool {
+    runnable.get_outputs().unwrap_or(vec!()).iter().any(|output| output.name() != ERROR_OUTPUT)
+}
+
+/*
+    Give the runnables left new ids in order, as they are the index of the runnable in the
+    table, and change the ids used to refer to them in output routes and the routes tables
+*/
+fn renumber_runnables(tables: &mut CodeGenTables) {
+    let new_ids: HashMap<usize, usize> = tables.runnables.iter().enumerate()
+        .map(|(new_id, runnable)| (runnable.get_id(), new_id)).collect();
+
+    for runnable in tables.runnables.iter_mut() {
+        let output_routes = runnable.get_output_routes().iter()
+            .map(|&(ref output_route, destination_id, input_number)|
+                (output_route.clone(), new_ids[&destination_id], input_number))
+            .collect();
+        runnable.set_output_routes(output_routes);
+    }
+    gatherer::index_runnables(&mut tables.runnables);
+
+    for &mut (_, ref mut id) in tables.source_routes.values_mut() {
+        *id = new_ids[id];
+    }
+    for &mut (ref mut id, _) in tables.destination_routes.values_mut() {
+        *id = new_ids[id];
+    }
+}
@@ -18,18 +18,19 @@ use model::process::Process::FlowProcess;
 ///
 /// use std::env;
 /// use url::Url;
+/// use flowrlib::errors::ProviderError;
 /// use flowrlib::provider::Provider;
 /// use flowclib::model::process::Process::FlowProcess;
 ///
 /// struct DummyProvider {}
 ///
 /// impl Provider for DummyProvider {
-///     fn resolve(&self, url: &Url) -> Result<(Url, Option<String>), String> {
+///     fn resolve(&self, url: &Url) -> Result<(Url, Option<String>), ProviderError> {
 ///         Ok((url.clone(), None))
 ///     }
 ///
-///     fn get(&self, url: &Url) -> Result<String, String> {
-///         Ok("flow = \"dummy\"\n[[input]]".to_string())
+///     fn get(&self, url: &Url) -> Result<Vec<u8>, ProviderError> {
+///         Ok("flow = \"dummy\"\n[[input]]".as_bytes().to_owned())
 ///     }
 /// }
 ///
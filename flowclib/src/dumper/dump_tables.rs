@@ -18,18 +18,19 @@ use ::dumper::dump_dot;
 ///
 /// use std::env;
 /// use url::Url;
+/// use flowrlib::errors::ProviderError;
 /// use flowrlib::provider::Provider;
 /// use flowclib::model::process::Process::FlowProcess;
 ///
 /// struct DummyProvider {}
 ///
 /// impl Provider for DummyProvider {
-///     fn resolve(&self, url: &Url) -> Result<(Url, Option<String>), String> {
+///     fn resolve(&self, url: &Url) -> Result<(Url, Option<String>), ProviderError> {
 ///         Ok((url.clone(), None))
 ///     }
 ///
-///     fn get(&self, url: &Url) -> Result<String, String> {
-///         Ok("flow = \"dummy\"\n[[input]]".to_string())
+///     fn get(&self, url: &Url) -> Result<Vec<u8>, ProviderError> {
+///         Ok("flow = \"dummy\"\n[[input]]".as_bytes().to_owned())
 ///     }
 /// }
 ///
@@ -96,19 +97,20 @@ pub fn dump_tables(tables: &CodeGenTables, output_dir: &PathBuf) -> io::Result<S
 ///
 /// use std::env;
 /// use url::Url;
+/// use flowrlib::errors::ProviderError;
 /// use flowrlib::provider::Provider;
 /// use flowclib::model::process::Process::FlowProcess;
 ///
 /// struct DummyProvider {}
 ///
 /// impl Provider for DummyProvider {
-///     fn resolve(&self, url: &Url) -> Result<(Url, Option<String>), String> {
+///     fn resolve(&self, url: &Url) -> Result<(Url, Option<String>), ProviderError> {
 ///         Ok((url.clone(), None))
 ///     }
 ///
 ///     // Return a flow definition for the content for the example
-///     fn get(&self, url: &Url) -> Result<String, String> {
-///         Ok("flow = \"dummy\"\n[[input]]".to_string())
+///     fn get(&self, url: &Url) -> Result<Vec<u8>, ProviderError> {
+///         Ok("flow = \"dummy\"\n[[input]]".as_bytes().to_owned())
 ///     }
 /// }
 ///
@@ -8,7 +8,9 @@ use model::runnable::Runnable;
 use model::flow::Flow;
 use model::route::Route;
 use model::connection::Connection;
+use model::shape::Shape;
 use flowrlib::manifest::Manifest;
+use flowrlib::input::InputInitializer;
 
 #[derive(Serialize)]
 pub struct CodeGenTables {
@@ -17,6 +19,10 @@ pub struct CodeGenTables {
     pub destination_routes: HashMap<Route, (usize, usize)>,
     pub collapsed_connections: Vec<Connection>,
     pub runnables: Vec<Box<Runnable>>,
+    #[serde(skip)]
+    pub output_shapes: HashMap<Route, Shape>,
+    #[serde(skip)]
+    pub flow_input_initializers: Vec<(Route, InputInitializer)>,
     pub libs: HashSet<String>
 }
 
@@ -38,6 +44,8 @@ impl CodeGenTables {
             destination_routes: HashMap::<Route, (usize, usize)>::new(),
             collapsed_connections: Vec::new(),
             runnables: Vec::new(),
+            output_shapes: HashMap::new(),
+            flow_input_initializers: Vec::new(),
             libs: HashSet::new()
         }
     }
@@ -82,7 +90,7 @@ fn runnable_to_process(runnable: &Box<Runnable>) -> flowrlib::process::Process {
     let initial_value = runnable.get_initial_value();
     let output_routes = runnable.get_output_routes().clone();
 
-    flowrlib::process::Process::new(
+    let mut process = flowrlib::process::Process::new(
         name,
         is_static,
         impl_path,
@@ -90,7 +98,17 @@ fn runnable_to_process(runnable: &Box<Runnable>) -> flowrlib::process::Process {
         id,
         initial_value,
         output_routes,
-    )
+    );
+
+    if let Some(inputs) = runnable.get_inputs() {
+        for (input_number, input) in inputs.iter().enumerate() {
+            if let Some(ref initializer) = *input.initializer() {
+                process.set_input_initializer(input_number, initializer.clone());
+            }
+        }
+    }
+
+    process
 }
 
 // TODO re-instate tests with new implementation
@@ -15,6 +15,8 @@ extern crate strfmt;
 extern crate url;
 extern crate yaml_rust;
 extern crate flowrlib;
+#[cfg(test)]
+extern crate tempdir;
 
 pub mod loader;
 pub mod dumper;
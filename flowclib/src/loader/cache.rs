@@ -0,0 +1,304 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use flowrlib::provider::Provider;
+use loader::loader_helper::get_loader;
+use model::flow::Flow;
+use model::process::Process;
+use model::process::Process::FlowProcess;
+use model::process::Process::FunctionProcess;
+use url::Url;
+
+/// A cache of the definitions of processes used while loading flows, so that the same definition
+/// is not fetched or parsed again when it is used again or has not changed.
+///
+/// Content from local files is always read again, as that is how a change to it is seen, but the
+/// process parsed from it is only parsed again if the hash of the content has changed. Content from
+/// anywhere else (e.g. over http) is only fetched once, and if the cache has a directory it is kept
+/// there so it is not fetched again by later uses of the cache either. It is never checked to see if
+/// it has changed where it was fetched from, so it is used until the cache is refreshed.
+pub struct Cache<'a> {
+    provider: &'a Provider,
+    directory: Option<PathBuf>,
+    resolved: RefCell<HashMap<Url, (Url, Option<String>)>>,
+    contents: RefCell<HashMap<Url, (u64, String)>>,
+    processes: RefCell<HashMap<Url, (u64, Process)>>,
+}
+
+impl<'a> Cache<'a> {
+    /// Create a cache of the content got from 'provider', that keeps content that is not from local
+    /// files in 'directory' (if given) between uses of the cache
+    pub fn new(provider: &'a Provider, directory: Option<PathBuf>) -> Self {
+        Cache {
+            provider,
+            directory,
+            resolved: RefCell::new(HashMap::new()),
+            contents: RefCell::new(HashMap::new()),
+            processes: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Resolve 'url' to where it's content can be found, using the provider. Urls that are not of
+    /// local files are only resolved once.
+    pub fn resolve(&self, url: &Url) -> Result<(Url, Option<String>), String> {
+        if let Some(resolved) = self.resolved.borrow().get(url) {
+            return Ok(resolved.clone());
+        }
+
+        let resolved = self.provider.resolve(url).map_err(|e| e.to_string())?;
+        if url.scheme() != "file" {
+            self.resolved.borrow_mut().insert(url.clone(), resolved.clone());
+        }
+        Ok(resolved)
+    }
+
+    /// Load the process defined at 'url' (that has already been resolved), before it is configured
+    /// for where it is used. The process is only parsed again if it's content has changed.
+    pub fn load(&self, url: &Url) -> Result<Process, String> {
+        let (hash, contents) = self.get(url)?;
+
+        if let Some(&(cached_hash, ref process)) = self.processes.borrow().get(url) {
+            if cached_hash == hash {
+                debug!("Using cached process loaded from '{}'", url);
+                return Ok(process.clone());
+            }
+        }
+
+        let process = get_loader(url)?.load_process(&contents)?;
+        self.processes.borrow_mut().insert(url.clone(), (hash, process.clone()));
+        Ok(process)
+    }
+
+    /// The content at 'url' that was got using the cache, if it has been
+    pub fn contents_of(&self, url: &Url) -> Option<String> {
+        self.contents.borrow().get(url).map(|&(_, ref contents)| contents.clone())
+    }
+
+    /// Forget all content that is not from local files, so that it is fetched again the next
+    /// time it is used
+    pub fn refresh(&self) -> Result<(), String> {
+        self.resolved.borrow_mut().clear();
+        self.contents.borrow_mut().retain(|url, _| url.scheme() == "file");
+        if let Some(ref directory) = self.directory {
+            if directory.exists() {
+                fs::remove_dir_all(directory).map_err(|e| e.to_string())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Calculate a hash of the content of all the definitions that 'flow' was loaded from and the
+    /// urls they were loaded from, that changes if any of them do
+    pub fn hash_of(&self, flow: &Flow) -> Result<u64, String> {
+        let sources: String = self.sources_of(flow)?.iter()
+            .map(|(url, content_hash)| format!("{} {:016x}\n", url, content_hash)).collect();
+        Ok(hash(sources.as_bytes()))
+    }
+
+    /// Find the urls of all the definitions that 'flow' was loaded from (the flow and all the
+    /// processes it uses, recursively, with library urls resolved) and the hash of their content
+    pub fn sources_of(&self, flow: &Flow) -> Result<BTreeMap<Url, u64>, String> {
+        let mut sources = BTreeMap::new();
+        self.add_sources(flow, &mut sources)?;
+        Ok(sources)
+    }
+
+    /// The paths of all the local files that content has been read from using the cache, including
+    /// those read while loading a flow that failed to load
+    pub fn local_files(&self) -> Vec<PathBuf> {
+        self.contents.borrow().keys().filter_map(|url| url.to_file_path().ok()).collect()
+    }
+
+    /*
+        Add the url and hash of the content of the definition of the flow and all the processes it
+        uses (recursively) to 'sources'
+    */
+    fn add_sources(&self, flow: &Flow, sources: &mut BTreeMap<Url, u64>) -> Result<(), String> {
+        sources.insert(flow.source_url.clone(), self.hash_of_content(&flow.source_url)?);
+
+        for process_ref in flow.process_refs.iter().flatten() {
+            match process_ref.process {
+                FlowProcess(ref subflow) => self.add_sources(subflow, sources)?,
+                FunctionProcess(ref function) => {
+                    let url = function.get_source_url();
+                    sources.insert(url.clone(), self.hash_of_content(url)?);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn hash_of_content(&self, url: &Url) -> Result<u64, String> {
+        self.contents.borrow().get(url).map(|&(hash, _)| hash)
+            .ok_or(format!("'{}' was not loaded using the cache", url))
+    }
+
+    /*
+        Get the content at 'url' and it's hash, from the cache if it's not from a local file and
+        has been fetched before, or else using the provider
+    */
+    fn get(&self, url: &Url) -> Result<(u64, String), String> {
+        let is_file = url.scheme() == "file";
+
+        if !is_file {
+            if let Some(cached) = self.contents.borrow().get(url) {
+                return Ok(cached.clone());
+            }
+        }
+
+        let content = match self.read_from_directory(url) {
+            Some(content) => content,
+            None => {
+                let content = self.provider.get(url).map_err(|e| e.to_string())?;
+                if !is_file {
+                    self.write_to_directory(url, &content);
+                }
+                content
+            }
+        };
+
+        let contents = String::from_utf8(content)
+            .map_err(|e| format!("Content of '{}' is not valid UTF-8 ({})", url, e))?;
+        let cached = (hash(contents.as_bytes()), contents);
+        self.contents.borrow_mut().insert(url.clone(), cached.clone());
+        Ok(cached)
+    }
+
+    /*
+        The file in the cache's directory that the content at 'url' is kept in, if it's not from a
+        local file and the cache has a directory
+    */
+    fn cached_file(&self, url: &Url) -> Option<PathBuf> {
+        match self.directory {
+            Some(ref directory) if url.scheme() != "file" =>
+                Some(directory.join(format!("{:016x}", hash(url.as_str().as_bytes())))),
+            _ => None
+        }
+    }
+
+    fn read_from_directory(&self, url: &Url) -> Option<Vec<u8>> {
+        let file = self.cached_file(url)?;
+        let content = fs::read(&file).ok()?;
+        debug!("Using content of '{}' cached in '{}'", url, file.display());
+        Some(content)
+    }
+
+    /*
+        Keep the content at 'url' in the cache's directory. Failing to do so is not an error, as it
+        will just be fetched again the next time.
+    */
+    fn write_to_directory(&self, url: &Url, content: &[u8]) {
+        if let Some(file) = self.cached_file(url) {
+            let written = file.parent().map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::write(&file, content));
+            if let Err(e) = written {
+                info!("Could not cache content of '{}' in '{}' ({})", url, file.display(), e);
+            }
+        }
+    }
+}
+
+/// Calculate the hash of some content, as used by the cache to tell when it has changed. The hash
+/// is kept between runs, so it is calculated the same way by every build (using 64 bit FNV-1a).
+pub fn hash(content: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    content.iter().fold(OFFSET_BASIS, |hash, &byte| (hash ^ u64::from(byte)).wrapping_mul(PRIME))
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::Cell;
+    use std::collections::HashMap;
+    use flowrlib::errors::ProviderError;
+    use flowrlib::provider::Provider;
+    use tempdir::TempDir;
+    use url::Url;
+    use model::process::Process::FunctionProcess;
+    use model::name::HasName;
+    use super::Cache;
+    use super::hash;
+
+    struct CountingProvider {
+        contents: HashMap<String, String>,
+        gets: Cell<usize>,
+    }
+
+    impl CountingProvider {
+        fn new(url: &str, content: &str) -> Self {
+            let mut contents = HashMap::new();
+            contents.insert(url.to_string(), content.to_string());
+            CountingProvider { contents, gets: Cell::new(0) }
+        }
+    }
+
+    impl Provider for CountingProvider {
+        fn resolve(&self, url: &Url) -> Result<(Url, Option<String>), ProviderError> {
+            Ok((url.clone(), None))
+        }
+
+        fn get(&self, url: &Url) -> Result<Vec<u8>, ProviderError> {
+            self.gets.set(self.gets.get() + 1);
+            self.contents.get(url.as_str()).map(|content| content.as_bytes().to_vec())
+                .ok_or(ProviderError::NotFound { url: url.to_string() })
+        }
+    }
+
+    const FUNCTION: &str = "function = 'print'\n[[input]]\ntype = 'String'";
+
+    #[test]
+    fn remote_content_fetched_once() {
+        let url = Url::parse("http://test.com/print.toml").unwrap();
+        let provider = CountingProvider::new(url.as_str(), FUNCTION);
+        let cache = Cache::new(&provider, None);
+
+        for _ in 0..2 {
+            match cache.load(&url).unwrap() {
+                FunctionProcess(ref function) => assert_eq!(function.name(), "print"),
+                _ => panic!("Process loaded was not a function")
+            }
+        }
+        assert_eq!(provider.gets.get(), 1);
+
+        cache.refresh().unwrap();
+        cache.load(&url).unwrap();
+        assert_eq!(provider.gets.get(), 2);
+    }
+
+    #[test]
+    fn local_content_read_each_time() {
+        let url = Url::parse("file:///flow/print.toml").unwrap();
+        let provider = CountingProvider::new(url.as_str(), FUNCTION);
+        let cache = Cache::new(&provider, None);
+
+        cache.load(&url).unwrap();
+        cache.load(&url).unwrap();
+        assert_eq!(provider.gets.get(), 2);
+    }
+
+    #[test]
+    fn remote_content_kept_in_directory() {
+        let directory = TempDir::new("cache").unwrap();
+        let url = Url::parse("http://test.com/print.toml").unwrap();
+
+        let provider = CountingProvider::new(url.as_str(), FUNCTION);
+        Cache::new(&provider, Some(directory.path().to_path_buf())).load(&url).unwrap();
+        assert_eq!(provider.gets.get(), 1);
+
+        let later_provider = CountingProvider::new(url.as_str(), FUNCTION);
+        Cache::new(&later_provider, Some(directory.path().to_path_buf())).load(&url).unwrap();
+        assert_eq!(later_provider.gets.get(), 0);
+    }
+
+    #[test]
+    fn hash_is_the_same_in_every_build() {
+        assert_eq!(hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(hash(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+}
@@ -0,0 +1,412 @@
+use std::fmt;
+
+use loader::cache::Cache;
+use model::flow::Flow;
+use model::name::HasName;
+use model::process::Process::FlowProcess;
+use model::span;
+use model::span::Span;
+use model::span::Spans;
+use url::Url;
+
+// Where an error is located when where it is in a definition is not known
+const START: Span = Span { line: 1, column: 1, length: 0 };
+
+/// How serious a problem found in a flow (by `lint`, or loading or compiling it) is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Something that may be intended, but is worth knowing about (e.g. an unused output)
+    Info,
+    /// Something that means part of the flow will not do anything when it runs (e.g. a function
+    /// with an input that nothing is connected to, so it can never run)
+    Warning,
+    /// Something that means the flow cannot be loaded or compiled (e.g. a connection to an input
+    /// that does not exist)
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// Where in the definition of a flow or function an error was found
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location {
+    /// The url the definition was loaded from
+    pub url: Url,
+    /// The line (counting from 1) the entry the error is about is on
+    pub line: usize,
+    /// The column (counting from 1, in characters) the entry starts at
+    pub column: usize,
+    /// How many characters of the line the entry covers
+    pub length: usize,
+    /// The text of the line, to show where the error is
+    pub source_line: String,
+}
+
+/// A problem found while loading or compiling a flow, with how serious it is and where it was found
+/// in the definitions the flow was loaded from, if that is known. Most are errors, but warnings
+/// found by `lint` can be located in the same way.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceError {
+    pub severity: Severity,
+    pub message: String,
+    pub location: Option<Location>,
+}
+
+/// An error about an entry in the definition of a flow or function, given by the path to it (e.g.
+/// "connection.1.to" for the 'to' of the second connection), so it can be located where the parser
+/// found the entry when the definition was loaded
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntryError {
+    pub entry: String,
+    pub message: String,
+}
+
+impl EntryError {
+    pub fn new(entry: String, message: String) -> Self {
+        EntryError { entry, message }
+    }
+}
+
+impl SourceError {
+    /// An error whose location is not known
+    pub fn new(message: String) -> Self {
+        SourceError { severity: Severity::Error, message, location: None }
+    }
+
+    /// The same problem, with a different severity (e.g. a warning)
+    pub fn with_severity(self, severity: Severity) -> Self {
+        SourceError { severity, ..self }
+    }
+
+    /// An error about the definition loaded from 'url', whose text is 'contents'.
+    ///
+    /// It is located using a position given in the message (as errors parsing the definition do),
+    /// or else at the start of the definition.
+    pub fn in_definition(message: String, url: &Url, contents: &str) -> Self {
+        let (message, span) = match split_position(&message) {
+            Some((message, line, column)) => (message, Span { line, column, length: 1 }),
+            None => (message, START)
+        };
+
+        SourceError { location: Some(location(url, contents, span)), ..SourceError::new(message) }
+    }
+
+    /// An error about an entry in the definition loaded from 'url', located where the entry was
+    /// found when the definition was parsed ('spans')
+    pub fn at_entry(error: EntryError, url: &Url, spans: &Spans, cache: &Cache) -> Self {
+        SourceError::new(error.message).or_at(url, spans, &error.entry, cache)
+    }
+
+    /// An error found compiling 'flow', that is about the routes quoted in the message (e.g.
+    /// "/context/add/i1"). It is located in the definition of the flow that the route is in, where
+    /// the route is used in a connection or else where the process it is part of is named.
+    pub fn in_flow(message: String, flow: &Flow, cache: &Cache) -> Self {
+        for route in quoted(&message).into_iter().filter(|quoted| quoted.starts_with('/')) {
+            if let Some(location) = locate_route(route, flow, cache) {
+                return SourceError { location: Some(location), ..SourceError::new(message) };
+            }
+        }
+
+        SourceError::new(message)
+    }
+
+    /// Locate this error in the definition loaded from 'url' (e.g. the flow that references the
+    /// process it is about) if it's location is not known yet
+    pub fn or_in_definition(self, url: &Url, cache: &Cache) -> Self {
+        match (&self.location, cache.contents_of(url)) {
+            (&None, Some(contents)) =>
+                SourceError::in_definition(self.message, url, &contents).with_severity(self.severity),
+            _ => self
+        }
+    }
+
+    /// Locate this error at 'entry' (e.g. "process.0.source", the source of a process reference)
+    /// in the definition loaded from 'url', whose values were found at 'spans', if it's location
+    /// is not known yet. If the entry was not found it is located at the start of the definition.
+    pub fn or_at(self, url: &Url, spans: &Spans, entry: &str, cache: &Cache) -> Self {
+        match (&self.location, cache.contents_of(url)) {
+            (&None, Some(contents)) => {
+                let span = span::find(spans, entry).unwrap_or(START);
+                SourceError { location: Some(location(url, &contents, span)), ..self }
+            }
+            _ => self
+        }
+    }
+}
+
+impl From<String> for SourceError {
+    fn from(message: String) -> Self {
+        SourceError::new(message)
+    }
+}
+
+/// Show how serious the problem is and what it is, and if it's location is known, where it is and
+/// the line it is on with the entry it is about marked. Local files are shown by their path.
+impl fmt::Display for SourceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)?;
+
+        if let Some(ref location) = self.location {
+            let source = match location.url.to_file_path() {
+                Ok(path) => path.display().to_string(),
+                Err(_) => location.url.to_string()
+            };
+            let number = location.line.to_string();
+            let margin = " ".repeat(number.len());
+
+            write!(f, "\n{}--> {}:{}:{}", margin, source, location.line, location.column)?;
+            write!(f, "\n{} |", margin)?;
+            write!(f, "\n{} | {}", number, location.source_line)?;
+            write!(f, "\n{} | {}{}", margin, " ".repeat(location.column - 1), "^".repeat(location.length.max(1)))?;
+        }
+
+        Ok(())
+    }
+}
+
+fn location(url: &Url, contents: &str, span: Span) -> Location {
+    let source_line = contents.lines().nth(span.line - 1).unwrap_or("").to_string();
+    Location { url: url.clone(), line: span.line, column: span.column, length: span.length, source_line }
+}
+
+/*
+    Split the position given at the end of an error message from parsing a definition, of the
+    form " at line N column M" (or just " at line N"), from the rest of the message
+*/
+fn split_position(message: &str) -> Option<(String, usize, usize)> {
+    let start = message.rfind(" at line ")?;
+    let mut numbers = message[start + " at line ".len()..].split(" column ");
+    let line = numbers.next()?.trim().parse::<usize>().ok()?;
+    let column = match numbers.next() {
+        Some(column) => column.trim().parse::<usize>().ok()?,
+        None => 1
+    };
+
+    if line == 0 || column == 0 {
+        return None;
+    }
+    Some((message[..start].to_string(), line, column))
+}
+
+/*
+    The parts of a message that are quoted, with single quotes or backticks, in order
+*/
+fn quoted(message: &str) -> Vec<&str> {
+    let mut parts = vec!();
+    for quote in &['\'', '`'] {
+        parts.extend(message.split(*quote).skip(1).step_by(2).filter(|part| !part.is_empty()));
+    }
+    parts
+}
+
+/*
+    Find where a route is used in the definition of the flow it is in. Sub-flows are searched
+    first, as the route is in the most deeply nested flow whose route it starts with.
+*/
+fn locate_route(route: &str, flow: &Flow, cache: &Cache) -> Option<Location> {
+    if flow.route.is_empty() || !route.starts_with(flow.route.as_str()) {
+        return None;
+    }
+    let relative = &route[flow.route.len()..];
+    if !relative.starts_with('/') {
+        return None;
+    }
+
+    for process_ref in flow.process_refs.iter().flatten() {
+        if let FlowProcess(ref subflow) = process_ref.process {
+            if let Some(location) = locate_route(route, subflow, cache) {
+                return Some(location);
+            }
+        }
+    }
+
+    let span = route_entries(&relative[1..], flow).iter()
+        .filter_map(|entry| span::find(&flow.spans, entry)).next()?;
+    let contents = cache.contents_of(&flow.source_url)?;
+    Some(location(&flow.source_url, &contents, span))
+}
+
+/*
+    The entries in the definition of a flow that a route within it (e.g. "add/i1" for the route
+    "/context/add/i1") could be located at, best first: where the process or value is named if the
+    route is to it, or else a connection to or from it (e.g. "process/add/i1"), or else where the
+    process or value it is part of is named or defined.
+*/
+fn route_entries(relative: &str, flow: &Flow) -> Vec<String> {
+    let name = relative.split('/').next().unwrap_or("");
+    let mut named = vec!();
+    if let Some(index) = flow.process_refs.iter().flatten().position(|process_ref| process_ref.alias() == name) {
+        named.push(format!("process.{}.alias", index));
+        named.push(format!("process.{}", index));
+    }
+    if let Some(index) = flow.values.iter().flatten().position(|value| value.name() == name) {
+        named.push(format!("value.{}.name", index));
+    }
+
+    // A connection gives the kind of thing it is to or from (e.g. "process") before the route
+    let used = |descriptor: &str| descriptor.splitn(2, '/').nth(1) == Some(relative);
+    let mut connections = vec!();
+    for (index, connection) in flow.connections.iter().flatten().enumerate() {
+        if used(&connection.from) {
+            connections.push(format!("connection.{}.from", index));
+        }
+        if used(&connection.to) {
+            connections.push(format!("connection.{}.to", index));
+        }
+    }
+
+    if relative.contains('/') {
+        connections.extend(named);
+        connections
+    } else {
+        named.extend(connections);
+        named
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use flowrlib::errors::ProviderError;
+    use flowrlib::provider::Provider;
+    use url::Url;
+
+    use loader::cache::Cache;
+    use model::process::Process::FlowProcess;
+    use super::EntryError;
+    use super::SourceError;
+    use super::split_position;
+
+    const FLOW: &str = "flow = 'test'
+
+[[value]]
+name = 'one'
+type = 'Number'
+
+[[process]]
+alias = 'add'
+source = 'lib://flowstdlib/math/add'
+
+[[connection]]
+from = 'value/one'
+to = \"process/add/i2\"
+";
+
+    struct FlowProvider;
+
+    impl Provider for FlowProvider {
+        fn resolve(&self, url: &Url) -> Result<(Url, Option<String>), ProviderError> {
+            Ok((url.clone(), None))
+        }
+
+        fn get(&self, _url: &Url) -> Result<Vec<u8>, ProviderError> {
+            Ok(FLOW.as_bytes().to_vec())
+        }
+    }
+
+    fn url() -> Url {
+        Url::parse("file:///flows/test.toml").unwrap()
+    }
+
+    /*
+        Locate an error about 'entry' in FLOW, as loaded by a cache
+    */
+    fn at_entry(entry: &str, message: &str) -> SourceError {
+        let provider = FlowProvider;
+        let cache = Cache::new(&provider, None);
+        match cache.load(&url()).unwrap() {
+            FlowProcess(ref flow) =>
+                SourceError::at_entry(EntryError::new(entry.to_string(), message.to_string()), &url(), &flow.spans, &cache),
+            _ => panic!("Expected a flow")
+        }
+    }
+
+    /*
+        Locate an error found compiling FLOW, loaded by a cache, when it is used with the alias
+        "context"
+    */
+    fn in_flow(message: &str) -> SourceError {
+        let provider = FlowProvider;
+        let cache = Cache::new(&provider, None);
+        match cache.load(&url()).unwrap() {
+            FlowProcess(mut flow) => {
+                flow.route = "/context".to_string();
+                flow.source_url = url();
+                SourceError::in_flow(message.to_string(), &flow, &cache)
+            }
+            _ => panic!("Expected a flow")
+        }
+    }
+
+    #[test]
+    fn position_in_message_used() {
+        assert_eq!(split_position("expected a value at line 3 column 7"),
+                   Some(("expected a value".to_string(), 3, 7)));
+        assert_eq!(split_position("expected a value at line 3"), Some(("expected a value".to_string(), 3, 1)));
+        assert_eq!(split_position("no position given"), None);
+
+        let error = SourceError::in_definition("expected an equals at line 8 column 3".to_string(), &url(), FLOW);
+        let location = error.location.unwrap();
+        assert_eq!((location.line, location.column), (8, 3));
+        assert_eq!(location.source_line, "alias = 'add'");
+        assert_eq!(error.message, "expected an equals");
+    }
+
+    #[test]
+    fn entry_located_where_parsed() {
+        let error = at_entry("connection.0.to", "Did not find connection destination: 'process/add/i2'");
+        let location = error.location.unwrap();
+        assert_eq!((location.line, location.column, location.length), (13, 7, 14));
+    }
+
+    #[test]
+    fn entry_located_at_first_value_in_it() {
+        let error = at_entry("process.0", "Process 'add' is not valid");
+        let location = error.location.unwrap();
+        assert_eq!((location.line, location.column, location.length), (8, 10, 3));
+    }
+
+    #[test]
+    fn entry_not_found_at_start() {
+        let error = at_entry("connection.3", "A connection that is not in the flow");
+        let location = error.location.unwrap();
+        assert_eq!((location.line, location.column), (1, 1));
+    }
+
+    #[test]
+    fn route_to_process_located_where_process_named() {
+        let error = in_flow("Process '/context/add' can never run");
+        let location = error.location.unwrap();
+        assert_eq!((location.line, location.column, location.length), (8, 10, 3));
+    }
+
+    #[test]
+    fn route_located_where_used_in_connection() {
+        let error = in_flow("Input '/context/add/i2' has more than one connection to it");
+        let location = error.location.unwrap();
+        assert_eq!((location.line, location.column, location.length), (13, 7, 14));
+    }
+
+    #[test]
+    fn route_in_other_flow_not_located() {
+        assert_eq!(in_flow("Process '/other/add' can never run").location, None);
+        assert_eq!(in_flow("Process '/contextual/add' can never run").location, None);
+    }
+
+    #[test]
+    fn error_shown_with_line_marked() {
+        let error = at_entry("value.0.name", "Name 'one' is used twice");
+        assert_eq!(error.to_string(), "error: Name 'one' is used twice
+ --> /flows/test.toml:4:9
+  |
+4 | name = 'one'
+  |         ^^^");
+    }
+}
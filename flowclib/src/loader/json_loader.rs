@@ -0,0 +1,183 @@
+use loader::loader::Loader;
+use model::process::Process;
+use model::span::Span;
+use model::span::Spans;
+use serde_json;
+
+pub struct FlowJsonLoader;
+
+impl Loader for FlowJsonLoader {
+    fn load_process(&self, contents: &str) -> Result<Process, String> {
+        let mut process: Process = serde_json::from_str(contents).map_err(|e| format!("{}", e))?;
+        process.set_spans(spans(contents));
+        Ok(process)
+    }
+}
+
+/*
+    Find where the values in a definition are, so errors in it can be located. serde_json does not
+    give the position of values, so the definition (that it has already parsed) is scanned for them.
+*/
+fn spans(contents: &str) -> Spans {
+    let mut spans = Spans::new();
+    add_spans(contents, &mut 0, &mut vec!(), &mut spans);
+    spans
+}
+
+/*
+    Add the spans of the value that starts at (or after whitespace following) 'offset' in 'contents',
+    that is at 'path', and of the values in it if it is an object or array. 'offset' is moved past it.
+*/
+fn add_spans(contents: &str, offset: &mut usize, path: &mut Vec<String>, spans: &mut Spans) {
+    skip_whitespace(contents, offset);
+    let bytes = contents.as_bytes();
+    match bytes.get(*offset) {
+        Some(&b'{') | Some(&b'[') => {
+            let is_object = bytes[*offset] == b'{';
+            *offset += 1;
+            let mut index = 0;
+            loop {
+                skip_whitespace(contents, offset);
+                match bytes.get(*offset) {
+                    Some(&b'}') | Some(&b']') | None => break,
+                    Some(&b',') => {
+                        *offset += 1;
+                        continue;
+                    }
+                    _ => {}
+                }
+
+                if is_object {
+                    let start = *offset + 1;
+                    skip_string(contents, offset);
+                    path.push(contents[start..*offset - 1].to_string());
+                    skip_whitespace(contents, offset);
+                    *offset += 1; // the ':' after the key
+                } else {
+                    path.push(index.to_string());
+                    index += 1;
+                }
+                add_spans(contents, offset, path, spans);
+                path.pop();
+            }
+            *offset += 1;
+        }
+        Some(_) => {
+            let start = *offset;
+            if bytes[start] == b'"' {
+                skip_string(contents, offset);
+            } else {
+                *offset += contents[start..].find(|c: char| c == ',' || c == '}' || c == ']' || c.is_whitespace())
+                    .unwrap_or(contents.len() - start);
+            }
+            spans.insert(path.join("."), Span::of(contents, start, *offset));
+        }
+        None => {}
+    }
+}
+
+fn skip_whitespace(contents: &str, offset: &mut usize) {
+    if let Some(rest) = contents.get(*offset..) {
+        *offset += rest.len() - rest.trim_start().len();
+    }
+}
+
+// Move 'offset' from the opening quote of a string to after the closing quote
+fn skip_string(contents: &str, offset: &mut usize) {
+    let bytes = contents.as_bytes();
+    *offset += 1;
+    while *offset < bytes.len() && bytes[*offset] != b'"' {
+        *offset += if bytes[*offset] == b'\\' { 2 } else { 1 };
+    }
+    *offset += 1;
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json;
+    use toml;
+
+    use loader::loader::Loader;
+    use loader::loader_helper::test::sample_definitions;
+    use loader::toml_loader::FlowTomelLoader;
+    use model::process::Process::FlowProcess;
+    use model::process::Process::FunctionProcess;
+    use model::span::Span;
+    use model::span::Spans;
+
+    use super::FlowJsonLoader;
+
+    #[test]
+    fn simple_context_loads() {
+        let flow_description = r#"{
+            "flow": "hello-world-simple-json",
+            "value": [{ "name": "message", "type": "String", "init": "Hello World!" }],
+            "process": [{ "alias": "print", "source": "terminal.json" }],
+            "connection": [{ "name": "message", "from": "value/message", "to": "function/print/stdout" }]
+        }"#;
+
+        match FlowJsonLoader.load_process(flow_description).unwrap() {
+            FlowProcess(flow) => {
+                assert_eq!(flow.name, "hello-world-simple-json");
+                assert_eq!(flow.process_refs.unwrap()[0].source, "terminal.json");
+            }
+            _ => panic!("Expected a flow")
+        }
+    }
+
+    #[test]
+    fn spans_of_values_recorded() {
+        let flow_description = r#"{
+    "flow": "test",
+    "connection": [{ "from": "input/number", "to": "process/add/i1" }]
+}"#;
+
+        match FlowJsonLoader.load_process(flow_description).unwrap() {
+            FlowProcess(flow) => {
+                assert_eq!(flow.spans.get("flow"), Some(&Span { line: 2, column: 14, length: 4 }));
+                assert_eq!(flow.spans.get("connection.0.from"), Some(&Span { line: 3, column: 31, length: 12 }));
+                assert_eq!(flow.spans.get("connection.0.to"), Some(&Span { line: 3, column: 53, length: 14 }));
+            }
+            _ => panic!("Expected a flow")
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn flow_errors_on_unknown_fields() {
+        FlowJsonLoader.load_process(r#"{ "flow": "test", "foo": true }"#).unwrap();
+    }
+
+    #[test]
+    fn function_parses() {
+        let function_definition = r#"{
+            "function": "stdout",
+            "input": [{ "name": "stdout", "type": "String" }]
+        }"#;
+
+        match FlowJsonLoader.load_process(function_definition).unwrap() {
+            FunctionProcess(_) => {}
+            _ => panic!("Expected a function")
+        }
+    }
+
+    /*
+        Every definition in the samples, when written in JSON, loads to the same model as the
+        TOML definition (apart from where the values are in it)
+    */
+    #[test]
+    fn samples_load_from_json() {
+        for (file, contents) in sample_definitions() {
+            let mut toml_process = FlowTomelLoader.load_process(&contents).unwrap();
+            toml_process.set_spans(Spans::new());
+
+            let json = serde_json::to_string(&toml::from_str::<toml::Value>(&contents).unwrap()).unwrap();
+            let mut json_process = FlowJsonLoader.load_process(&json)
+                .map_err(|e| format!("{}: {}\n{}", file.display(), e, json)).unwrap();
+            json_process.set_spans(Spans::new());
+
+            assert_eq!(format!("{:?}", json_process), format!("{:?}", toml_process),
+                       "{} loads differently from JSON", file.display());
+        }
+    }
+}
@@ -8,7 +8,17 @@ use model::name::HasName;
 use model::route::Route;
 use model::route::HasRoute;
 use model::route::SetRoute;
-use loader::loader_helper::get_loader;
+use model::datatype::TypeCheck;
+use model::struct_type::StructType;
+use model::process_reference::ProcessReference;
+use model::parameter;
+use flowrlib::input::InputInitializer;
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use model::runnable::Runnable;
+use loader::cache::Cache;
+use loader::error::EntryError;
+use loader::error::SourceError;
 use flowrlib::provider::Provider;
 use model::process::Process::FlowProcess;
 use model::process::Process::FunctionProcess;
@@ -16,6 +26,8 @@ use std::mem::replace;
 
 use url::Url;
 
+pub use loader::loader_helper::DEFINITION_EXTENSIONS;
+
 // Any loader has to implement these methods
 pub trait Loader {
     fn load_process(&self, contents: &str) -> Result<Process, String>;
@@ -27,8 +39,8 @@ pub trait Validate {
 
 /// load a process definition from `url`, recursively loading all sub-processes referenced.
 ///
-/// The return value is a `Result` containing the `Process`, or a `String`
-/// describing the error found while loading.
+/// The return value is a `Result` containing the `Process`, or the errors found while loading,
+/// with where they were found in the definitions loaded (if that is known).
 ///
 /// # Example
 /// ```
@@ -36,6 +48,7 @@ pub trait Validate {
 /// extern crate flowclib;
 /// extern crate flowrlib;
 ///
+/// use flowrlib::errors::ProviderError;
 /// use flowrlib::provider::Provider;
 /// use std::env;
 /// use url::Url;
@@ -44,14 +57,14 @@ pub trait Validate {
 /// struct DummyProvider {};
 ///
 /// impl Provider for DummyProvider {
-///     fn resolve(&self, url: &Url) -> Result<(Url, Option<String>), String> {
+///     fn resolve(&self, url: &Url) -> Result<(Url, Option<String>), ProviderError> {
 ///        // Just fake the url resolution in this example
 ///        Ok((url.clone(), None))
 ///     }
 ///
-///    fn get(&self, url: &Url) -> Result<String, String> {
+///    fn get(&self, url: &Url) -> Result<Vec<u8>, ProviderError> {
 ///        // Return the simplest flow definition possible
-///        Ok("flow = \"test\"".to_string())
+///        Ok("flow = \"test\"".as_bytes().to_owned())
 ///     }
 /// }
 ///
@@ -64,38 +77,118 @@ pub trait Validate {
 /// ```
 // TODO Make this more ergonomic for clients and tests using some form of Intro trait for routes and alias
 // https://hermanradtke.com/2015/05/06/creating-a-rust-function-that-accepts-string-or-str.html
-pub fn load_process(parent_route: &Route, alias: &Name, url: &Url, provider: &Provider) -> Result<Process, String> {
-    let (resolved_url, lib_ref) = provider.resolve(url)?;
-    let loader = get_loader(&resolved_url)?;
-    info!("Loading process with alias = '{}' from url='{}' ", alias, resolved_url);
-    let contents = provider.get(&resolved_url)?;
+pub fn load_process(parent_route: &Route, alias: &Name, url: &Url, provider: &Provider)
+                    -> Result<Process, Vec<SourceError>> {
+    load_cached_process(parent_route, alias, url, &Cache::new(provider, None))
+}
+
+/// load a process definition from `url` as `load_process` does, but using `cache` to get the
+/// definitions of it and all the sub-processes it references, so those that were loaded before
+/// using the same cache and have not changed are not fetched or parsed again.
+pub fn load_cached_process(parent_route: &Route, alias: &Name, url: &Url, cache: &Cache)
+                           -> Result<Process, Vec<SourceError>> {
+    load_process_in_scope(parent_route, alias, url, cache, &[], &HashMap::new(), &[])
+}
 
-    let mut process = loader.load_process(&contents)?;
+/*
+    Load a process that is used by a flow, where the struct types in scope in the flow
+    ('parent_types') can also be used in the process, and with the values given for it's
+    parameters in the reference to it. 'loading' is the urls of the flows that are being loaded
+    that (directly or indirectly) use the process, from the outermost one, so that a flow that
+    uses itself is found rather than being loaded forever.
+
+    All the errors found in the process, and in the processes it uses, are returned together.
+    Errors in the definition of the process are located in it. Errors that are not (e.g. it could
+    not be found) are left for the flow that references it to locate.
+*/
+fn load_process_in_scope(parent_route: &Route, alias: &Name, url: &Url, cache: &Cache,
+                         parent_types: &[StructType], parameters: &HashMap<Name, JsonValue>,
+                         loading: &[Url]) -> Result<Process, Vec<SourceError>> {
+    let (resolved_url, lib_ref) = cache.resolve(url).map_err(|e| vec!(SourceError::new(e)))?;
+    if let Some(start) = loading.iter().position(|loading_url| *loading_url == resolved_url) {
+        let cycle: Vec<String> = loading[start..].iter().chain(Some(&resolved_url))
+            .map(|url| format!("'{}'", url)).collect();
+        return Err(vec!(SourceError::new(format!("Reference cycle found, the flow '{}' uses itself: {}",
+                                                 resolved_url, cycle.join(" -> ")))));
+    }
+    info!("Loading process with alias = '{}' from url='{}' ", alias, resolved_url);
+    let mut process = cache.load(&resolved_url)
+        .map_err(|e| vec!(SourceError::new(e).or_in_definition(&resolved_url, cache)))?;
+    let mut errors = vec!();
 
     match process {
         FlowProcess(ref mut flow) => {
-            config_flow(flow, &resolved_url, parent_route, alias)?;
-            load_values(flow)?;
-            load_subprocesses(flow, provider)?;
-            build_flow_connections(flow)?;
+            let spans = flow.spans.clone();
+            let at_entry = |error: EntryError| SourceError::at_entry(error, &resolved_url, &spans, cache);
+            if let Err(e) = config_flow(flow, &resolved_url, parent_route, alias, parent_types) {
+                errors.push(at_entry(e));
+            }
+            match parameter::resolve(&flow.parameters, parameters, &flow.alias) {
+                Ok(values) => if let Err(e) = substitute_parameters(flow, &values) {
+                    errors.push(at_entry(e));
+                },
+                Err(e) => errors.push(SourceError::new(e))
+            }
+            if let Err(e) = load_values(flow) {
+                errors.push(SourceError::new(e).or_in_definition(&resolved_url, cache));
+            }
+            let mut loading = loading.to_vec();
+            loading.push(resolved_url.clone());
+            let not_loaded = load_subprocesses(flow, cache, &mut errors, &loading);
+            if let Err(connection_errors) = build_flow_connections(flow, &not_loaded) {
+                errors.extend(connection_errors.into_iter().map(&at_entry));
+            }
         }
         FunctionProcess(ref mut function) => {
-            config_function(function, &resolved_url, parent_route, alias, lib_ref)?;
+            if !parameters.is_empty() {
+                errors.push(SourceError::new(
+                    format!("Function '{}' has no parameters, but values were given for them", alias)));
+            }
+            if let Err(e) = config_function(function, &resolved_url, parent_route, alias, lib_ref, parent_types) {
+                errors.push(SourceError::at_entry(e, &resolved_url, function.get_spans(), cache));
+            }
         }
     }
 
-    Ok(process)
+    if errors.is_empty() {
+        Ok(process)
+    } else {
+        Err(errors)
+    }
 }
 
 /*
-    Load all sub-processes referenced from a flow via the process_reference fields
+    Load all sub-processes referenced from a flow via the process_reference fields, adding the
+    errors found to 'errors' and carrying on with the rest when one can't be loaded. Errors loading
+    a sub-process that are not located in it's definition are located at the reference to it.
+
+    Returns the aliases of the sub-processes that could not be loaded.
 */
-fn load_subprocesses(flow: &mut Flow, provider: &Provider) -> Result<(), String> {
+fn load_subprocesses(flow: &mut Flow, cache: &Cache, errors: &mut Vec<SourceError>, loading: &[Url]) -> Vec<Name> {
+    let mut not_loaded = vec!();
+    let source_url = flow.source_url.clone();
+    let (route, types, spans) = (&flow.route, &flow.types, &flow.spans);
+
     if let Some(ref mut process_refs) = flow.process_refs {
-        for process_ref in process_refs {
-            let subprocess_url = flow.source_url.join(&process_ref.source)
-                .map_err(|_e| "URL join error")?;
-            process_ref.process = load_process(&flow.route, &process_ref.alias(), &subprocess_url, provider)?;
+        for (index, process_ref) in process_refs.iter_mut().enumerate() {
+            let entry = format!("process.{}", index);
+            let loaded = source_url.join(&process_ref.source)
+                .map_err(|e| vec!(SourceError::new(format!("Invalid source '{}' ({})", process_ref.source, e))))
+                .and_then(|subprocess_url| load_process_in_scope(route, &process_ref.alias(), &subprocess_url,
+                                                                 cache, types, &process_ref.parameters, loading));
+            match loaded {
+                Ok(process) => process_ref.process = process,
+                Err(found) => {
+                    errors.extend(found.into_iter()
+                        .map(|error| error.or_at(&source_url, spans, &format!("{}.source", entry), cache)));
+                    not_loaded.push(process_ref.alias().to_string());
+                    continue;
+                }
+            }
+
+            if let Err(e) = initialize_inputs(process_ref, &entry) {
+                errors.push(SourceError::at_entry(e, &source_url, spans, cache));
+            }
 
             if let FunctionProcess(ref function) = process_ref.process {
                 if let Some(lib_ref) = function.get_lib_reference() {
@@ -104,24 +197,89 @@ fn load_subprocesses(flow: &mut Flow, provider: &Provider) -> Result<(), String>
             }
         }
     }
+
+    not_loaded
+}
+
+/*
+    Replace the references to the flow's parameters in the initial values of it's values, and in
+    the inputs and parameters given in it's references to other processes, with the values of the
+    parameters ('parameters'). This is done before the processes it references are loaded, so that
+    they are loaded with the values.
+*/
+fn substitute_parameters(flow: &mut Flow, parameters: &HashMap<Name, JsonValue>) -> Result<(), EntryError> {
+    let alias = &flow.alias;
+
+    for (index, value) in flow.values.iter_mut().flatten().enumerate() {
+        if let Some(mut initial_value) = value.get_initial_value() {
+            parameter::substitute(&mut initial_value, parameters, alias)
+                .map_err(|e| EntryError::new(format!("value.{}.init", index), e))?;
+            value.set_initial_value(Some(initial_value));
+        }
+    }
+
+    for (index, process_ref) in flow.process_refs.iter_mut().flatten().enumerate() {
+        for (name, initializer) in process_ref.initializations.iter_mut() {
+            match *initializer {
+                InputInitializer::Once(ref mut value) | InputInitializer::Constant(ref mut value) =>
+                    parameter::substitute(value, parameters, alias)
+                        .map_err(|e| EntryError::new(format!("process.{}.input.{}", index, name), e))?
+            }
+        }
+
+        for (name, value) in process_ref.parameters.iter_mut() {
+            parameter::substitute(value, parameters, alias)
+                .map_err(|e| EntryError::new(format!("process.{}.parameter.{}", index, name), e))?;
+        }
+    }
+
+    Ok(())
+}
+
+/*
+    Give the inputs of a process the values they are initialized with in the reference to it, that
+    is the entry 'entry' (e.g. "process.0") in the flow's definition. The inputs of a sub-flow pass
+    them on to the inputs they are connected to when it is compiled.
+*/
+fn initialize_inputs(process_ref: &mut ProcessReference, entry: &str) -> Result<(), EntryError> {
+    for (name, initializer) in &process_ref.initializations {
+        let at_input = |message: String| EntryError::new(format!("{}.input.{}", entry, name), message);
+        let not_found = at_input(format!("Process '{}' has no input called '{}' to initialize", process_ref.alias, name));
+        match process_ref.process {
+            FlowProcess(ref mut flow) => {
+                let input = flow.inputs.iter_mut().flatten().find(|input| input.name() == name)
+                    .ok_or(not_found)?;
+                input.set_initializer(initializer.clone());
+            }
+            FunctionProcess(ref mut function) => {
+                let input_number = function.get_inputs().unwrap_or(vec!()).iter()
+                    .position(|input| input.name() == name).ok_or(not_found)?;
+                function.set_input_initializer(input_number, initializer.clone()).map_err(&at_input)?;
+            }
+        }
+    }
+
     Ok(())
 }
 
 fn config_function(function: &mut Function, source_url: &Url, parent_route: &Route, alias: &Name,
-                   lib_ref: Option<String>) -> Result<(), String> {
+                   lib_ref: Option<String>, parent_types: &[StructType]) -> Result<(), EntryError> {
+    function.add_parent_types(parent_types)?;
     function.set_alias(alias.to_string());
     function.set_source_url(source_url.clone());
     function.set_lib_reference(lib_ref);
+    function.add_error_output();
     function.set_routes_from_parent(parent_route, false);
-    function.validate()
+    function.validate_entries()
 }
 
-fn config_flow(flow: &mut Flow, source_url: &Url, parent_route: &Route, alias: &Name)
-    -> Result<(), String> {
+fn config_flow(flow: &mut Flow, source_url: &Url, parent_route: &Route, alias: &Name,
+               parent_types: &[StructType]) -> Result<(), EntryError> {
+    flow.add_parent_types(parent_types)?;
     flow.alias = alias.to_string();
     flow.source_url = source_url.clone();
     flow.set_routes_from_parent(parent_route, true);
-    flow.validate()
+    flow.validate_entries()
 }
 
 /*
@@ -149,48 +307,59 @@ fn load_values(flow: &mut Flow) -> Result<(), String> {
 
         "process/flow_name/io_name"
         "process/function_name/io_name"
+
+    Connections to or from the sub-processes that could not be loaded ('not_loaded') are not
+    built, as the errors loading them have already been found. Each error found is about the
+    'from' or 'to' of a connection (e.g. "connection.1.to").
 */
-fn build_flow_connections(flow: &mut Flow) -> Result<(), String> {
+fn build_flow_connections(flow: &mut Flow, not_loaded: &[Name]) -> Result<(), Vec<EntryError>> {
     if flow.connections.is_none() { return Ok(()); }
 
     debug!("Building connections for flow '{}'", flow.source_url);
 
-    let mut error_count = 0;
+    let mut errors = vec!();
 
     // get connections out of self - so we can use immutable references to self inside loop
     let connections = replace(&mut flow.connections, None);
     let mut connections = connections.unwrap();
 
-    for connection in connections.iter_mut() {
-        connection.check_for_loops(flow.source_url.as_str())?;
+    for (index, connection) in connections.iter_mut().enumerate() {
+        let (from_entry, to_entry) = (format!("connection.{}.from", index), format!("connection.{}.to", index));
+        if not_loaded.iter().any(|alias| uses_process(&connection.from, alias) || uses_process(&connection.to, alias)) {
+            continue;
+        }
+
+        if let Err(error) = connection.check_for_loops(flow.source_url.as_str()) {
+            errors.push(EntryError::new(format!("connection.{}", index), error));
+            continue;
+        }
         match flow.get_route_and_type(FROM, &connection.from) {
             Ok(from) => {
                 debug!("Found source of connection:\n{:#?}", from);
                 match flow.get_route_and_type(TO, &connection.to) {
                     Ok(to) => {
                         debug!("Found destination of connection:\n{:#?}", to);
-                        if (from.datatype(0) == to.datatype(0)) ||
-                            from.datatype(0) == "Json" || to.datatype(0) == "Json" {
+                        if from.full_datatype().compatible_with(to.full_datatype()) {
                             debug!("Connection source and destination types match, connection built");
                             connection.from_io = from;
                             connection.to_io = to;
                         } else {
-                            error!("Type mismatch in flow '{}' connection:\n\nfrom\n\n{:#?}\n\nto\n\n{:#?}",
-                                   flow.source_url, from, to);
-                            error_count += 1;
+                            errors.push(EntryError::new(to_entry, format!(
+                                "Type mismatch in flow '{}': connection from '{}' of type '{}' to '{}' of type '{}'",
+                                flow.source_url, connection.from, from.full_datatype(), connection.to, to.full_datatype())));
                         }
                     }
                     Err(error) => {
-                        error!("Did not find connection destination: '{}' specified in flow '{}'\n\t\t{}",
-                               connection.to, flow.source_url, error);
-                        error_count += 1;
+                        errors.push(EntryError::new(to_entry, format!(
+                            "Did not find connection destination: '{}' specified in flow '{}' ({})",
+                            connection.to, flow.source_url, error)));
                     }
                 }
             }
             Err(error) => {
-                error!("Did not find connection source: '{}' specified in flow '{}'\n\t\t{}",
-                       connection.from, flow.source_url, error);
-                error_count += 1;
+                errors.push(EntryError::new(from_entry, format!(
+                    "Did not find connection source: '{}' specified in flow '{}' ({})",
+                    connection.from, flow.source_url, error)));
             }
         }
     }
@@ -198,10 +367,19 @@ fn build_flow_connections(flow: &mut Flow) -> Result<(), String> {
     // put connections back into self
     replace(&mut flow.connections, Some(connections));
 
-    if error_count == 0 {
+    if errors.is_empty() {
         debug!("All connections inside flow '{}' successfully built", flow.source_url);
         Ok(())
     } else {
-        Err(format!("{} connections errors found in flow '{}'", error_count, flow.source_url))
+        Err(errors)
     }
-}
\ No newline at end of file
+}
+
+/*
+    Check if a connection's 'from' or 'to' is to the sub-process with the alias, or to one of it's
+    inputs or outputs
+*/
+fn uses_process(descriptor: &str, alias: &Name) -> bool {
+    let process = format!("process/{}", alias);
+    descriptor == process || descriptor.starts_with(&format!("{}/", process))
+}
@@ -1,10 +1,15 @@
 use loader::yaml_loader::FlowYamlLoader;
 use loader::toml_loader::FlowTomelLoader;
+use loader::json_loader::FlowJsonLoader;
 use loader::loader::Loader;
 use url::Url;
 
 const TOML: &Loader = &FlowTomelLoader as &Loader;
 const YAML: &Loader = &FlowYamlLoader as &Loader;
+const JSON: &Loader = &FlowJsonLoader as &Loader;
+
+/// The file extensions of the definitions there is a loader for
+pub const DEFINITION_EXTENSIONS: &[&str] = &["toml", "yaml", "yml", "json"];
 
 pub fn get_loader(url: &Url) -> Result<&'static Loader, String> {
     match get_file_extension(url) {
@@ -13,6 +18,7 @@ pub fn get_loader(url: &Url) -> Result<&'static Loader, String> {
                 "toml" => Ok(TOML),
                 "yaml" => Ok(YAML),
                 "yml" => Ok(YAML),
+                "json" => Ok(JSON),
                 _ => Err("Unknown file extension so cannot determine which loader to use".to_string())
             }
         }
@@ -33,11 +39,42 @@ fn get_file_extension(url: &Url) -> Result<String, String> {
 }
 
 #[cfg(test)]
-mod test {
+pub mod test {
+    use std::fs;
+    use std::path::{Path, PathBuf};
     use url::Url;
+    use super::DEFINITION_EXTENSIONS;
     use super::get_file_extension;
     use super::get_loader;
 
+    // The path and contents of all the TOML definitions in the samples
+    pub fn sample_definitions() -> Vec<(PathBuf, String)> {
+        let mut definitions = vec!();
+        let samples = Path::new(env!("CARGO_MANIFEST_DIR")).join("../samples");
+        for sample in fs::read_dir(samples).unwrap() {
+            let sample = sample.unwrap().path();
+            if !sample.is_dir() {
+                continue;
+            }
+            for file in fs::read_dir(sample).unwrap() {
+                let file = file.unwrap().path();
+                if file.extension().map_or(false, |extension| extension == "toml") {
+                    let contents = fs::read_to_string(&file).unwrap();
+                    definitions.push((file, contents));
+                }
+            }
+        }
+        assert!(!definitions.is_empty());
+        definitions
+    }
+
+    #[test]
+    fn loader_for_every_extension() {
+        for extension in DEFINITION_EXTENSIONS {
+            get_loader(&Url::parse(&format!("file:///context.{}", extension)).unwrap()).unwrap();
+        }
+    }
+
     #[test]
     #[should_panic]
     fn no_extension() {
@@ -69,4 +106,9 @@ mod test {
     fn yaml_extension_loader() {
         get_loader(&Url::parse("file:///extension.yaml").unwrap()).unwrap();
     }
+
+    #[test]
+    fn json_extension_loader() {
+        get_loader(&Url::parse("file:///extension.json").unwrap()).unwrap();
+    }
 }
\ No newline at end of file
@@ -1,5 +1,8 @@
 //! Loader module that parses flow descriptions in files and constructure a hierarchical model of the flow in memory
 pub mod loader;
+pub mod cache;
+pub mod error;
 mod yaml_loader;
 mod toml_loader;
+mod json_loader;
 mod loader_helper;
\ No newline at end of file
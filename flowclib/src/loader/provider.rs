@@ -9,6 +9,6 @@ pub trait Provider {
     fn resolve(&self, url: &Url) -> Result<(Url, Option<String>), String>;
 
     /// 'get' fetches content from a url. It resolves the url internally before attempting to
-    /// fetch actual content
-    fn get(&self, url: &Url) -> Result<String, String>;
+    /// fetch actual content. Content is returned as raw bytes, as it maybe binary (e.g. WASM)
+    fn get(&self, url: &Url) -> Result<Vec<u8>, String>;
 }
\ No newline at end of file
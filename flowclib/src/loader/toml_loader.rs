@@ -1,12 +1,118 @@
+use std::collections::BTreeMap;
+
 use toml;
+use toml::Spanned;
+use toml::Value as TomlValue;
 use loader::loader::Loader;
 use model::process::Process;
+use model::span::Span;
+use model::span::Spans;
 
 pub struct FlowTomelLoader;
 
 impl Loader for FlowTomelLoader {
     fn load_process(&self, contents: &str) -> Result<Process, String> {
-        toml::from_str(contents).map_err(|e| format!("{}", e))
+        let mut process: Process = toml::from_str(contents).map_err(|e| with_column(&e))?;
+        process.set_spans(spans(contents));
+        Ok(process)
+    }
+}
+
+/*
+    The message of an error parsing a definition, with the column as well as the line it is at
+    (the column is not included in the message by toml), so it can be located in the definition
+*/
+fn with_column(error: &toml::de::Error) -> String {
+    let message = error.to_string();
+    match (error.line_col(), message.rfind(" at line ")) {
+        (Some((line, column)), Some(position)) =>
+            format!("{} at line {} column {}", &message[..position], line + 1, column + 1),
+        _ => message
+    }
+}
+
+/*
+    The entries of a definition that errors can be about, with the span toml found each of the
+    values in them at. Tables written with their own header (e.g. "[[process]]") are not values
+    that toml can give the span of, so the tables and arrays of them that a definition can have are
+    declared here, and the rest of the definition is ignored.
+*/
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct DefinitionSpans {
+    flow: Option<Spanned<TomlValue>>,
+    function: Option<Spanned<TomlValue>>,
+    input: Vec<EntrySpans>,
+    output: Vec<EntrySpans>,
+    process: Vec<EntrySpans>,
+    value: Vec<EntrySpans>,
+    connection: Vec<EntrySpans>,
+    #[serde(rename = "type")]
+    types: Vec<EntrySpans>,
+    parameter: Vec<EntrySpans>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct EntrySpans {
+    name: Option<Spanned<TomlValue>>,
+    alias: Option<Spanned<TomlValue>>,
+    source: Option<Spanned<TomlValue>>,
+    from: Option<Spanned<TomlValue>>,
+    to: Option<Spanned<TomlValue>>,
+    #[serde(rename = "type")]
+    datatype: Option<Spanned<TomlValue>>,
+    init: Option<Spanned<TomlValue>>,
+    default: Option<Spanned<TomlValue>>,
+    input: BTreeMap<String, Spanned<TomlValue>>,
+    parameter: BTreeMap<String, Spanned<TomlValue>>,
+    field: Vec<EntrySpans>,
+}
+
+/*
+    Find where the values in a definition are. If they cannot be found (e.g. a table toml cannot
+    give the span of is written with it's own header) then errors are located at the start of the
+    definition instead.
+*/
+fn spans(contents: &str) -> Spans {
+    let mut spans = Spans::new();
+    match toml::from_str::<DefinitionSpans>(contents) {
+        Ok(definition) => {
+            let mut add = |path: String, value: Option<&Spanned<TomlValue>>| {
+                if let Some(value) = value {
+                    spans.insert(path, Span::of(contents, value.start(), value.end()));
+                }
+            };
+            add("flow".to_string(), definition.flow.as_ref());
+            add("function".to_string(), definition.function.as_ref());
+            for &(key, ref entries) in &[("input", &definition.input), ("output", &definition.output),
+                ("process", &definition.process), ("value", &definition.value),
+                ("connection", &definition.connection), ("type", &definition.types),
+                ("parameter", &definition.parameter)] {
+                for (index, entry) in entries.iter().enumerate() {
+                    add_entry(&format!("{}.{}", key, index), entry, &mut add);
+                }
+            }
+        }
+        Err(e) => debug!("Could not find where the values in the definition are ({})", e)
+    }
+    spans
+}
+
+fn add_entry<F>(path: &str, entry: &EntrySpans, add: &mut F)
+    where F: FnMut(String, Option<&Spanned<TomlValue>>) {
+    for &(key, value) in &[("name", &entry.name), ("alias", &entry.alias), ("source", &entry.source),
+        ("from", &entry.from), ("to", &entry.to), ("type", &entry.datatype), ("init", &entry.init),
+        ("default", &entry.default)] {
+        add(format!("{}.{}", path, key), value.as_ref());
+    }
+    for &(key, ref values) in &[("input", &entry.input), ("parameter", &entry.parameter)] {
+        for (name, value) in values.iter() {
+            add(format!("{}.{}.{}", path, key, name), Some(value));
+        }
+    }
+    for (index, field) in entry.field.iter().enumerate() {
+        add_entry(&format!("{}.field.{}", path, index), field, add);
     }
 }
 
@@ -187,4 +293,44 @@ type = 'String'";
 
     let toml = FlowTomelLoader {};
     toml.load_process(function_definition).unwrap();
-}
\ No newline at end of file
+}
+
+#[test]
+fn error_gives_line_and_column() {
+    let flow_description = "\
+flow = 'test'
+[[value]]
+name 'message'";
+
+    let toml = FlowTomelLoader {};
+    let error = toml.load_process(flow_description).err().unwrap();
+    assert!(error.ends_with(" at line 3 column 6"), "{}", error);
+}
+
+#[test]
+fn spans_of_values_recorded() {
+    use super::super::model::process::Process::FlowProcess;
+    use super::super::model::span::Span;
+    let flow_description = "\
+flow = 'test'
+
+[[process]]
+alias = 'add'
+source = 'lib://flowstdlib/math/add.toml'
+input.i2 = { constant = 1 }
+
+[[connection]]
+from = 'input/number'
+to = \"process/add/i1\"";
+
+    let toml = FlowTomelLoader {};
+    match toml.load_process(flow_description).unwrap() {
+        FlowProcess(flow) => {
+            assert_eq!(flow.spans.get("flow"), Some(&Span { line: 1, column: 9, length: 4 }));
+            assert_eq!(flow.spans.get("process.0.source"), Some(&Span { line: 5, column: 11, length: 30 }));
+            assert_eq!(flow.spans.get("process.0.input.i2"), Some(&Span { line: 6, column: 12, length: 16 }));
+            assert_eq!(flow.spans.get("connection.0.to"), Some(&Span { line: 10, column: 7, length: 14 }));
+        }
+        _ => assert!(false)
+    }
+}
@@ -1,16 +1,193 @@
 use loader::loader::Loader;
-use model::flow::Flow;
 use model::process::Process;
+use serde_json;
+use serde_json::Map;
+use serde_json::Number;
+use serde_json::Value as JsonValue;
+use yaml_rust::Yaml;
+use yaml_rust::YamlLoader;
 
 pub struct FlowYamlLoader;
 
+/*
+    The YAML is parsed and converted into the equivalent JSON value, which is then deserialized
+    into the same model as TOML definitions are, so they are validated in the same way
+    (e.g. unknown fields are errors).
+
+    yaml_rust does not give the positions of the values it parses, so no spans are recorded for a
+    YAML definition and errors about entries in it are located at the start of the definition.
+*/
 impl Loader for FlowYamlLoader {
-    fn load_process(&self, _contents: &str) -> Result<Process, String> {
-//        let docs = YamlLoader::load_from_str(&contents).unwrap();
-//        let doc = &docs[0];
+    fn load_process(&self, contents: &str) -> Result<Process, String> {
+        let mut docs = YamlLoader::load_from_str(contents).map_err(|e| format!("{}", e))?;
+        if docs.len() != 1 {
+            return Err(format!("Expected one YAML document but found {}", docs.len()));
+        }
+
+        let value = yaml_to_json(docs.remove(0))?;
+        serde_json::from_value(value).map_err(|e| format!("{}", e))
+    }
+}
+
+fn yaml_to_json(yaml: Yaml) -> Result<JsonValue, String> {
+    match yaml {
+        Yaml::Null => Ok(JsonValue::Null),
+        Yaml::Boolean(boolean) => Ok(JsonValue::Bool(boolean)),
+        Yaml::Integer(integer) => Ok(JsonValue::Number(Number::from(integer))),
+        Yaml::Real(real) => {
+            real.parse::<f64>().ok().and_then(Number::from_f64).map(JsonValue::Number)
+                .ok_or(format!("'{}' is not a valid number", real))
+        }
+        Yaml::String(string) => Ok(JsonValue::String(string)),
+        Yaml::Array(array) => {
+            let values: Result<Vec<JsonValue>, String> = array.into_iter().map(yaml_to_json).collect();
+            Ok(JsonValue::Array(values?))
+        }
+        Yaml::Hash(hash) => {
+            let mut map = Map::new();
+            for (key, value) in hash {
+                match key {
+                    Yaml::String(key) => { map.insert(key, yaml_to_json(value)?); }
+                    _ => return Err(format!("Keys must be strings, found {:?}", key))
+                }
+            }
+            Ok(JsonValue::Object(map))
+        }
+        Yaml::Alias(_) => Err("YAML aliases are not supported".to_string()),
+        Yaml::BadValue => Err("Invalid YAML value".to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use toml;
+    use yaml_rust::Yaml;
+    use yaml_rust::YamlEmitter;
+
+    use loader::loader::Loader;
+    use loader::loader_helper::test::sample_definitions;
+    use loader::toml_loader::FlowTomelLoader;
+    use model::flow::Flow;
+    use model::process::Process::FlowProcess;
+    use model::process::Process::FunctionProcess;
+    use model::span::Spans;
+
+    use super::FlowYamlLoader;
+
+    #[test]
+    fn simple_context_loads() {
+        let flow_description = "
+flow: hello-world-simple-yaml
+value:
+  - name: message
+    type: String
+    init: Hello World!
+process:
+  - alias: print
+    source: terminal.yaml
+connection:
+  - name: message
+    from: value/message
+    to: function/print/stdout
+";
+
+        match FlowYamlLoader.load_process(flow_description).unwrap() {
+            FlowProcess(flow) => {
+                assert_eq!(flow.name, "hello-world-simple-yaml");
+                assert_eq!(flow.values.unwrap().len(), 1);
+                assert_eq!(flow.process_refs.unwrap()[0].source, "terminal.yaml");
+            }
+            _ => panic!("Expected a flow")
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn flow_errors_on_unknown_fields() {
+        let flow_description = "
+flow: hello-world-simple-yaml
+foo: true
+";
+
+        FlowYamlLoader.load_process(flow_description).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn function_errors_on_unknown_fields() {
+        let function_definition = "
+function: stdout
+output:
+  - foo: true
+";
+
+        FlowYamlLoader.load_process(function_definition).unwrap();
+    }
+
+    #[test]
+    fn default_optional_values() {
+        match FlowYamlLoader.load_process("flow: test").unwrap() {
+            FlowProcess(flow) => {
+                assert_eq!(flow.version, Flow::default_version());
+                assert_eq!(flow.author_name, Flow::default_author());
+                assert_eq!(flow.author_email, Flow::default_email());
+            }
+            _ => panic!("Expected a flow")
+        }
+    }
+
+    #[test]
+    fn function_parses() {
+        let function_definition = "
+function: stdout
+input:
+  - name: stdout
+    type: String
+";
+
+        match FlowYamlLoader.load_process(function_definition).unwrap() {
+            FunctionProcess(_) => {}
+            _ => panic!("Expected a function")
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_yaml() {
+        FlowYamlLoader.load_process("flow: [test").unwrap();
+    }
+
+    fn toml_to_yaml(value: toml::Value) -> Yaml {
+        match value {
+            toml::Value::String(string) => Yaml::String(string),
+            toml::Value::Integer(integer) => Yaml::Integer(integer),
+            toml::Value::Float(float) => Yaml::Real(float.to_string()),
+            toml::Value::Boolean(boolean) => Yaml::Boolean(boolean),
+            toml::Value::Datetime(datetime) => Yaml::String(datetime.to_string()),
+            toml::Value::Array(array) => Yaml::Array(array.into_iter().map(toml_to_yaml).collect()),
+            toml::Value::Table(table) => Yaml::Hash(table.into_iter()
+                .map(|(key, value)| (Yaml::String(key), toml_to_yaml(value))).collect()),
+        }
+    }
+
+    /*
+        Every definition in the samples, when written in YAML, loads to the same model as the
+        TOML definition (apart from where the values are in it, which is not known for YAML)
+    */
+    #[test]
+    fn samples_load_from_yaml() {
+        for (file, contents) in sample_definitions() {
+            let mut toml_process = FlowTomelLoader.load_process(&contents).unwrap();
+            toml_process.set_spans(Spans::new());
 
-        let flow = Flow::default();
+            let mut yaml = String::new();
+            YamlEmitter::new(&mut yaml)
+                .dump(&toml_to_yaml(toml::from_str(&contents).unwrap())).unwrap();
+            let yaml_process = FlowYamlLoader.load_process(&yaml)
+                .map_err(|e| format!("{}: {}\n{}", file.display(), e, yaml)).unwrap();
 
-        Ok(Process::FlowProcess(flow))
+            assert_eq!(format!("{:?}", yaml_process), format!("{:?}", toml_process),
+                       "{} loads differently from YAML", file.display());
+        }
     }
 }
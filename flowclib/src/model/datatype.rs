@@ -1,3 +1,6 @@
+use model::struct_type::StructType;
+use model::struct_type::FindType;
+
 const DATATYPES: &'static [&'static str] = &["String", "Json", "Number", "Bool", "Map", "Array"];
 
 pub type DataType = String;
@@ -8,25 +11,84 @@ pub trait HasDataType {
 
 pub trait TypeCheck {
     fn valid(&self) -> Result<(), String>;
+    fn valid_in(&self, types: &[StructType]) -> Result<(), String>;
     fn is_array(&self) -> bool;
+    fn is_generic(&self) -> bool;
+    fn is_struct(&self) -> bool;
+    fn array_type(&self) -> DataType;
+    fn compatible_with(&self, to: &DataType) -> bool;
 }
 
 impl TypeCheck for DataType {
     fn valid(&self) -> Result<(), String> {
-        // Split the type hierarchy and check all levels are valid
+        self.valid_in(&[])
+    }
+
+    /*
+        Split the type hierarchy and check all levels are valid, either being one of the built-in
+        types or the name of one of the struct 'types' declared
+    */
+    fn valid_in(&self, types: &[StructType]) -> Result<(), String> {
         let type_levels = self.split('/');
 
         for type_level in type_levels {
-            if !DATATYPES.contains(&type_level) {
+            if !DATATYPES.contains(&type_level) && types.find_type(type_level).is_none() {
                 return Err(format!("Type '{}' is invalid", &self));
             }
         }
         return Ok(());
     }
 
+    // An array of any type, e.g. "Array" or "Array/Number"
     fn is_array(&self) -> bool {
-        self == &DataType::from("Array")
+        top_level(self) == "Array"
+    }
+
+    // "Json" can be a value of any of the other types
+    fn is_generic(&self) -> bool {
+        self == &DataType::from("Json")
+    }
+
+    // Any type that is not one of the built-in types is a declared struct type
+    fn is_struct(&self) -> bool {
+        !DATATYPES.contains(&top_level(self).as_str())
+    }
+
+    /*
+        The type of the elements of an array type - e.g. "Array/Number" contains "Number" and
+        "Array/Array/String" contains "Array/String". An array with no element type given can
+        contain values of any type, so it's elements are "Json"
+    */
+    fn array_type(&self) -> DataType {
+        match self.splitn(2, '/').nth(1) {
+            Some(element_type) => DataType::from(element_type),
+            None => DataType::from("Json")
+        }
     }
+
+    /*
+        Determine if a value of this type can be sent to an input of type 'to'.
+
+        "Json" is a supertype that any value can be sent to, and a "Json" value's actual type is not
+        known until run-time, so it can be sent anywhere. Arrays are compatible if the types of their
+        elements are, checking each level of nested arrays in turn (e.g. "Array/Array/Number").
+        Struct types are compatible with the same type, and a struct's value is a "Map" so it can
+        be sent to a "Map" input.
+    */
+    fn compatible_with(&self, to: &DataType) -> bool {
+        if self.is_generic() || to.is_generic() {
+            return true;
+        }
+
+        match (self.is_array(), to.is_array()) {
+            (true, true) => self.array_type().compatible_with(&to.array_type()),
+            _ => self == to || (self.is_struct() && to == "Map")
+        }
+    }
+}
+
+fn top_level(datatype: &DataType) -> DataType {
+    DataType::from(datatype.split('/').next().unwrap_or(""))
 }
 
 #[test]
@@ -52,10 +114,73 @@ fn invalid_data_type() {
 fn is_array_true() {
     let array_type = DataType::from("Array".to_string());
     assert!(array_type.is_array());
+    assert!(DataType::from("Array/Number").is_array());
 }
 
 #[test]
 fn is_array_false() {
     let string_type = DataType::from("String".to_string());
     assert_eq!(string_type.is_array(), false);
-}
\ No newline at end of file
+}
+
+#[test]
+fn array_types() {
+    assert_eq!(DataType::from("Array/Number").array_type(), "Number");
+    assert_eq!(DataType::from("Array/Array/String").array_type(), "Array/String");
+    assert_eq!(DataType::from("Array").array_type(), "Json");
+}
+
+#[test]
+fn valid_struct_type() {
+    let types: Vec<StructType> = vec!(StructType { name: "Point".to_string(), fields: vec!() });
+    DataType::from("Array/Point").valid_in(&types).unwrap();
+    assert!(DataType::from("Point").valid().is_err());
+}
+
+#[test]
+fn struct_types_compatible() {
+    assert!(compatible("Point", "Point"));
+    assert!(compatible("Point", "Map"));
+    assert!(compatible("Array/Point", "Array/Map"));
+    assert!(!compatible("Point", "Line"));
+    assert!(!compatible("Map", "Point"));
+}
+
+#[cfg(test)]
+fn compatible(from: &str, to: &str) -> bool {
+    DataType::from(from).compatible_with(&DataType::from(to))
+}
+
+#[test]
+fn same_types_compatible() {
+    assert!(compatible("String", "String"));
+    assert!(compatible("Array/Number", "Array/Number"));
+}
+
+#[test]
+fn different_types_incompatible() {
+    assert!(!compatible("String", "Number"));
+    assert!(!compatible("Map", "Bool"));
+}
+
+#[test]
+fn json_is_supertype() {
+    assert!(compatible("Number", "Json"));
+    assert!(compatible("Array/String", "Json"));
+    assert!(compatible("Json", "Number"));
+}
+
+#[test]
+fn nested_array_levels_checked() {
+    assert!(compatible("Array/Array/Number", "Array/Array/Number"));
+    assert!(compatible("Array/Array/Number", "Array/Json"));
+    assert!(compatible("Array/Number", "Array"));
+    assert!(!compatible("Array/Array/Number", "Array/Array/String"));
+    assert!(!compatible("Array/Number", "Array/Array/Number"));
+}
+
+#[test]
+fn array_not_compatible_with_element() {
+    assert!(!compatible("Array/Number", "Number"));
+    assert!(!compatible("Number", "Array/Number"));
+}
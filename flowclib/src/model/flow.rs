@@ -4,12 +4,18 @@ use model::connection::Connection;
 use model::io::IO;
 use model::io::IOSet;
 use model::value::Value;
+use model::struct_type::StructType;
+use model::struct_type::add_parent_types;
+use model::parameter;
+use model::parameter::Parameter;
 use model::process_reference::ProcessReference;
+use model::span::Spans;
 use model::route::Route;
 use model::route::HasRoute;
 use model::route::SetRoute;
 use model::io::Find;
 use loader::loader::Validate;
+use loader::error::EntryError;
 use model::connection::Direction;
 use model::runnable::Runnable;
 use model::process::Process::FlowProcess;
@@ -32,6 +38,10 @@ pub struct Flow {
     pub values: Option<Vec<Value>>,
     #[serde(rename = "connection")]
     pub connections: Option<Vec<Connection>>,
+    #[serde(rename = "type", default)]
+    pub types: Vec<StructType>,
+    #[serde(rename = "parameter", default)]
+    pub parameters: Vec<Parameter>,
 
     #[serde(default = "Flow::default_version")]
     pub version: String,
@@ -48,42 +58,15 @@ pub struct Flow {
     pub route: Route,
     #[serde(skip_deserializing)]
     pub lib_references: Vec<String>,
+    // Where the values in the definition are, to locate errors in it
+    #[serde(skip_deserializing)]
+    pub spans: Spans,
 }
 
 impl Validate for Flow {
     // check the correctness of all the fields in this flow, prior to loading sub-elements
     fn validate(&self) -> Result<(), String> {
-        if let Some(ref process_refs) = self.process_refs {
-            for process_ref in process_refs {
-                process_ref.validate()?;
-            }
-        }
-
-        if let Some(ref inputs) = self.inputs {
-            for input in inputs {
-                input.validate()?;
-            }
-        }
-
-        if let Some(ref outputs) = self.outputs {
-            for output in outputs {
-                output.validate()?;
-            }
-        }
-
-        if let Some(ref values) = self.values {
-            for value in values {
-                value.validate()?;
-            }
-        }
-
-        if let Some(ref connections) = self.connections {
-            for connection in connections {
-                connection.validate()?;
-            }
-        }
-
-        Ok(())
+        self.validate_entries().map_err(|e| e.message)
     }
 }
 
@@ -143,7 +126,10 @@ impl Default for Flow {
             inputs: None,
             outputs: None,
             connections: None,
+            types: vec!(),
+            parameters: vec!(),
             lib_references: vec!(),
+            spans: Spans::new(),
             version: Flow::default_version(),
             author_name: Flow::default_author(),
             author_email: Flow::default_email(),
@@ -182,6 +168,45 @@ impl Flow {
         "unknown@unknown.com".to_string()
     }
 
+    pub fn add_parent_types(&mut self, parent_types: &[StructType]) -> Result<(), EntryError> {
+        add_parent_types(&mut self.types, parent_types)
+    }
+
+    /// Check the correctness of all the fields in this flow, as `validate` does, giving the entry
+    /// in the flow's definition that an error is about (e.g. "connection.1" for the second connection)
+    pub fn validate_entries(&self) -> Result<(), EntryError> {
+        for (index, struct_type) in self.types.iter().enumerate() {
+            struct_type.validate_in(&self.types).map_err(|e| EntryError::new(format!("type.{}", index), e))?;
+        }
+
+        for (index, parameter) in self.parameters.iter().enumerate() {
+            parameter.validate().map_err(|e| EntryError::new(format!("parameter.{}", index), e))?;
+        }
+        parameter::declared_once(&self.parameters, &self.alias)?;
+
+        for (index, process_ref) in self.process_refs.iter().flatten().enumerate() {
+            process_ref.validate().map_err(|e| EntryError::new(format!("process.{}", index), e))?;
+        }
+
+        for (index, input) in self.inputs.iter().flatten().enumerate() {
+            input.validate_in(&self.types).map_err(|e| EntryError::new(format!("input.{}", index), e))?;
+        }
+
+        for (index, output) in self.outputs.iter().flatten().enumerate() {
+            output.validate_in(&self.types).map_err(|e| EntryError::new(format!("output.{}", index), e))?;
+        }
+
+        for (index, value) in self.values.iter().flatten().enumerate() {
+            value.validate_in(&self.types).map_err(|e| EntryError::new(format!("value.{}", index), e))?;
+        }
+
+        for (index, connection) in self.connections.iter().flatten().enumerate() {
+            connection.validate().map_err(|e| EntryError::new(format!("connection.{}", index), e))?;
+        }
+
+        Ok(())
+    }
+
     fn get_io_subprocess(&self, subprocess_alias: &str, direction: Direction, route: &Route) -> Result<IO, String> {
         if let Some(ref process_refs) = self.process_refs {
             for process_ref in process_refs {
@@ -199,8 +224,8 @@ impl Flow {
                     FunctionProcess(ref function) => {
                         if process_ref.name() == subprocess_alias {
                             return match direction {
-                                Direction::TO => function.get_inputs().find_by_route(route),
-                                Direction::FROM => function.get_outputs().find_by_route(route)
+                                Direction::TO => function.get_inputs().find_by_route(route, function.get_types()),
+                                Direction::FROM => function.get_outputs().find_by_route(route, function.get_types())
                             };
                         }
                     },
@@ -221,7 +246,7 @@ impl Flow {
                 if value.name() == value_name {
                     return match direction {
                         Direction::TO => value.get_input(),
-                        Direction::FROM => value.get_outputs().find_by_route(route)
+                        Direction::FROM => value.get_outputs().find_by_route(route, &self.types)
                     };
                 }
             }
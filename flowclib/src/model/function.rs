@@ -4,11 +4,17 @@ use model::name::Name;
 use model::name::HasName;
 use model::io::IO;
 use model::io::IOSet;
+use model::struct_type::StructType;
+use model::struct_type::add_parent_types;
 use model::route::Route;
 use model::route::HasRoute;
 use model::route::SetRoute;
 use loader::loader::Validate;
+use loader::error::EntryError;
 use model::runnable::Runnable;
+use model::span::Spans;
+use flowrlib::implementation::ERROR_OUTPUT;
+use flowrlib::input::InputInitializer;
 use serde_json::Value as JsonValue;
 use url::Url;
 
@@ -21,6 +27,10 @@ pub struct Function {
     inputs: IOSet,
     #[serde(rename = "output")]
     outputs: IOSet,
+    #[serde(rename = "type", default)]
+    types: Vec<StructType>,
+    #[serde(default)]
+    impure: bool,
 
     #[serde(skip_deserializing)]
     alias: Name,
@@ -35,6 +45,9 @@ pub struct Function {
     output_routes: Vec<(Route, usize, usize)>,
     #[serde(skip_deserializing)]
     id: usize,
+    // Where the values in the definition are, to locate errors in it
+    #[serde(skip_deserializing)]
+    spans: Spans,
 }
 
 impl HasName for Function {
@@ -69,6 +82,20 @@ impl Runnable for Function {
         self.output_routes.push(output_route);
     }
 
+    fn set_output_routes(&mut self, output_routes: Vec<(Route, usize, usize)>) {
+        self.output_routes = output_routes;
+    }
+
+    fn set_input_initializer(&mut self, input_number: usize, initializer: InputInitializer) -> Result<(), String> {
+        match self.inputs {
+            Some(ref mut inputs) if input_number < inputs.len() => {
+                inputs[input_number].set_initializer(initializer);
+                Ok(())
+            }
+            _ => Err(format!("Function '{}' has no input number {}", self.alias, input_number))
+        }
+    }
+
     // Could combine with get_impl_path() ????
     fn source_url(&self) -> Option<Url> {
         if self.lib_reference.is_none() {
@@ -84,6 +111,8 @@ impl Runnable for Function {
 
     fn is_static_value(&self) -> bool { false }
 
+    fn is_impure(&self) -> bool { self.impure }
+
     fn get_output_routes(&self) -> &Vec<(Route, usize, usize)> {
         &self.output_routes
     }
@@ -101,30 +130,7 @@ impl Runnable for Function {
 
 impl Validate for Function {
     fn validate(&self) -> Result<(), String> {
-        self.name.validate()?;
-
-        let mut io_count = 0;
-
-        if let Some(ref inputs) = self.inputs {
-            for i in inputs {
-                io_count += 1;
-                i.validate()?
-            }
-        }
-
-        if let Some(ref outputs) = self.outputs {
-            for i in outputs {
-                io_count += 1;
-                i.validate()?
-            }
-        }
-
-        // A function must have at least one valid input or output
-        if io_count == 0 {
-            return Err("A function must have at least one input or output".to_string());
-        }
-
-        Ok(())
+        self.validate_entries().map_err(|e| e.message)
     }
 }
 
@@ -159,11 +165,14 @@ impl Default for Function {
             alias: "".to_string(),
             inputs: None,
             outputs: Some(vec!(IO::new(&"Json".to_string(), &"".to_string()))),
+            types: vec!(),
+            impure: false,
             source_url: Function::default_url(),
             route: "".to_string(),
             lib_reference: None,
             id: 0,
             output_routes: vec!(("".to_string(), 0, 0)),
+            spans: Spans::new(),
         }
     }
 }
@@ -185,8 +194,8 @@ impl Function {
     route: Route, lib_reference: Option<String>, output_connections: Vec<(Route, usize, usize)>,
     id: usize) -> Self {
         Function {
-            name, alias, inputs, outputs, source_url, route, lib_reference,
-            output_routes: output_connections,  id
+            name, alias, inputs, outputs, types: vec!(), impure: false, source_url, route, lib_reference,
+            output_routes: output_connections,  id, spans: Spans::new()
         }
     }
 
@@ -198,6 +207,11 @@ impl Function {
         self.source_url = source
     }
 
+    // The url the function's definition was loaded from, including for functions in a library
+    pub fn get_source_url(&self) -> &Url {
+        &self.source_url
+    }
+
     pub fn set_lib_reference(&mut self, lib_reference: Option<String>) {
         self.lib_reference = lib_reference
     }
@@ -205,6 +219,69 @@ impl Function {
     pub fn get_lib_reference(&self) -> &Option<String> {
         &self.lib_reference
     }
+
+    pub fn set_spans(&mut self, spans: Spans) {
+        self.spans = spans
+    }
+
+    // Where the values in the function's definition are, to locate errors in it
+    pub fn get_spans(&self) -> &Spans {
+        &self.spans
+    }
+
+    // The struct types declared in the function's definition, followed by those of the flow using it
+    pub fn get_types(&self) -> &Vec<StructType> {
+        &self.types
+    }
+
+    pub fn add_parent_types(&mut self, parent_types: &[StructType]) -> Result<(), EntryError> {
+        add_parent_types(&mut self.types, parent_types)
+    }
+
+    /// Check the correctness of all the fields in this function, as `validate` does, giving the
+    /// entry in the function's definition that an error is about (e.g. "input.0" for the first input)
+    pub fn validate_entries(&self) -> Result<(), EntryError> {
+        self.name.validate().map_err(|e| EntryError::new("function".to_string(), e))?;
+
+        for (index, struct_type) in self.types.iter().enumerate() {
+            struct_type.validate_in(&self.types).map_err(|e| EntryError::new(format!("type.{}", index), e))?;
+        }
+
+        let mut io_count = 0;
+
+        for (index, input) in self.inputs.iter().flatten().enumerate() {
+            io_count += 1;
+            input.validate_in(&self.types).map_err(|e| EntryError::new(format!("input.{}", index), e))?;
+        }
+
+        for (index, output) in self.outputs.iter().flatten().enumerate() {
+            io_count += 1;
+            output.validate_in(&self.types).map_err(|e| EntryError::new(format!("output.{}", index), e))?;
+        }
+
+        // A function must have at least one valid input or output
+        if io_count == 0 {
+            return Err(EntryError::new("function".to_string(),
+                                       "A function must have at least one input or output".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /*
+        Every function has an 'error' output that the runtime sends a description of the error to
+        if the function fails, so flows can connect to it like any other output. Add it unless the
+        function's definition already declares it.
+    */
+    pub fn add_error_output(&mut self) {
+        let error_output_name = ERROR_OUTPUT.to_string();
+        let outputs = self.outputs.get_or_insert_with(|| vec!());
+        if !outputs.iter().any(|output| output.name() == &error_output_name) {
+            let mut error_output = IO::new(&"Map".to_string(), &"".to_string());
+            error_output.set_name(error_output_name);
+            outputs.push(error_output);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -217,6 +294,7 @@ mod test {
     use model::route::HasRoute;
     use model::route::SetRoute;
     use model::io::Find;
+    use model::span::Spans;
 
     #[test]
     fn function_with_no_io_not_valid() {
@@ -226,10 +304,13 @@ mod test {
             source_url: Function::default_url(),
             inputs: Some(vec!()), // No inputs!
             outputs: None,         // No output!
+            types: vec!(),
+            impure: false,
             route: "".to_string(),
             lib_reference: None,
             id: 0,
             output_routes: vec!(("test_function".to_string(), 0, 0)),
+            spans: Spans::new(),
         };
 
         assert_eq!(fun.validate().is_err(), true);
@@ -385,7 +466,30 @@ mod test {
 
         // Test
         // Try and get the output using a route to a specific element of the output
-        let output = function.outputs.find_by_route(&Route::from("/0")).unwrap();
+        let output = function.outputs.find_by_route(&Route::from("/0"), &function.types).unwrap();
         assert_eq!(output.name(), "");
     }
+
+    #[test]
+    fn error_output_added() {
+        let function_str = "
+        function = 'test_function'
+        [[output]]
+        type = 'String'
+        ";
+
+        // Setup
+        let mut function: Function = toml::from_str(function_str).unwrap();
+        function.alias = "test_alias".to_string();
+
+        // Test
+        function.add_error_output();
+        function.add_error_output();
+        function.set_routes_from_parent(&Route::from("/flow"), false);
+
+        assert_eq!(function.outputs.as_ref().unwrap().len(), 2);
+        let output = function.outputs.find_by_route(&Route::from("error"), &function.types).unwrap();
+        assert_eq!(output.route(), "/flow/test_alias/error");
+        assert_eq!(output.datatype(0), "Map");
+    }
 }
\ No newline at end of file
@@ -9,7 +9,9 @@ use loader::loader::Validate;
 use model::route::Route;
 use model::route::SetRoute;
 use std::collections::HashSet;
-use model::route::Router;
+use model::struct_type::StructType;
+use model::struct_type::FindType;
+use flowrlib::input::InputInitializer;
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(deny_unknown_fields)]
@@ -26,6 +28,9 @@ pub struct IO {
     route: Route,
     #[serde(skip_deserializing)]
     flow_io: bool,
+    // A value an input is given without being sent it, once or always (as a constant)
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    initializer: Option<InputInitializer>,
 }
 
 impl Default for IO {
@@ -36,6 +41,7 @@ impl Default for IO {
             depth: default_depth(),
             route: "".to_string(),
             flow_io: false,
+            initializer: None,
         }
     }
 }
@@ -71,10 +77,23 @@ impl IO {
         self.flow_io = flow_io;
     }
 
+    pub fn initializer(&self) -> &Option<InputInitializer> {
+        &self.initializer
+    }
+
+    pub fn set_initializer(&mut self, initializer: InputInitializer) {
+        self.initializer = Some(initializer);
+    }
+
     pub fn set_name(&mut self, name: String) {
         self.name = name;
     }
 
+    // The full type of the IO, including all levels (e.g. "Array/Number")
+    pub fn full_datatype(&self) -> &DataType {
+        &self.datatype
+    }
+
     pub fn datatype(&self, level: usize) -> DataType {
         let type_levels: Vec<&str> = self.datatype.split('/').collect();
         DataType::from(type_levels[level])
@@ -98,6 +117,11 @@ impl IO {
     pub fn set_datatype(&mut self, datatype: &DataType) {
         self.datatype = datatype.clone()
     }
+
+    // Validate the IO, where it's type can be one of the struct 'types' declared
+    pub fn validate_in(&self, types: &[StructType]) -> Result<(), String> {
+        self.datatype.valid_in(types)
+    }
 }
 
 impl HasRoute for IO {
@@ -120,7 +144,7 @@ fn default_depth() -> usize {
 
 impl Validate for IO {
     fn validate(&self) -> Result<(), String> {
-        self.datatype.valid()
+        self.validate_in(&[])
     }
 }
 
@@ -174,7 +198,7 @@ impl SetRoute for IOSet {
 
 pub trait Find {
     fn find_by_name(&self, name: &Name) -> Result<IO, String>;
-    fn find_by_route(&self, route: &Route) -> Result<IO, String>;
+    fn find_by_route(&self, route: &Route, types: &[StructType]) -> Result<IO, String>;
 }
 
 impl Find for IOSet {
@@ -190,25 +214,34 @@ impl Find for IOSet {
         Err(format!("No inputs or outputs found when looking for input/output '{}'", name))
     }
 
-    // TODO improve the Route handling of this - maybe moving into Router
+    /*
+        Find the IO at a sub-route, which is either the name of an IO or a route to part of the
+        value of an IO - an element of an array (e.g. "numbers/0") or a field of a struct type
+        (e.g. "point/x"). The IO returned for a part of an IO has the type of that part.
+    */
     // TODO return a reference to the IO, with same lifetime as IOSet?
-    fn find_by_route(&self, sub_route: &Route) -> Result<IO, String> {
+    fn find_by_route(&self, sub_route: &Route, types: &[StructType]) -> Result<IO, String> {
         if let Some(ios) = self {
             for io in ios {
-                let (array_route, _num, array_index) = Router::without_trailing_array_index(sub_route);
-                if array_index && (io.datatype(0).is_array()) && (io.name() == array_route.as_ref()) {
-                    let mut found = io.clone();
-                    found.set_datatype(&io.datatype(1)); // the type within the array
-                    let mut new_route = found.route().clone();
-                    new_route.push_str(&format!("/{}", sub_route));
-                    found.set_route(new_route, false);
-                    return Ok(found);
-                }
-
                 if io.name() == sub_route {
                     return Ok(io.clone());
                 }
             }
+
+            // Look for an IO that the sub-route is within, preferring a named IO to the unnamed one
+            let within = ios.iter().filter(|io| !io.name().is_empty())
+                .find(|io| sub_route.starts_with(&format!("{}/", io.name())))
+                .or(ios.iter().find(|io| io.name().is_empty()));
+            if let Some(io) = within {
+                let part_route = sub_route[io.name().len()..].trim_start_matches('/');
+                let mut found = io.clone();
+                found.set_datatype(&part_type(io.full_datatype(), part_route, types)
+                    .map_err(|e| format!("Sub-route '{}' is not valid: {}", sub_route, e))?);
+                let route = format!("{}/{}", io.route(), part_route);
+                found.set_route(route, false);
+                return Ok(found);
+            }
+
             return Err(format!("No output with sub-route '{}' was found", sub_route));
         }
 
@@ -216,10 +249,36 @@ impl Find for IOSet {
     }
 }
 
+/*
+    Find the type of the part of a value of type 'datatype' at 'part_route', following each
+    segment of the route into an element of an array or a field of a struct
+*/
+fn part_type(datatype: &DataType, part_route: &str, types: &[StructType]) -> Result<DataType, String> {
+    let mut part_type = datatype.clone();
+
+    for segment in part_route.split('/') {
+        if part_type.is_array() && segment.parse::<usize>().is_ok() {
+            part_type = part_type.array_type();
+        } else if let Some(struct_type) = types.find_type(&part_type) {
+            part_type = struct_type.field_type(segment)
+                .ok_or(format!("type '{}' has no field '{}'", part_type, segment))?.clone();
+        } else {
+            return Err(format!("'{}' is not an array index or field of type '{}'", segment, part_type));
+        }
+    }
+
+    Ok(part_type)
+}
+
 #[cfg(test)]
 mod test {
     use toml;
     use super::IO;
+    use super::IOSet;
+    use super::Find;
+    use model::struct_type::StructType;
+    use model::struct_type::Field;
+    use model::route::HasRoute;
     use loader::loader::Validate;
     use model::name::HasName;
 
@@ -321,6 +380,7 @@ mod test {
             route: "".to_string(),
             depth: 1,
             flow_io: false,
+            initializer: None,
         };
         let io1 = IO {
             name: "different_name".to_string(),
@@ -328,6 +388,7 @@ mod test {
             route: "".to_string(),
             depth: 1,
             flow_io: false,
+            initializer: None,
         };
         let ioset = Some(vec!(io0, io1));
         ioset.validate().unwrap()
@@ -342,6 +403,7 @@ mod test {
             route: "".to_string(),
             depth: 1,
             flow_io: false,
+            initializer: None,
         };
         let io1 = io0.clone();
         let ioset = Some(vec!(io0, io1));
@@ -357,6 +419,7 @@ mod test {
             route: "".to_string(),
             depth: 1,
             flow_io: false,
+            initializer: None,
         };
         let io1 = IO {
             name: "".to_string(),
@@ -364,8 +427,78 @@ mod test {
             route: "".to_string(),
             depth: 1,
             flow_io: false,
+            initializer: None,
         };
         let ioset = Some(vec!(io0, io1));
         ioset.validate().unwrap()
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn array_index_sub_route_has_element_type() {
+        let io = IO {
+            name: "matrix".to_string(),
+            datatype: "Array/Array/Number".to_string(),
+            route: "/flow/f/matrix".to_string(),
+            depth: 1,
+            flow_io: false,
+            initializer: None,
+        };
+        let ioset = Some(vec!(io));
+        let element = ioset.find_by_route(&"matrix/0".to_string(), &[]).unwrap();
+        assert_eq!(element.full_datatype(), "Array/Number");
+        assert_eq!(element.route(), "/flow/f/matrix/0");
+    }
+
+    fn point_types() -> Vec<StructType> {
+        vec!(StructType {
+            name: "Point".to_string(),
+            fields: vec!(Field { name: "x".to_string(), datatype: "Number".to_string() },
+                         Field { name: "label".to_string(), datatype: "String".to_string() }),
+        })
+    }
+
+    fn points_ioset() -> IOSet {
+        let points = IO {
+            name: "points".to_string(),
+            datatype: "Array/Point".to_string(),
+            route: "/flow/f/points".to_string(),
+            depth: 1,
+            flow_io: false,
+            initializer: None,
+        };
+        let origin = IO {
+            name: "".to_string(),
+            datatype: "Point".to_string(),
+            route: "/flow/f".to_string(),
+            depth: 1,
+            flow_io: false,
+            initializer: None,
+        };
+        Some(vec!(points, origin))
+    }
+
+    #[test]
+    fn struct_field_sub_route_has_field_type() {
+        let label = points_ioset().find_by_route(&"label".to_string(), &point_types()).unwrap();
+        assert_eq!(label.full_datatype(), "String");
+        assert_eq!(label.route(), "/flow/f/label");
+    }
+
+    #[test]
+    fn field_of_array_element_sub_route() {
+        let x = points_ioset().find_by_route(&"points/1/x".to_string(), &point_types()).unwrap();
+        assert_eq!(x.full_datatype(), "Number");
+        assert_eq!(x.route(), "/flow/f/points/1/x");
+    }
+
+    #[test]
+    fn unknown_field_sub_route_not_found() {
+        assert!(points_ioset().find_by_route(&"points/1/y".to_string(), &point_types()).is_err());
+        assert!(points_ioset().find_by_route(&"y".to_string(), &point_types()).is_err());
+    }
+
+    #[test]
+    fn field_of_undeclared_type_not_found() {
+        assert!(points_ioset().find_by_route(&"label".to_string(), &[]).is_err());
+    }
+}
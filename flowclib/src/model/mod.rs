@@ -1,5 +1,8 @@
 pub mod name;
 pub mod datatype;
+pub mod struct_type;
+pub mod parameter;
+pub mod shape;
 pub mod route;
 pub mod value;
 pub mod function;
@@ -8,4 +11,5 @@ pub mod process;
 pub mod process_reference;
 pub mod connection;
 pub mod io;
-pub mod runnable;
\ No newline at end of file
+pub mod runnable;
+pub mod span;
\ No newline at end of file
@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+
+use serde_json::Value as JsonValue;
+
+use model::name::Name;
+use loader::loader::Validate;
+use loader::error::EntryError;
+
+/// A named parameter declared by a flow, that a reference to the flow can give a value for, so
+/// the same flow can be used with different configuration. Where a string in the flow that is
+/// just "$" followed by the name of the parameter (e.g. "$max") is used as the initial value of a
+/// value, or an input or parameter in a reference to a process, it is replaced by the value. A
+/// string that starts with "$" but is not the name of a parameter is an error, and to use such a
+/// string as it is the "$" is doubled (e.g. "$$max" for "$max").
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Parameter {
+    pub name: Name,
+    /// The value used if a reference to the flow doesn't give one
+    pub default: Option<JsonValue>,
+}
+
+impl Validate for Parameter {
+    fn validate(&self) -> Result<(), String> {
+        self.name.validate()
+    }
+}
+
+/// Check that each of the parameters declared by a flow is only declared once, giving the entry
+/// in the flow's definition of the one that is declared again if not
+pub fn declared_once(declared: &[Parameter], flow_name: &str) -> Result<(), EntryError> {
+    for (index, parameter) in declared.iter().enumerate() {
+        if declared[..index].iter().any(|earlier| earlier.name == parameter.name) {
+            return Err(EntryError::new(format!("parameter.{}.name", index),
+                                       format!("Parameter '{}' is declared more than once in flow '{}'",
+                                               parameter.name, flow_name)));
+        }
+    }
+
+    Ok(())
+}
+
+/// Find the value of each of the parameters declared by a flow, from the values given for them in
+/// the reference to it ('supplied') or else their default values
+pub fn resolve(declared: &[Parameter], supplied: &HashMap<Name, JsonValue>, flow_name: &str)
+               -> Result<HashMap<Name, JsonValue>, String> {
+    for name in supplied.keys() {
+        if !declared.iter().any(|parameter| &parameter.name == name) {
+            return Err(format!("Flow '{}' has no parameter called '{}'", flow_name, name));
+        }
+    }
+
+    let mut values = HashMap::new();
+    for parameter in declared {
+        let value = supplied.get(&parameter.name).or(parameter.default.as_ref())
+            .ok_or(format!("Parameter '{}' of flow '{}' has no default value, so it must be given a value \
+                            where the flow is used", parameter.name, flow_name))?;
+        values.insert(parameter.name.clone(), value.clone());
+    }
+
+    Ok(values)
+}
+
+/// Replace each string within 'value' that refers to one of the parameters (e.g. "$max") with the
+/// value of the parameter, and each string starting with "$$" with the string after the first "$".
+/// Any other string starting with "$" refers to a parameter the flow doesn't declare, so is an error.
+pub fn substitute(value: &mut JsonValue, parameters: &HashMap<Name, JsonValue>, flow_name: &str)
+                  -> Result<(), String> {
+    let replacement = match *value {
+        JsonValue::String(ref string) if string.starts_with("$$") => Some(JsonValue::String(string[1..].to_string())),
+        JsonValue::String(ref string) if string.starts_with('$') => Some(parameters.get(&string[1..]).cloned()
+            .ok_or(format!("'{}' refers to a parameter that flow '{}' does not declare", string, flow_name))?),
+        JsonValue::Array(ref mut elements) => {
+            for element in elements {
+                substitute(element, parameters, flow_name)?;
+            }
+            None
+        }
+        JsonValue::Object(ref mut fields) => {
+            for (_, field) in fields.iter_mut() {
+                substitute(field, parameters, flow_name)?;
+            }
+            None
+        }
+        _ => None
+    };
+
+    if let Some(replacement) = replacement {
+        *value = replacement;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+    use toml;
+    use super::Parameter;
+    use super::declared_once;
+    use super::resolve;
+    use super::substitute;
+
+    fn declared() -> Vec<Parameter> {
+        #[derive(Deserialize)]
+        struct Parameters {
+            parameter: Vec<Parameter>
+        }
+        let parameters: Parameters = toml::from_str("
+        [[parameter]]
+        name = 'max'
+        default = 10
+        [[parameter]]
+        name = 'label'
+        ").unwrap();
+        parameters.parameter
+    }
+
+    #[test]
+    fn supplied_value_used_in_place_of_default() {
+        let mut supplied = HashMap::new();
+        supplied.insert("max".to_string(), json!(5));
+        supplied.insert("label".to_string(), json!("count"));
+        let values = resolve(&declared(), &supplied, "flow").unwrap();
+        assert_eq!(values.get("max"), Some(&json!(5)));
+    }
+
+    #[test]
+    fn default_used_if_not_supplied() {
+        let mut supplied = HashMap::new();
+        supplied.insert("label".to_string(), json!("count"));
+        let values = resolve(&declared(), &supplied, "flow").unwrap();
+        assert_eq!(values.get("max"), Some(&json!(10)));
+    }
+
+    #[test]
+    fn missing_value_is_error() {
+        assert!(resolve(&declared(), &HashMap::new(), "flow").is_err());
+    }
+
+    #[test]
+    fn undeclared_parameter_is_error() {
+        let mut supplied = HashMap::new();
+        supplied.insert("label".to_string(), json!("count"));
+        supplied.insert("min".to_string(), json!(0));
+        assert!(resolve(&declared(), &supplied, "flow").is_err());
+    }
+
+    #[test]
+    fn parameters_substituted_within_value() {
+        let mut parameters = HashMap::new();
+        parameters.insert("max".to_string(), json!([1, 2]));
+        let mut value = json!({"limit": "$max", "others": ["$max", "$$min", "max"]});
+        substitute(&mut value, &parameters, "flow").unwrap();
+        assert_eq!(value, json!({"limit": [1, 2], "others": [[1, 2], "$min", "max"]}));
+    }
+
+    #[test]
+    fn reference_to_undeclared_parameter_is_error() {
+        let mut parameters = HashMap::new();
+        parameters.insert("max".to_string(), json!(10));
+        let mut value = json!(["$max", "$min"]);
+        assert!(substitute(&mut value, &parameters, "flow").is_err());
+    }
+
+    #[test]
+    fn parameter_declared_twice_is_error() {
+        let mut parameters = declared();
+        declared_once(&parameters, "flow").unwrap();
+        parameters.push(parameters[0].clone());
+        assert_eq!(declared_once(&parameters, "flow").unwrap_err().entry, "parameter.2.name");
+    }
+}
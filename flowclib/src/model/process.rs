@@ -1,5 +1,6 @@
 use model::flow::Flow;
 use model::function::Function;
+use model::span::Spans;
 
 #[derive(Deserialize, Debug, Clone)]
 #[serde(untagged)]
@@ -12,4 +13,13 @@ impl Default for Process {
     fn default() -> Process {
         Process::FlowProcess(Flow::default())
     }
-}
\ No newline at end of file
+}
+impl Process {
+    /// Record where the values in the process's definition are, as found by the parser
+    pub fn set_spans(&mut self, spans: Spans) {
+        match *self {
+            Process::FlowProcess(ref mut flow) => flow.spans = spans,
+            Process::FunctionProcess(ref mut function) => function.set_spans(spans)
+        }
+    }
+}
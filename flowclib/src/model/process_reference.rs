@@ -4,14 +4,25 @@ use model::route::Route;
 use model::route::HasRoute;
 use model::process::Process;
 use loader::loader::Validate;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
 use std::fmt;
 use url::Url;
+use flowrlib::input::InputInitializer;
+use serde_json::Value as JsonValue;
 
 #[derive(Deserialize, Debug, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct ProcessReference {
     pub alias: Name,
     pub source: String,
+    // Values to give inputs of the process, by name, without connecting to them (in name order,
+    // so that the same definition always gives the same process)
+    #[serde(rename = "input", default)]
+    pub initializations: BTreeMap<Name, InputInitializer>,
+    // Values for the parameters of the process (if it's a flow), by name
+    #[serde(rename = "parameter", default)]
+    pub parameters: HashMap<Name, JsonValue>,
     #[serde(skip_deserializing, default = "ProcessReference::default_url")]
     pub source_url: Url,
     #[serde(skip_deserializing)]
@@ -58,6 +69,7 @@ impl ProcessReference {
 
 #[cfg(test)]
 mod test {
+    use flowrlib::input::InputInitializer;
     use super::ProcessReference;
 
     #[test]
@@ -70,6 +82,45 @@ mod test {
         let _reference: ProcessReference = toml::from_str(input_str).unwrap();
     }
 
+    #[test]
+    fn deserialize_input_initializers() {
+        let input_str = "
+        alias = 'other'
+        source = 'other.toml'
+        input.max = { constant = 10 }
+        input.start = { once = 'go' }
+        ";
+
+        let reference: ProcessReference = toml::from_str(input_str).unwrap();
+        assert_eq!(reference.initializations.get("max"), Some(&InputInitializer::Constant(json!(10))));
+        assert_eq!(reference.initializations.get("start"), Some(&InputInitializer::Once(json!("go"))));
+    }
+
+    #[test]
+    fn deserialize_parameters() {
+        let input_str = "
+        alias = 'other'
+        source = 'other.toml'
+        parameter.max = 10
+        parameter.label = 'count'
+        ";
+
+        let reference: ProcessReference = toml::from_str(input_str).unwrap();
+        assert_eq!(reference.parameters.get("max"), Some(&json!(10)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn deserialize_unknown_initializer_fails() {
+        let input_str = "
+        alias = 'other'
+        source = 'other.toml'
+        input.max = { always = 10 }
+        ";
+
+        let _reference: ProcessReference = toml::from_str(input_str).unwrap();
+    }
+
     #[test]
     #[should_panic]
     fn deserialize_extra_field_fails() {
@@ -4,6 +4,7 @@ use url::Url;
 use model::name::HasName;
 use model::route::HasRoute;
 use serde_json::Value as JsonValue;
+use flowrlib::input::InputInitializer;
 use std::fmt;
 
 pub trait Runnable: fmt::Display + HasName + HasRoute {
@@ -12,9 +13,12 @@ pub trait Runnable: fmt::Display + HasName + HasRoute {
     fn get_inputs(&self) -> IOSet;
     fn get_outputs(&self) -> IOSet;
     fn add_output_route(&mut self, output_route: (Route, usize, usize)); // Route is the output subroute
+    fn set_output_routes(&mut self, output_routes: Vec<(Route, usize, usize)>);
+    fn set_input_initializer(&mut self, input_number: usize, initializer: InputInitializer) -> Result<(), String>;
     fn source_url(&self) -> Option<Url>;
     fn get_type(&self) -> &str;
     fn is_static_value(&self) -> bool;
+    fn is_impure(&self) -> bool; // It has effects other than its outputs, so must run even if they are not used
     fn get_output_routes(&self) -> &Vec<(Route, usize, usize)>;
     fn get_initial_value(&self) -> Option<JsonValue>;
     fn get_impl_path(&self) -> String;
@@ -0,0 +1,155 @@
+use model::name::Name;
+use model::name::HasName;
+use model::datatype::DataType;
+use model::datatype::TypeCheck;
+use model::io::IOSet;
+use model::struct_type::StructType;
+use model::struct_type::FindType;
+use flowrlib::implementation::ERROR_OUTPUT;
+
+/// The shape of the value output by a runnable, built from it's declared outputs and the struct
+/// types they use, that determines which routes (JSON pointers) to parts of the output can exist
+/// when it runs.
+#[derive(Debug, PartialEq)]
+pub enum Shape {
+    /// A value whose shape is not known until run-time (e.g. "Json" or "Map"), so any part may exist
+    Any,
+    /// A value that has no parts (e.g. a "String" or "Number")
+    Scalar(DataType),
+    /// An array with elements of a shape
+    Array(Box<Shape>),
+    /// An object with named fields (of a struct type, or of the named outputs of a runnable)
+    Object(Vec<(Name, Shape)>),
+}
+
+impl Shape {
+    pub fn of(datatype: &DataType, types: &[StructType]) -> Shape {
+        if datatype.is_generic() || datatype == "Map" {
+            Shape::Any
+        } else if datatype.is_array() {
+            Shape::Array(Box::new(Shape::of(&datatype.array_type(), types)))
+        } else if let Some(struct_type) = types.find_type(datatype) {
+            Shape::Object(struct_type.fields.iter()
+                .map(|field| (field.name.clone(), Shape::of(&field.datatype, types))).collect())
+        } else {
+            Shape::Scalar(datatype.clone())
+        }
+    }
+
+    /*
+        The output of a runnable is the value of it's unnamed output, or if it only has named
+        outputs then an object with a field for each of them. The error output is not part of the
+        output, as errors are sent separately.
+    */
+    pub fn of_outputs(outputs: &IOSet, types: &[StructType]) -> Shape {
+        let mut fields = vec!();
+
+        if let Some(ios) = outputs {
+            for io in ios.iter().filter(|io| io.name() != ERROR_OUTPUT) {
+                let shape = Shape::of(io.full_datatype(), types);
+                if io.name().is_empty() {
+                    return shape;
+                }
+                fields.push((io.name().clone(), shape));
+            }
+        }
+
+        Shape::Object(fields)
+    }
+
+    /*
+        Find the shape of the part of the value at 'route' (a JSON pointer such as "/points/0/x"),
+        or return an error describing why that part cannot exist
+    */
+    pub fn part(&self, route: &str) -> Result<&Shape, String> {
+        let mut shape = self;
+
+        for segment in route.split('/').skip_while(|segment| segment.is_empty()) {
+            shape = match *shape {
+                Shape::Any => return Ok(shape),
+                Shape::Array(ref element) if segment.parse::<usize>().is_ok() => element,
+                Shape::Object(ref fields) => {
+                    match fields.iter().find(|&&(ref name, _)| name == segment) {
+                        Some(&(_, ref field)) => field,
+                        None => return Err(format!("there is no field or output named '{}'", segment))
+                    }
+                }
+                Shape::Array(_) => return Err(format!("'{}' is not an array index", segment)),
+                Shape::Scalar(ref datatype) =>
+                    return Err(format!("a value of type '{}' has no part '{}'", datatype, segment))
+            };
+        }
+
+        Ok(shape)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use toml;
+    use model::io::IO;
+    use model::io::IOSet;
+    use model::struct_type::StructType;
+    use model::struct_type::Field;
+    use super::Shape;
+
+    fn types() -> Vec<StructType> {
+        vec!(StructType {
+            name: "Point".to_string(),
+            fields: vec!(Field { name: "x".to_string(), datatype: "Number".to_string() }),
+        })
+    }
+
+    fn outputs(definition: &str) -> IOSet {
+        #[derive(Deserialize)]
+        struct Outputs {
+            output: Vec<IO>
+        }
+        let outputs: Outputs = toml::from_str(definition).unwrap();
+        Some(outputs.output)
+    }
+
+    #[test]
+    fn named_outputs_are_fields() {
+        let shape = Shape::of_outputs(&outputs("
+        [[output]]
+        name = 'reversed'
+        type = 'String'
+        [[output]]
+        name = 'points'
+        type = 'Array/Point'
+        [[output]]
+        name = 'error'
+        type = 'Map'
+        "), &types());
+
+        assert_eq!(shape.part("").unwrap(), &shape);
+        assert_eq!(shape.part("/reversed").unwrap(), &Shape::Scalar("String".to_string()));
+        assert_eq!(shape.part("/points/2/x").unwrap(), &Shape::Scalar("Number".to_string()));
+        assert!(shape.part("/original").is_err());
+        assert!(shape.part("/error").is_err());
+        assert!(shape.part("/points/x").is_err());
+        assert!(shape.part("/reversed/0").is_err());
+    }
+
+    #[test]
+    fn unnamed_output_is_whole_value() {
+        let shape = Shape::of_outputs(&outputs("
+        [[output]]
+        type = 'Array/String'
+        "), &types());
+
+        assert_eq!(shape.part("/0").unwrap(), &Shape::Scalar("String".to_string()));
+        assert!(shape.part("/0/1").is_err());
+    }
+
+    #[test]
+    fn any_part_of_json_may_exist() {
+        let shape = Shape::of_outputs(&outputs("
+        [[output]]
+        type = 'Json'
+        "), &types());
+
+        assert_eq!(shape.part("/a/0/b").unwrap(), &Shape::Any);
+    }
+}
@@ -0,0 +1,76 @@
+use std::collections::BTreeMap;
+
+/// Where a value is in the text of a definition, as found by the parser when it was loaded
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Span {
+    /// The line (counting from 1) the value starts on
+    pub line: usize,
+    /// The column (counting from 1, in characters) the value starts at. For a string this is
+    /// after the opening quote.
+    pub column: usize,
+    /// How many characters of the line the value covers, not counting the quotes of a string
+    pub length: usize,
+}
+
+/// The spans of the values in a definition, by the path to each, made of the keys of the tables
+/// and the indexes of the arrays it is in (e.g. "connection.1.to" for the 'to' of the second
+/// connection)
+pub type Spans = BTreeMap<String, Span>;
+
+impl Span {
+    /// The span of the text of 'contents' between the byte offsets 'start' and 'end', without the
+    /// quotes if it is a quoted string
+    pub fn of(contents: &str, start: usize, end: usize) -> Self {
+        let text = &contents[start..end];
+        let quoted = text.len() >= 2 && (text.starts_with('"') || text.starts_with('\''))
+            && text.ends_with(&text[..1]);
+        let (start, end) = if quoted { (start + 1, end - 1) } else { (start, end) };
+
+        let before = &contents[..start];
+        let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+        Span {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            length: contents[start..end].chars().count(),
+        }
+    }
+}
+
+/// Find the span of 'entry' in a definition: of the value at that path, or if it is a table or
+/// array, of the first value in it (e.g. "connection.1" is at the first value of that connection)
+pub fn find(spans: &Spans, entry: &str) -> Option<Span> {
+    if let Some(span) = spans.get(entry) {
+        return Some(*span);
+    }
+
+    let prefix = format!("{}.", entry);
+    spans.iter().filter(|&(path, _)| path.starts_with(&prefix))
+        .map(|(_, span)| *span).min_by_key(|span| (span.line, span.column))
+}
+
+#[cfg(test)]
+mod test {
+    use super::Span;
+    use super::Spans;
+    use super::find;
+
+    #[test]
+    fn quotes_not_included() {
+        let contents = "flow = 'test'\nto = \"process/add/i1\"\ninit = 10\n";
+        assert_eq!(Span::of(contents, 7, 13), Span { line: 1, column: 9, length: 4 });
+        assert_eq!(Span::of(contents, 19, 35), Span { line: 2, column: 7, length: 14 });
+        assert_eq!(Span::of(contents, 43, 45), Span { line: 3, column: 8, length: 2 });
+    }
+
+    #[test]
+    fn entry_found_at_first_value() {
+        let mut spans = Spans::new();
+        spans.insert("connection.0.to".to_string(), Span { line: 5, column: 7, length: 2 });
+        spans.insert("connection.0.from".to_string(), Span { line: 4, column: 9, length: 3 });
+        spans.insert("connection.1.from".to_string(), Span { line: 7, column: 9, length: 3 });
+
+        assert_eq!(find(&spans, "connection.0.to"), Some(Span { line: 5, column: 7, length: 2 }));
+        assert_eq!(find(&spans, "connection.0"), Some(Span { line: 4, column: 9, length: 3 }));
+        assert_eq!(find(&spans, "connection.2"), None);
+    }
+}
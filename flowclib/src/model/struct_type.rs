@@ -0,0 +1,192 @@
+use model::name::Name;
+use model::datatype::DataType;
+use model::datatype::TypeCheck;
+use loader::loader::Validate;
+use loader::error::EntryError;
+use std::collections::HashSet;
+
+/// A named record type declared in a flow or function definition, with a set of named and typed
+/// fields. It can be used as the type of an IO or value within the definition it is declared in,
+/// and within any process it uses, and the fields are the sub-routes of an output of that type.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct StructType {
+    pub name: DataType,
+    #[serde(rename = "field")]
+    pub fields: Vec<Field>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Field {
+    pub name: Name,
+    #[serde(rename = "type")]
+    pub datatype: DataType,
+}
+
+impl StructType {
+    pub fn field_type(&self, name: &str) -> Option<&DataType> {
+        self.fields.iter().find(|field| field.name == name).map(|field| &field.datatype)
+    }
+
+    /*
+        Check the type is well formed, with the types of it's fields being ones that are
+        in 'scope'
+    */
+    pub fn validate_in(&self, scope: &[StructType]) -> Result<(), String> {
+        if self.name.contains('/') || DataType::from(self.name.as_str()).valid().is_ok() {
+            return Err(format!("'{}' cannot be used as the name of a type", self.name));
+        }
+
+        if self.fields.is_empty() {
+            return Err(format!("Type '{}' must have at least one field", self.name));
+        }
+
+        let mut field_names = HashSet::new();
+        for field in &self.fields {
+            field.name.validate()?;
+            if !field_names.insert(&field.name) {
+                return Err(format!("Type '{}' has two fields called '{}'", self.name, field.name));
+            }
+            field.datatype.valid_in(scope)
+                .map_err(|e| format!("Field '{}' of type '{}': {}", field.name, self.name, e))?;
+        }
+
+        Ok(())
+    }
+}
+
+pub trait FindType {
+    fn find_type(&self, name: &str) -> Option<&StructType>;
+}
+
+impl FindType for [StructType] {
+    fn find_type(&self, name: &str) -> Option<&StructType> {
+        self.iter().find(|struct_type| struct_type.name == name)
+    }
+}
+
+/// Add the types in scope in the parent definition to the types declared in a definition, so
+/// that a type declared in the definition hides one with the same name declared in a parent.
+pub fn add_parent_types(types: &mut Vec<StructType>, parent_types: &[StructType]) -> Result<(), EntryError> {
+    let mut names = HashSet::new();
+    for (index, struct_type) in types.iter().enumerate() {
+        if !names.insert(struct_type.name.clone()) {
+            return Err(EntryError::new(format!("type.{}.name", index),
+                                       format!("Type '{}' is declared more than once", struct_type.name)));
+        }
+    }
+
+    types.extend(parent_types.iter().cloned());
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use toml;
+    use super::StructType;
+    use super::FindType;
+    use super::add_parent_types;
+
+    #[derive(Deserialize)]
+    struct Types {
+        #[serde(rename = "type")]
+        types: Vec<StructType>,
+    }
+
+    fn types(definition: &str) -> Vec<StructType> {
+        let types: Types = toml::from_str(definition).unwrap();
+        types.types
+    }
+
+    const POINT: &str = "
+        [[type]]
+        name = 'Point'
+        [[type.field]]
+        name = 'x'
+        type = 'Number'
+        [[type.field]]
+        name = 'y'
+        type = 'Number'
+        ";
+
+    #[test]
+    fn deserialize_type() {
+        let types = types(POINT);
+        types[0].validate_in(&types).unwrap();
+        assert_eq!(types.find_type("Point").unwrap().field_type("y").unwrap(), "Number");
+        assert!(types[0].field_type("z").is_none());
+    }
+
+    #[test]
+    fn field_of_declared_type() {
+        let mut types = types("
+        [[type]]
+        name = 'Line'
+        [[type.field]]
+        name = 'points'
+        type = 'Array/Point'
+        ");
+        add_parent_types(&mut types, &self::types(POINT)).unwrap();
+        types[0].validate_in(&types).unwrap();
+    }
+
+    #[test]
+    fn field_of_unknown_type() {
+        let types = types("
+        [[type]]
+        name = 'Line'
+        [[type.field]]
+        name = 'start'
+        type = 'Point'
+        ");
+        assert!(types[0].validate_in(&types).is_err());
+    }
+
+    #[test]
+    fn builtin_type_name_not_allowed() {
+        let types = types("
+        [[type]]
+        name = 'String'
+        [[type.field]]
+        name = 'x'
+        type = 'Number'
+        ");
+        assert!(types[0].validate_in(&types).is_err());
+    }
+
+    #[test]
+    fn duplicate_field_not_allowed() {
+        let types = types("
+        [[type]]
+        name = 'Point'
+        [[type.field]]
+        name = 'x'
+        type = 'Number'
+        [[type.field]]
+        name = 'x'
+        type = 'String'
+        ");
+        assert!(types[0].validate_in(&types).is_err());
+    }
+
+    #[test]
+    fn declared_type_hides_parent_type() {
+        let mut types = types("
+        [[type]]
+        name = 'Point'
+        [[type.field]]
+        name = 'x'
+        type = 'String'
+        ");
+        add_parent_types(&mut types, &self::types(POINT)).unwrap();
+        assert_eq!(types.find_type("Point").unwrap().field_type("x").unwrap(), "String");
+    }
+
+    #[test]
+    fn duplicate_declaration_not_allowed() {
+        let mut types = types(POINT);
+        types.extend(self::types(POINT));
+        assert!(add_parent_types(&mut types, &vec!()).is_err());
+    }
+}
@@ -9,7 +9,10 @@ use model::route::HasRoute;
 use model::route::SetRoute;
 use model::io::IO;
 use model::io::IOSet;
+use model::datatype::TypeCheck;
+use model::struct_type::StructType;
 use model::runnable::Runnable;
+use flowrlib::input::InputInitializer;
 use url::Url;
 
 use std::fmt;
@@ -84,6 +87,16 @@ impl Runnable for Value {
         self.output_routes.push(output_route);
     }
 
+    fn set_output_routes(&mut self, output_routes: Vec<(Route, usize, usize)>) {
+        self.output_routes = output_routes;
+    }
+
+    // A value is a store for what is sent to it, and it's initial value is set with 'init'
+    fn set_input_initializer(&mut self, _input_number: usize, _initializer: InputInitializer) -> Result<(), String> {
+        Err(format!("The input of value '{}' cannot be initialized, use 'init' to give it an initial value",
+                    self.name))
+    }
+
     fn source_url(&self) -> Option<Url> {
         None
     }
@@ -96,6 +109,8 @@ impl Runnable for Value {
         self.static_value
     }
 
+    fn is_impure(&self) -> bool { false }
+
     fn get_output_routes(&self) -> &Vec<(Route, usize, usize)> {
         &self.output_routes
     }
@@ -111,7 +126,7 @@ impl Runnable for Value {
 
 impl Validate for Value {
     fn validate(&self) -> Result<(), String> {
-        self.datatype.validate()
+        self.validate_in(&[])
     }
 }
 
@@ -164,6 +179,17 @@ impl Value {
         }
     }
 
+    // Validate the value, where it's type can be one of the struct 'types' declared
+    pub fn validate_in(&self, types: &[StructType]) -> Result<(), String> {
+        self.name.validate()?;
+        self.datatype.valid_in(types)?;
+        self.outputs.iter().flatten().map(|output| output.validate_in(types)).collect()
+    }
+
+    pub fn set_initial_value(&mut self, initial_value: Option<JsonValue>) {
+        self.init = initial_value;
+    }
+
     pub fn get_input(&self) -> Result<IO, String> {
         Ok(IO::new(&self.datatype, &self.route))
     }
@@ -397,7 +423,7 @@ mod test {
         let mut value: Value = toml::from_str(value_str).unwrap();
         value.set_routes_from_parent(&Route::from("/flow"), false);
 
-        let output = value.outputs.find_by_route(&Route::from("")).unwrap();
+        let output = value.outputs.find_by_route(&Route::from(""), &[]).unwrap();
         assert_eq!(output.route(), &Route::from("/flow/test_value"));
         assert_eq!(output.datatype(0), DataType::from("Json"));
         assert_eq!(output.flow_io(), false);
@@ -420,7 +446,7 @@ mod test {
         let mut value: Value = toml::from_str(value_str).unwrap();
         value.set_routes_from_parent(&Route::from("/flow"), false);
 
-        let output = value.outputs.find_by_route(&Route::from("sub_output")).unwrap();
+        let output = value.outputs.find_by_route(&Route::from("sub_output"), &[]).unwrap();
         assert_eq!(output.route(), &Route::from("/flow/test_value/sub_output"));
         assert_eq!(output.datatype(0), DataType::from("String"));
         assert_eq!(output.flow_io(), false);
@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use flowclib::loader::cache::Cache;
+use flowclib::loader::loader;
+use flowclib::loader::loader::DEFINITION_EXTENSIONS;
+use flowclib::model::io::IOSet;
+use flowclib::model::name::HasName;
+use flowclib::model::process::Process::FlowProcess;
+use flowclib::model::process::Process::FunctionProcess;
+use flowclib::model::runnable::Runnable;
+use serde_json::Value as JsonValue;
+use url::Url;
+
+use document::line_at;
+use document::string_entry;
+use document_provider::DocumentProvider;
+
+// The kinds of completion items, in the Language Server Protocol
+const FIELD: u64 = 5;
+const FILE: u64 = 17;
+
+/*
+    Find the completions for the string being typed at a position in the document at 'url':
+      - the routes that a connection can be 'from' or 'to', of the flow's inputs or outputs, it's
+        values and the inputs or outputs of the processes it uses (e.g. "process/add/i1")
+      - the 'source' of a process in a library (e.g. "lib://flowstdlib/math/add.toml"), found
+        using the 'FLOW_LIB_PATH' environment variable as flowc does, in any format there is a
+        loader for
+    Each completion replaces what has been typed of the string so far.
+*/
+pub fn completions(url: &Url, documents: &HashMap<Url, String>, line: usize, character: usize) -> Vec<JsonValue> {
+    let line_text = match documents.get(url).and_then(|text| line_at(text, line)) {
+        Some(line_text) => line_text,
+        None => return vec!()
+    };
+    let entry = match string_entry(line_text) {
+        Some(entry) => entry,
+        None => return vec!()
+    };
+    if character < entry.value_start {
+        return vec!();
+    }
+    let typed: String = line_text.chars().skip(entry.value_start).take(character - entry.value_start).collect();
+
+    let (candidates, kind) = match entry.key {
+        "from" => (routes(url, documents, true), FIELD),
+        "to" => (routes(url, documents, false), FIELD),
+        "source" if typed.starts_with("lib:") || "lib://".starts_with(typed.as_str()) => (lib_sources(), FILE),
+        _ => (vec!(), FIELD)
+    };
+
+    candidates.into_iter().filter(|candidate| candidate.starts_with(&typed))
+        .map(|candidate| json!({
+            "label": candidate,
+            "kind": kind,
+            "textEdit": {
+                "range": {
+                    "start": { "line": line, "character": entry.value_start },
+                    "end": { "line": line, "character": character }
+                },
+                "newText": candidate
+            }
+        })).collect()
+}
+
+/*
+    The routes that connections in the flow defined in the document can be from (or else to). The
+    flow is only parsed, and each process it uses loaded by itself, so that routes can be found
+    while the flow has errors (e.g. a connection that is only partly typed).
+*/
+fn routes(url: &Url, documents: &HashMap<Url, String>, from: bool) -> Vec<String> {
+    let provider = DocumentProvider::new(documents);
+    let flow = match Cache::new(&provider, None).load(url) {
+        Ok(FlowProcess(flow)) => flow,
+        _ => return vec!()
+    };
+
+    let mut routes = vec!();
+    let ios = if from { &flow.inputs } else { &flow.outputs };
+    add_routes(&mut routes, if from { "input" } else { "output" }, ios);
+
+    for value in flow.values.iter().flatten() {
+        routes.push(format!("value/{}", value.name()));
+    }
+
+    for process_ref in flow.process_refs.iter().flatten() {
+        let source_url = match url.join(&process_ref.source) {
+            Ok(source_url) => source_url,
+            Err(_) => continue
+        };
+        let prefix = format!("process/{}", process_ref.alias());
+        match loader::load_process(&"".to_string(), process_ref.alias(), &source_url, &provider) {
+            Ok(FunctionProcess(ref function)) =>
+                add_routes(&mut routes, &prefix, &if from { function.get_outputs() } else { function.get_inputs() }),
+            Ok(FlowProcess(ref subflow)) =>
+                add_routes(&mut routes, &prefix, if from { &subflow.outputs } else { &subflow.inputs }),
+            Err(_) => {}
+        }
+    }
+
+    routes
+}
+
+fn add_routes(routes: &mut Vec<String>, prefix: &str, ios: &IOSet) {
+    for io in ios.iter().flatten() {
+        if io.name().is_empty() {
+            routes.push(prefix.to_string());
+        } else {
+            routes.push(format!("{}/{}", prefix, io.name()));
+        }
+    }
+}
+
+/*
+    Find the sources of all the definitions in the libraries in the directories in the
+    'FLOW_LIB_PATH' environment variable. The definitions of a library are the files in it's 'src'
+    directory.
+*/
+fn lib_sources() -> Vec<String> {
+    let mut sources = vec!();
+
+    if let Some(lib_path) = env::var_os("FLOW_LIB_PATH") {
+        for dir in env::split_paths(&lib_path) {
+            for lib in fs::read_dir(dir).into_iter().flatten().flatten() {
+                let lib_dir = lib.path();
+                if let Some(lib_name) = lib_dir.file_name().and_then(|name| name.to_str()) {
+                    add_definitions(&mut sources, &format!("lib://{}", lib_name), &lib_dir.join("src"));
+                }
+            }
+        }
+    }
+
+    sources.sort();
+    sources.dedup();
+    sources
+}
+
+fn add_definitions(sources: &mut Vec<String>, prefix: &str, dir: &Path) {
+    for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+        let path = entry.path();
+        let name = match path.file_name().and_then(|name| name.to_str()) {
+            Some(name) => name.to_string(),
+            None => continue
+        };
+
+        if path.is_dir() {
+            add_definitions(sources, &format!("{}/{}", prefix, name), &path);
+        } else if path.extension().and_then(|extension| extension.to_str())
+            .map_or(false, |extension| DEFINITION_EXTENSIONS.contains(&extension)) {
+            sources.push(format!("{}/{}", prefix, name));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+    use std::env;
+    use std::fs;
+
+    use serde_json::Value as JsonValue;
+    use url::Url;
+
+    use super::add_definitions;
+    use super::completions;
+
+    fn labels(completions: Vec<JsonValue>) -> Vec<String> {
+        completions.iter().map(|completion| completion["label"].as_str().unwrap().to_string()).collect()
+    }
+
+    #[test]
+    fn routes_completed() {
+        let url = Url::from_file_path(env::current_dir().unwrap()).unwrap().join("open/context.toml").unwrap();
+        let mut documents = HashMap::new();
+        documents.insert(url.clone(), "flow = 'open'
+[[input]]
+name = 'start'
+type = 'Number'
+
+[[value]]
+name = 'total'
+type = 'Number'
+
+[[connection]]
+from = 'input/'
+to = 'value/'
+".to_string());
+
+        assert_eq!(labels(completions(&url, &documents, 10, 14)), vec!("input/start"));
+        assert_eq!(labels(completions(&url, &documents, 11, 6)), vec!("value/total"));
+        assert!(completions(&url, &documents, 0, 8).is_empty());
+    }
+
+    #[test]
+    fn definitions_in_every_format_found() {
+        let dir = env::temp_dir().join("flowls_definitions_in_every_format_found");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("math")).unwrap();
+        for file in &["print.toml", "read.yaml", "write.yml", "math/add.json", "math/add.rs", "print.md"] {
+            fs::write(dir.join(file), "").unwrap();
+        }
+
+        let mut sources = vec!();
+        add_definitions(&mut sources, "lib://test", &dir);
+        sources.sort();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(sources, vec!("lib://test/math/add.json", "lib://test/print.toml", "lib://test/read.yaml",
+                                 "lib://test/write.yml"));
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+
+use flowclib::loader::error::Severity;
+use flowclib::loader::error::SourceError;
+use flowclib::loader::loader;
+use serde_json::Value as JsonValue;
+use url::Url;
+
+use document_provider::DocumentProvider;
+
+// The severities of diagnostics, in the Language Server Protocol
+const ERROR: u64 = 1;
+const WARNING: u64 = 2;
+const INFORMATION: u64 = 3;
+
+/*
+    Load the flow or function defined in the document at 'url', as flowc does, with the flows and
+    functions it uses, and turn the errors found (e.g. by validating it and building it's
+    connections) into diagnostics to show in the document
+*/
+pub fn diagnostics(url: &Url, documents: &HashMap<Url, String>) -> Vec<JsonValue> {
+    if !documents.contains_key(url) {
+        return vec!();
+    }
+
+    let provider = DocumentProvider::new(documents);
+    match loader::load_process(&"".to_string(), &alias_of(url), url, &provider) {
+        Ok(_) => vec!(),
+        Err(errors) => errors.iter().map(|error| diagnostic(url, error)).collect()
+    }
+}
+
+/*
+    The alias to load the document with, as if it was the context of a flow, from it's file name
+*/
+fn alias_of(url: &Url) -> String {
+    url.path_segments().and_then(|segments| segments.last())
+        .and_then(|file_name| file_name.split('.').next())
+        .filter(|name| !name.is_empty())
+        .unwrap_or("context").to_string()
+}
+
+/*
+    Create a diagnostic for an error, at the entry in the document it is about. An error in
+    another definition (e.g. of a flow the document uses) is shown at the start of the document,
+    with where it is in the other definition as related information.
+*/
+fn diagnostic(url: &Url, error: &SourceError) -> JsonValue {
+    let mut diagnostic = json!({
+        "range": range(0, 0, 0),
+        "severity": match error.severity {
+            Severity::Error => ERROR,
+            Severity::Warning => WARNING,
+            Severity::Info => INFORMATION
+        },
+        "source": "flowls",
+        "message": error.message
+    });
+
+    if let Some(ref location) = error.location {
+        let entry = range(location.line - 1, location.column - 1, location.length);
+        if location.url == *url {
+            diagnostic["range"] = entry;
+        } else {
+            diagnostic["relatedInformation"] = json!([{
+                "location": { "uri": location.url.as_str(), "range": entry },
+                "message": format!("Found in '{}'", location.url)
+            }]);
+        }
+    }
+
+    diagnostic
+}
+
+fn range(line: usize, character: usize, length: usize) -> JsonValue {
+    json!({
+        "start": { "line": line, "character": character },
+        "end": { "line": line, "character": character + length }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+    use std::env;
+
+    use url::Url;
+
+    use super::diagnostics;
+
+    fn document(text: &str) -> (Url, HashMap<Url, String>) {
+        let url = Url::from_file_path(env::current_dir().unwrap()).unwrap().join("open/context.toml").unwrap();
+        let mut documents = HashMap::new();
+        documents.insert(url.clone(), text.to_string());
+        (url, documents)
+    }
+
+    #[test]
+    fn valid_flow_has_no_diagnostics() {
+        let (url, documents) = document("flow = 'ok'\n[[value]]\nname = 'one'\ntype = 'Number'\ninit = 1\n");
+        assert!(diagnostics(&url, &documents).is_empty());
+    }
+
+    #[test]
+    fn connection_error_at_route() {
+        let (url, documents) = document("flow = 'bad'
+[[value]]
+name = 'one'
+type = 'Number'
+
+[[connection]]
+from = 'value/one'
+to = 'value/two'
+");
+        let diagnostics = diagnostics(&url, &documents);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0]["range"]["start"], json!({"line": 7, "character": 6}));
+        assert_eq!(diagnostics[0]["range"]["end"], json!({"line": 7, "character": 15}));
+    }
+
+    #[test]
+    fn syntax_error_on_line() {
+        let (url, documents) = document("flow = 'bad'\n[[value]\n");
+        let diagnostics = diagnostics(&url, &documents);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0]["range"]["start"]["line"], json!(1));
+    }
+
+    #[test]
+    fn error_in_other_document_related() {
+        let (url, mut documents) = document("flow = 'uses'
+[[process]]
+alias = 'sub'
+source = 'sub.toml'
+");
+        let sub_url = url.join("sub.toml").unwrap();
+        documents.insert(sub_url.clone(), "flow = 'sub'\n[[connection]]\nfrom = 'value/a'\nto = 'value/b'\n".to_string());
+
+        let diagnostics = diagnostics(&url, &documents);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0]["range"]["start"], json!({"line": 0, "character": 0}));
+        let related = &diagnostics[0]["relatedInformation"][0]["location"];
+        assert_eq!(related["uri"], json!(sub_url.as_str()));
+        assert_eq!(related["range"]["start"], json!({"line": 2, "character": 8}));
+    }
+}
@@ -0,0 +1,100 @@
+/// A `key = "value"` entry on a line of a definition file, where the value is a string. If the
+/// string is not closed yet (e.g. while it is being typed) the value is the rest of the line.
+#[derive(Debug, PartialEq)]
+pub struct Entry<'a> {
+    pub key: &'a str,
+    pub value: &'a str,
+    /// The column (in characters) of the first character of the value, after the opening quote
+    pub value_start: usize,
+}
+
+/*
+    Find the string entry on a line, if there is one
+*/
+pub fn string_entry(line: &str) -> Option<Entry> {
+    let equals = line.find('=')?;
+    let key = line[..equals].trim();
+    if key.is_empty() || key.starts_with('[') || key.starts_with('#') {
+        return None;
+    }
+
+    let after_equals = &line[equals + 1..];
+    let quote_offset = after_equals.find(|c: char| !c.is_whitespace())?;
+    let quote = after_equals[quote_offset..].chars().next()?;
+    if quote != '"' && quote != '\'' {
+        return None;
+    }
+
+    let start = equals + 1 + quote_offset + 1;
+    let rest = &line[start..];
+    let value = match rest.find(quote) {
+        Some(end) => &rest[..end],
+        None => rest
+    };
+
+    Some(Entry { key, value, value_start: line[..start].chars().count() })
+}
+
+/*
+    The text of a line of the document, if it has that many lines
+*/
+pub fn line_at(text: &str, line: usize) -> Option<&str> {
+    text.lines().nth(line)
+}
+
+/*
+    Find the 'source' of the process reference (a `[[process]]` table) that a line is in, if it
+    is in one
+*/
+pub fn process_source(text: &str, line: usize) -> Option<String> {
+    let lines: Vec<&str> = text.lines().collect();
+    if line >= lines.len() {
+        return None;
+    }
+
+    let header = (0..line + 1).rev().find(|&index| lines[index].trim().starts_with('['))?;
+    if lines[header].trim() != "[[process]]" {
+        return None;
+    }
+
+    lines[header + 1..].iter()
+        .take_while(|table_line| !table_line.trim().starts_with('['))
+        .filter_map(|table_line| string_entry(table_line))
+        .find(|entry| entry.key == "source")
+        .map(|entry| entry.value.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::Entry;
+    use super::process_source;
+    use super::string_entry;
+
+    const FLOW: &str = "flow = 'test'
+
+[[process]]
+alias = 'add'
+source = 'lib://flowstdlib/math/add.toml'
+
+[[connection]]
+from = \"process/add\"
+to = \"process/pr";
+
+    #[test]
+    fn entries_found() {
+        assert_eq!(string_entry("from = \"process/add\""),
+                   Some(Entry { key: "from", value: "process/add", value_start: 8 }));
+        assert_eq!(string_entry("  to = \"process/pr"),
+                   Some(Entry { key: "to", value: "process/pr", value_start: 8 }));
+        assert_eq!(string_entry("[[connection]]"), None);
+        assert_eq!(string_entry("depth = 2"), None);
+    }
+
+    #[test]
+    fn source_of_process_found() {
+        assert_eq!(process_source(FLOW, 3), Some("lib://flowstdlib/math/add.toml".to_string()));
+        assert_eq!(process_source(FLOW, 2), Some("lib://flowstdlib/math/add.toml".to_string()));
+        assert_eq!(process_source(FLOW, 7), None);
+        assert_eq!(process_source(FLOW, 0), None);
+    }
+}
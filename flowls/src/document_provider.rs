@@ -0,0 +1,36 @@
+use std::collections::HashMap;
+
+use flowrlib::errors::ProviderError;
+use flowrlib::provider::Provider;
+use url::Url;
+
+use provider::content::provider::MetaProvider;
+
+/// A provider of content that gives the text of the documents open in the client for their urls,
+/// as it may have been changed and not saved yet, and otherwise gets content as flowc does.
+pub struct DocumentProvider<'a> {
+    documents: &'a HashMap<Url, String>,
+    provider: MetaProvider,
+}
+
+impl<'a> DocumentProvider<'a> {
+    pub fn new(documents: &'a HashMap<Url, String>) -> Self {
+        DocumentProvider { documents, provider: MetaProvider {} }
+    }
+}
+
+impl<'a> Provider for DocumentProvider<'a> {
+    fn resolve(&self, url: &Url) -> Result<(Url, Option<String>), ProviderError> {
+        if self.documents.contains_key(url) {
+            return Ok((url.clone(), None));
+        }
+        self.provider.resolve(url)
+    }
+
+    fn get(&self, url: &Url) -> Result<Vec<u8>, ProviderError> {
+        match self.documents.get(url) {
+            Some(text) => Ok(text.as_bytes().to_vec()),
+            None => self.provider.get(url)
+        }
+    }
+}
@@ -0,0 +1,52 @@
+extern crate flowclib;
+extern crate flowrlib;
+extern crate provider;
+#[macro_use]
+extern crate serde_json;
+extern crate url;
+
+use std::io;
+use std::process::exit;
+
+mod completion;
+mod diagnostics;
+mod document;
+mod document_provider;
+mod navigation;
+mod server;
+mod transport;
+
+/*
+    A language server for flow definition files, that a client (e.g. an editor) starts and then
+    talks to using the Language Server Protocol over standard input and output. Nothing else can be
+    written to standard output, so errors are written to standard error.
+*/
+fn main() {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let mut input = stdin.lock();
+    let mut output = stdout.lock();
+    let mut server = server::Server::new();
+
+    loop {
+        let message = match transport::read_message(&mut input) {
+            Ok(Some(message)) => message,
+            Ok(None) => break,
+            Err(e) => {
+                eprintln!("{}", e);
+                continue;
+            }
+        };
+
+        if message["method"] == "exit" {
+            exit(if server.is_shutdown() { 0 } else { 1 });
+        }
+
+        for reply in server.handle(&message) {
+            if let Err(e) = transport::write_message(&mut output, &reply) {
+                eprintln!("Could not write to the client ({})", e);
+                exit(1);
+            }
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+
+use flowclib::loader::loader;
+use flowclib::model::io::IOSet;
+use flowclib::model::name::HasName;
+use flowclib::model::process::Process::FlowProcess;
+use flowclib::model::process::Process::FunctionProcess;
+use flowclib::model::runnable::Runnable;
+use flowrlib::provider::Provider;
+use serde_json::Value as JsonValue;
+use url::Url;
+
+use document::process_source;
+use document_provider::DocumentProvider;
+
+/*
+    Find where the definition of the process used by a `[[process]]` in the document is, if the
+    line is in one, as the location of the start of the file it's 'source' refers to (resolved
+    as flowc does, e.g. a "lib://" source to the file in the library)
+*/
+pub fn definition(url: &Url, documents: &HashMap<Url, String>, line: usize) -> JsonValue {
+    let provider = DocumentProvider::new(documents);
+    match source_url(url, documents, line).and_then(|source| provider.resolve(&source).ok()) {
+        Some((resolved_url, _)) => json!({
+            "uri": resolved_url.as_str(),
+            "range": {
+                "start": { "line": 0, "character": 0 },
+                "end": { "line": 0, "character": 0 }
+            }
+        }),
+        None => JsonValue::Null
+    }
+}
+
+/*
+    Describe the process used by a `[[process]]` in the document, if the line is in one: what
+    it is and it's inputs and outputs, with their types
+*/
+pub fn hover(url: &Url, documents: &HashMap<Url, String>, line: usize) -> JsonValue {
+    let source = match source_url(url, documents, line) {
+        Some(source) => source,
+        None => return JsonValue::Null
+    };
+
+    let provider = DocumentProvider::new(documents);
+    let description = match loader::load_process(&"".to_string(), &"process".to_string(), &source, &provider) {
+        Ok(FunctionProcess(ref function)) =>
+            describe("Function", function.name(), &function.get_inputs(), &function.get_outputs()),
+        Ok(FlowProcess(ref flow)) =>
+            describe("Flow", &flow.name, &flow.inputs, &flow.outputs),
+        Err(errors) => format!("Could not load '{}'\n\n{}", source,
+                               errors.iter().map(|error| error.message.as_str()).collect::<Vec<_>>().join("\n\n"))
+    };
+
+    json!({
+        "contents": {
+            "kind": "markdown",
+            "value": description
+        }
+    })
+}
+
+fn source_url(url: &Url, documents: &HashMap<Url, String>, line: usize) -> Option<Url> {
+    let source = process_source(documents.get(url)?, line)?;
+    url.join(&source).ok()
+}
+
+fn describe(kind: &str, name: &str, inputs: &IOSet, outputs: &IOSet) -> String {
+    format!("**{}** `{}`\n\n{}\n\n{}", kind, name, describe_ios("Inputs", inputs), describe_ios("Outputs", outputs))
+}
+
+fn describe_ios(heading: &str, ios: &IOSet) -> String {
+    let mut description = format!("{}:", heading);
+
+    match *ios {
+        Some(ref ios) if !ios.is_empty() => {
+            for io in ios {
+                if io.name().is_empty() {
+                    description.push_str(&format!("\n* `{}`", io.full_datatype()));
+                } else {
+                    description.push_str(&format!("\n* `{}`: `{}`", io.name(), io.full_datatype()));
+                }
+            }
+        }
+        _ => description.push_str(" none")
+    }
+
+    description
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+    use std::env;
+
+    use url::Url;
+
+    use super::definition;
+    use super::hover;
+
+    fn documents() -> (Url, HashMap<Url, String>) {
+        let url = Url::from_file_path(env::current_dir().unwrap()).unwrap().join("open/context.toml").unwrap();
+        let mut documents = HashMap::new();
+        documents.insert(url.clone(), "flow = 'open'
+
+[[process]]
+alias = 'sum'
+source = 'add.toml'
+".to_string());
+        documents.insert(url.join("add.toml").unwrap(), "function = 'add'
+[[input]]
+name = 'i1'
+type = 'Number'
+[[output]]
+type = 'Number'
+".to_string());
+        (url, documents)
+    }
+
+    #[test]
+    fn definition_of_source_found() {
+        let (url, documents) = documents();
+        assert_eq!(definition(&url, &documents, 4)["uri"], json!(url.join("add.toml").unwrap().as_str()));
+        assert!(definition(&url, &documents, 0).is_null());
+    }
+
+    #[test]
+    fn hover_describes_function() {
+        let (url, documents) = documents();
+        let description = hover(&url, &documents, 3)["contents"]["value"].as_str().unwrap().to_string();
+        assert!(description.starts_with("**Function** `add`"));
+        assert!(description.contains("* `i1`: `Number`"));
+        assert!(description.contains("Outputs:\n* `Number`"));
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+
+use serde_json::Value as JsonValue;
+use url::Url;
+
+use completion::completions;
+use diagnostics::diagnostics;
+use navigation::definition;
+use navigation::hover;
+
+// Error codes of responses, in the Language Server Protocol
+const INVALID_PARAMS: i64 = -32602;
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// The state of the language server: the documents open in the client (with the text they have
+/// in the client, that may not be saved yet) and if the client has asked it to shut down.
+pub struct Server {
+    documents: HashMap<Url, String>,
+    shutdown: bool,
+}
+
+impl Server {
+    pub fn new() -> Self {
+        Server { documents: HashMap::new(), shutdown: false }
+    }
+
+    /// If the client has asked the server to shut down, before it exits
+    pub fn is_shutdown(&self) -> bool {
+        self.shutdown
+    }
+
+    /// Handle a message from the client (a request or a notification) and return the messages to
+    /// send back to it: the response to a request, and diagnostics for the open documents when
+    /// they change
+    pub fn handle(&mut self, message: &JsonValue) -> Vec<JsonValue> {
+        let params = &message["params"];
+        match (message["method"].as_str(), message.get("id")) {
+            (Some(method), Some(id)) => {
+                let result = self.request(method, params);
+                vec!(response(id, result))
+            }
+            (Some(method), None) => self.notification(method, params),
+            // The server doesn't send requests, so there are no responses from the client to handle
+            _ => vec!()
+        }
+    }
+
+    fn request(&mut self, method: &str, params: &JsonValue) -> Result<JsonValue, (i64, String)> {
+        match method {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    "textDocumentSync": 1,
+                    "completionProvider": { "triggerCharacters": ["/", "\"", "'"] },
+                    "definitionProvider": true,
+                    "hoverProvider": true
+                },
+                "serverInfo": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION")
+                }
+            })),
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(JsonValue::Null)
+            }
+            "textDocument/completion" => {
+                let (url, line, character) = position(params)?;
+                Ok(json!(completions(&url, &self.documents, line, character)))
+            }
+            "textDocument/definition" => {
+                let (url, line, _) = position(params)?;
+                Ok(definition(&url, &self.documents, line))
+            }
+            "textDocument/hover" => {
+                let (url, line, _) = position(params)?;
+                Ok(hover(&url, &self.documents, line))
+            }
+            _ => Err((METHOD_NOT_FOUND, format!("Method '{}' is not supported", method)))
+        }
+    }
+
+    fn notification(&mut self, method: &str, params: &JsonValue) -> Vec<JsonValue> {
+        let url = match document_url(params) {
+            Ok(url) => url,
+            Err(_) => return vec!()
+        };
+
+        match method {
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or("").to_string();
+                self.documents.insert(url, text);
+            }
+            "textDocument/didChange" => {
+                // The whole text is sent on each change, so the last change is all of it
+                let changes = params["contentChanges"].as_array();
+                if let Some(text) = changes.and_then(|changes| changes.last()).and_then(|change| change["text"].as_str()) {
+                    self.documents.insert(url, text.to_string());
+                }
+            }
+            "textDocument/didSave" => {}
+            "textDocument/didClose" => {
+                self.documents.remove(&url);
+                return vec!(publish_diagnostics(&url, vec!()));
+            }
+            _ => return vec!()
+        }
+
+        // A change to one document can change the diagnostics of others that use it
+        self.documents.keys()
+            .map(|url| publish_diagnostics(url, diagnostics(url, &self.documents)))
+            .collect()
+    }
+}
+
+fn response(id: &JsonValue, result: Result<JsonValue, (i64, String)>) -> JsonValue {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+    }
+}
+
+fn publish_diagnostics(url: &Url, diagnostics: Vec<JsonValue>) -> JsonValue {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": url.as_str(), "diagnostics": diagnostics }
+    })
+}
+
+fn document_url(params: &JsonValue) -> Result<Url, (i64, String)> {
+    let uri = params["textDocument"]["uri"].as_str()
+        .ok_or((INVALID_PARAMS, "No text document given".to_string()))?;
+    Url::parse(uri).map_err(|e| (INVALID_PARAMS, format!("Invalid document uri '{}' ({})", uri, e)))
+}
+
+/*
+    The document and the line and character in it that a request is about
+*/
+fn position(params: &JsonValue) -> Result<(Url, usize, usize), (i64, String)> {
+    let url = document_url(params)?;
+    let line = params["position"]["line"].as_u64();
+    let character = params["position"]["character"].as_u64();
+    match (line, character) {
+        (Some(line), Some(character)) => Ok((url, line as usize, character as usize)),
+        _ => Err((INVALID_PARAMS, "No position in the document given".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Server;
+
+    #[test]
+    fn initialize_and_shutdown() {
+        let mut server = Server::new();
+        let responses = server.handle(&json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}}));
+        assert_eq!(responses[0]["id"], json!(1));
+        assert_eq!(responses[0]["result"]["capabilities"]["hoverProvider"], json!(true));
+
+        server.handle(&json!({"jsonrpc": "2.0", "id": 2, "method": "shutdown"}));
+        assert!(server.is_shutdown());
+    }
+
+    #[test]
+    fn unknown_request_is_error() {
+        let mut server = Server::new();
+        let responses = server.handle(&json!({"jsonrpc": "2.0", "id": 3, "method": "workspace/symbol", "params": {}}));
+        assert_eq!(responses[0]["error"]["code"], json!(-32601));
+    }
+
+    #[test]
+    fn opening_document_publishes_diagnostics() {
+        let mut server = Server::new();
+        let messages = server.handle(&json!({"jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {
+            "textDocument": { "uri": "file:///open/context.toml", "languageId": "toml", "version": 1,
+                              "text": "flow = 'open'\n[[connection]]\nfrom = 'value/a'\nto = 'value/b'\n" }
+        }}));
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0]["method"], json!("textDocument/publishDiagnostics"));
+        assert_eq!(messages[0]["params"]["diagnostics"].as_array().unwrap().len(), 1);
+    }
+}
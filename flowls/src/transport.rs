@@ -0,0 +1,76 @@
+use std::io::BufRead;
+use std::io::Write;
+
+use serde_json::Value as JsonValue;
+
+/*
+    Read the next message sent by the client. Each message is a JSON object, preceded by headers
+    (one per line, ending in an empty line) that give the length of it in bytes. Returns None when
+    the client has closed the connection.
+*/
+pub fn read_message(reader: &mut BufRead) -> Result<Option<JsonValue>, String> {
+    let mut content_length = None;
+
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).map_err(|e| e.to_string())? == 0 {
+            return Ok(None);
+        }
+
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+
+        let mut parts = header.splitn(2, ':');
+        if let (Some(name), Some(value)) = (parts.next(), parts.next()) {
+            if name.trim().eq_ignore_ascii_case("Content-Length") {
+                content_length = Some(value.trim().parse::<usize>()
+                    .map_err(|_| format!("Invalid Content-Length header '{}'", header))?);
+            }
+        }
+    }
+
+    let length = content_length.ok_or("Message has no Content-Length header")?;
+    let mut content = vec![0; length];
+    reader.read_exact(&mut content).map_err(|e| e.to_string())?;
+
+    serde_json::from_slice(&content).map(Some)
+        .map_err(|e| format!("Message is not valid JSON ({})", e))
+}
+
+/*
+    Send a message to the client, with the header giving it's length
+*/
+pub fn write_message(writer: &mut Write, message: &JsonValue) -> Result<(), String> {
+    let content = message.to_string();
+    write!(writer, "Content-Length: {}\r\n\r\n{}", content.len(), content).map_err(|e| e.to_string())?;
+    writer.flush().map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use super::read_message;
+    use super::write_message;
+
+    #[test]
+    fn message_written_can_be_read() {
+        let message = json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {"name": "façade"}});
+        let mut written = vec!();
+        write_message(&mut written, &message).unwrap();
+        write_message(&mut written, &message).unwrap();
+
+        let mut reader = Cursor::new(written);
+        assert_eq!(read_message(&mut reader).unwrap(), Some(message.clone()));
+        assert_eq!(read_message(&mut reader).unwrap(), Some(message));
+        assert_eq!(read_message(&mut reader).unwrap(), None);
+    }
+
+    #[test]
+    fn missing_length_is_error() {
+        let mut reader = Cursor::new("Content-Type: json\r\n\r\n{}".as_bytes().to_vec());
+        assert!(read_message(&mut reader).is_err());
+    }
+}
@@ -1,9 +1,7 @@
 use std::env;
 
 use flowrlib::implementation::Implementation;
-use flowrlib::implementation::RunAgain;
-use flowrlib::process::Process;
-use flowrlib::runlist::RunList;
+use flowrlib::implementation::RunResult;
 use serde_json::Value as JsonValue;
 
 use super::super::FLOW_ARGS_NAME;
@@ -11,13 +9,13 @@ use super::super::FLOW_ARGS_NAME;
 pub struct Get;
 
 impl Implementation for Get {
-    fn run(&self, process: &Process, mut _inputs: Vec<Vec<JsonValue>>, run_list: &mut RunList) -> RunAgain {
+    fn run(&self, _inputs: Vec<Vec<JsonValue>>) -> RunResult {
         if let Ok(args) = env::var(FLOW_ARGS_NAME) {
             env::remove_var(FLOW_ARGS_NAME); // so another invocation later won't use it by mistake
             let flow_args: Vec<&str> = args.split(' ').collect();
-            run_list.send_output(process, json!(flow_args));
+            return Ok((Some(json!(flow_args)), false));
         }
 
-        false
+        Ok((None, false))
     }
 }
\ No newline at end of file
@@ -0,0 +1,21 @@
+use std::io;
+use std::io::Write;
+
+use flowrlib::debug_client::DebugClient;
+
+/*
+    A DebugClient that uses the terminal, reading commands from STDIN and displaying on STDOUT.
+    Note that this is the same STDIN as functions in the flow (e.g. 'stdin') read from.
+*/
+pub struct CLIDebugClient {}
+
+impl DebugClient for CLIDebugClient {
+    fn display(&self, output: &str) {
+        print!("{}", output);
+        let _ = io::stdout().flush();
+    }
+
+    fn read_input(&self, input: &mut String) -> io::Result<usize> {
+        io::stdin().read_line(input)
+    }
+}
@@ -1,22 +1,25 @@
 use std::fs::File;
 use std::io::Write;
 
+use flowrlib::errors::ImplementationError;
 use flowrlib::implementation::Implementation;
-use flowrlib::implementation::RunAgain;
-use flowrlib::process::Process;
-use flowrlib::runlist::RunList;
+use flowrlib::implementation::RunResult;
 use serde_json::Value as JsonValue;
 
 pub struct FileWrite;
 
 impl Implementation for FileWrite {
-    fn run(&self, _process: &Process, mut inputs: Vec<Vec<JsonValue>>, _run_list: &mut RunList) -> RunAgain {
+    fn run(&self, mut inputs: Vec<Vec<JsonValue>>) -> RunResult {
         let filename = inputs.remove(0).remove(0);
         let bytes = inputs.remove(0).remove(0);
-        let mut file = File::create(filename.as_str().unwrap()).unwrap();
+        let filename = filename.as_str()
+            .ok_or(ImplementationError::InvalidInput(format!("filename '{}' is not a String", filename)))?;
+        let bytes = bytes.as_str()
+            .ok_or(ImplementationError::InvalidInput(format!("bytes '{}' is not a String", bytes)))?;
 
-        file.write(bytes.as_str().unwrap().as_bytes()).unwrap();
+        let mut file = File::create(filename)?;
+        file.write_all(bytes.as_bytes())?;
 
-        false
+        Ok((None, false))
     }
 }
\ No newline at end of file
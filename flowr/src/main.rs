@@ -1,6 +1,7 @@
 extern crate clap;
 extern crate flowrlib;
 extern crate flowstdlib;
+extern crate libc;
 #[macro_use]
 extern crate log;
 extern crate provider;
@@ -10,10 +11,16 @@ extern crate simplog;
 extern crate url;
 
 use std::env;
+use std::path::PathBuf;
 use std::process::exit;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 
 use clap::{App, AppSettings, Arg, ArgMatches};
-use flowrlib::execution::execute;
+use flowrlib::checkpoint::{Checkpoint, Snapshot};
+use flowrlib::debug_client::DebugClient;
+use flowrlib::debugger::Debugger;
+use flowrlib::execution::{execute, ExecutionOptions, FailurePolicy};
 use flowrlib::info;
 use flowrlib::loader::Loader;
 use simplog::simplog::SimpleLogger;
@@ -26,23 +33,43 @@ pub mod args;
 pub mod stdio;
 pub mod file;
 mod ilt;
+mod cli_debug_client;
 
 pub const FLOW_ARGS_NAME: &str = "FLOW_ARGS";
 
+const CLI_DEBUG_CLIENT: &DebugClient = &cli_debug_client::CLIDebugClient {};
+
+// Set when the user interrupts flowr (e.g. with Ctrl-C) to request a snapshot is written
+static SNAPSHOT_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn request_snapshot(_signal: libc::c_int) {
+    // If interrupted again before the snapshot was written, then exit without waiting for it
+    if SNAPSHOT_REQUESTED.swap(true, Ordering::SeqCst) {
+        unsafe { libc::_exit(130); }
+    }
+}
+
 fn main() -> Result<(), String> {
-    let url = parse_args(get_matches())?;
+    let (url, options) = parse_args(get_matches())?;
     let mut loader = Loader::new();
     let provider = MetaProvider {};
 
     // TODO pass in the root folder of the library, so wasm files in it can be found
     let cwd = cwd_as_url()?;
     // Load standard library functions we always want - flowr (for environment) and flowstdlib
-    loader.add_lib(&provider, ::ilt::get_ilt(), &cwd)?;
-    loader.add_lib(&provider, flowstdlib::ilt::get_ilt(), &cwd)?;
+    loader.add_lib(&provider, ::ilt::get_ilt(), &cwd).map_err(|e| e.to_string())?;
+    loader.add_lib(&provider, flowstdlib::ilt::get_ilt(), &cwd).map_err(|e| e.to_string())?;
 
-    let runnables = loader.load_flow(&provider, &url)?;
+    let runnables = loader.load_flow(&provider, &url).map_err(|e| e.to_string())?;
 
-    execute(runnables);
+    if let Err(e) = execute(runnables, options) {
+        error!("{}", e);
+        exit(1);
+    }
+
+    if SNAPSHOT_REQUESTED.load(Ordering::SeqCst) {
+        exit(130);
+    }
 
     exit(0);
 }
@@ -56,7 +83,7 @@ fn get_matches<'a>() -> ArgMatches<'a> {
         .version(env!("CARGO_PKG_VERSION"))
         .arg(Arg::with_name("flow-manifest")
             .help("the name of the 'flow' manifest file")
-            .required(true)
+            .required_unless("resume")
             .index(1))
         .arg(Arg::with_name("log")
             .short("l")
@@ -64,6 +91,58 @@ fn get_matches<'a>() -> ArgMatches<'a> {
             .takes_value(true)
             .value_name("LOG_LEVEL")
             .help("Set log level for output (trace, debug, info, warn, error (default))"))
+        .arg(Arg::with_name("debugger")
+            .short("d")
+            .long("debugger")
+            .help("Enable the debugger, which stops before the first process is dispatched"))
+        .arg(Arg::with_name("jobs")
+            .short("j")
+            .long("jobs")
+            .takes_value(true)
+            .value_name("MAX_JOBS")
+            .help("Set the maximum number of processs to run in parallel (default 1)"))
+        .arg(Arg::with_name("metrics")
+            .short("m")
+            .long("metrics")
+            .takes_value(true)
+            .value_name("METRICS_FILE")
+            .help("Write metrics gathered during execution to a file as JSON"))
+        .arg(Arg::with_name("prometheus")
+            .long("prometheus")
+            .takes_value(true)
+            .value_name("PROMETHEUS_FILE")
+            .help("Write metrics gathered during execution to a file in the Prometheus text format"))
+        .arg(Arg::with_name("trace")
+            .short("t")
+            .long("trace")
+            .takes_value(true)
+            .value_name("TRACE_FILE")
+            .help("Write a trace of the execution to a file in the Chrome trace event format"))
+        .arg(Arg::with_name("on-error")
+            .long("on-error")
+            .takes_value(true)
+            .value_name("POLICY")
+            .possible_values(&["abort", "skip"])
+            .help("What to do when a process fails, if it's error output is not connected: abort the flow (default), or report the error and skip it"))
+        .arg(Arg::with_name("checkpoint")
+            .short("c")
+            .long("checkpoint")
+            .takes_value(true)
+            .value_name("SNAPSHOT_FILE")
+            .help("Write a snapshot of the execution to a file when interrupted, so it can be resumed"))
+        .arg(Arg::with_name("checkpoint-interval")
+            .long("checkpoint-interval")
+            .takes_value(true)
+            .value_name("SECONDS")
+            .requires("checkpoint")
+            .help("Also write a snapshot of the execution every SECONDS"))
+        .arg(Arg::with_name("resume")
+            .short("r")
+            .long("resume")
+            .takes_value(true)
+            .value_name("SNAPSHOT_FILE")
+            .conflicts_with("flow-manifest")
+            .help("Resume execution of a flow from a snapshot"))
         .arg(Arg::with_name("flow-arguments")
             .multiple(true))
         .get_matches()
@@ -72,11 +151,35 @@ fn get_matches<'a>() -> ArgMatches<'a> {
 /*
     Parse the command line arguments
 */
-fn parse_args(matches: ArgMatches) -> Result<Url, String> {
+fn parse_args(matches: ArgMatches) -> Result<(Url, ExecutionOptions<'static>), String> {
+    SimpleLogger::init(matches.value_of("log"));
+
+    info!("'{}' version {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+    info!("'flowrlib' version {}\n", info::version());
+
+    // When resuming, the flow and its arguments are those the snapshot was taken of
+    let (url, flow_args, resume) = match matches.value_of("resume") {
+        Some(snapshot_file) => {
+            let snapshot_url = url_from_string(Some(snapshot_file))?;
+            let snapshot = Snapshot::load(&MetaProvider {}, &snapshot_url).map_err(|e| e.to_string())?;
+            let url = Url::parse(&snapshot.manifest)
+                .map_err(|e| format!("Invalid manifest url '{}' in snapshot ({})", snapshot.manifest, e))?;
+            info!("Resuming execution of flow '{}' from snapshot '{}'", url, snapshot_url);
+            (url, snapshot.arguments.clone(), Some(snapshot))
+        }
+        None => {
+            let flow_args = match matches.values_of("flow-arguments") {
+                Some(flow_args) => flow_args.map(String::from).collect(),
+                None => vec!()
+            };
+            (url_from_string(matches.value_of("flow-manifest"))?, flow_args, None)
+        }
+    };
+
     // Set anvironment variable with the args
     // this will not be unique, but it will be used very soon and removed
-    if let Some(flow_args) = matches.values_of("flow-arguments") {
-        let mut args: Vec<&str> = flow_args.collect();
+    if !flow_args.is_empty() {
+        let mut args: Vec<&str> = flow_args.iter().map(|arg| arg.as_str()).collect();
         // arg #0 is the flow/package name
         // TODO fix this to be the name of the flow, not 'flowr'
         args.insert(0, env!("CARGO_PKG_NAME"));
@@ -84,10 +187,64 @@ fn parse_args(matches: ArgMatches) -> Result<Url, String> {
         debug!("Setup '{}' with values = '{:?}'", FLOW_ARGS_NAME, args);
     }
 
-    SimpleLogger::init(matches.value_of("log"));
+    let jobs = match matches.value_of("jobs") {
+        Some(value) => match value.parse::<usize>() {
+            Ok(jobs) if jobs > 0 => jobs,
+            _ => return Err(format!("Invalid value '{}' for the maximum number of jobs, \
+                                    it must be a number greater than zero", value))
+        },
+        None => 1
+    };
+    debug!("Maximum number of parallel jobs set to {}", jobs);
 
-    info!("'{}' version {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
-    info!("'flowrlib' version {}\n", info::version());
+    let debugger = if matches.is_present("debugger") {
+        Some(Debugger::new(CLI_DEBUG_CLIENT))
+    } else {
+        None
+    };
+
+    let failure_policy = match matches.value_of("on-error") {
+        Some(policy) => policy.parse::<FailurePolicy>()?,
+        None => FailurePolicy::Abort
+    };
+
+    let options = ExecutionOptions {
+        jobs,
+        debugger,
+        metrics_file: matches.value_of("metrics").map(PathBuf::from),
+        prometheus_file: matches.value_of("prometheus").map(PathBuf::from),
+        trace_file: matches.value_of("trace").map(PathBuf::from),
+        failure_policy,
+        checkpoint: parse_checkpoint(&matches, &url, flow_args)?,
+        resume,
+    };
+
+    Ok((url, options))
+}
+
+/*
+    If a snapshot file was given, write a snapshot to it when interrupted, and at the interval given
+*/
+fn parse_checkpoint(matches: &ArgMatches, url: &Url, flow_args: Vec<String>)
+                    -> Result<Option<Checkpoint<'static>>, String> {
+    let snapshot_file = match matches.value_of("checkpoint") {
+        Some(snapshot_file) => snapshot_file,
+        None => return Ok(None)
+    };
+
+    let mut checkpoint = Checkpoint::new(PathBuf::from(snapshot_file), url.as_str(), flow_args);
+    checkpoint.set_request(&SNAPSHOT_REQUESTED);
+    unsafe {
+        libc::signal(libc::SIGINT, request_snapshot as extern "C" fn(libc::c_int) as libc::sighandler_t);
+    }
+
+    if let Some(value) = matches.value_of("checkpoint-interval") {
+        match value.parse::<u64>() {
+            Ok(seconds) if seconds > 0 => checkpoint.set_interval(Duration::from_secs(seconds)),
+            _ => return Err(format!("Invalid value '{}' for the checkpoint interval, \
+                                    it must be a number of seconds greater than zero", value))
+        }
+    }
 
-    url_from_string(matches.value_of("flow-manifest"))
+    Ok(Some(checkpoint))
 }
\ No newline at end of file
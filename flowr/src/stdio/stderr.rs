@@ -1,13 +1,11 @@
 use flowrlib::implementation::Implementation;
-use flowrlib::implementation::RunAgain;
-use flowrlib::process::Process;
-use flowrlib::runlist::RunList;
+use flowrlib::implementation::RunResult;
 use serde_json::Value as JsonValue;
 
 pub struct Stderr;
 
 impl Implementation for Stderr {
-    fn run(&self, _process: &Process, mut inputs: Vec<Vec<JsonValue>>, _run_list: &mut RunList) -> RunAgain {
+    fn run(&self, mut inputs: Vec<Vec<JsonValue>>) -> RunResult {
         let input = inputs.remove(0).remove(0);
         match input {
             JsonValue::String(string) => {
@@ -24,9 +22,12 @@ impl Implementation for Stderr {
                     eprintln!("{}", entry);
                 }
             },
+            JsonValue::Object(_) => {
+                eprintln!("{}", input);
+            },
             _ => {}
         };
 
-        true
+        Ok((None, true))
     }
 }
\ No newline at end of file
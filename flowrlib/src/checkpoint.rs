@@ -0,0 +1,120 @@
+use errors::ExecutionError;
+use errors::LoadError;
+use metrics::Metrics;
+use provider::Provider;
+use runlist::RunList;
+use serde_json;
+use serde_json::Value as JsonValue;
+use std::fs;
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use url::Url;
+
+/// The values waiting on the inputs of one process when a snapshot was taken
+#[derive(Serialize, Deserialize)]
+pub struct ProcessSnapshot {
+    pub id: usize,
+    pub name: String,
+    pub inputs: Vec<Vec<JsonValue>>,
+}
+
+/// A `Snapshot` is the state of execution of a flow at a point between running processs, that
+/// can be saved to a file and later used to resume execution of the flow from that point.
+///
+/// It contains the values waiting on the inputs of every process and the state of the `RunList`
+/// (which processs can run, which are blocked and the order they will run in) and the metrics
+/// gathered so far. The state inside implementations (e.g. the memory of a wasm implementation)
+/// is not part of the snapshot.
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+    /// The Url of the manifest of the flow
+    pub manifest: String,
+    /// The arguments the flow was started with
+    pub arguments: Vec<String>,
+    pub processs: Vec<ProcessSnapshot>,
+    pub can_run: Vec<usize>,
+    pub blocking: Vec<(usize, usize)>,
+    pub will_run: Vec<usize>,
+    pub metrics: Metrics,
+}
+
+impl Snapshot {
+    pub fn load(provider: &Provider, url: &Url) -> Result<Snapshot, LoadError> {
+        let (resolved_url, _) = provider.resolve(url)?;
+        let content = provider.get(&resolved_url)?;
+
+        serde_json::from_slice(&content)
+            .map_err(|e| LoadError::Parse { url: url.to_string(), source: e })
+    }
+}
+
+/// A `Checkpoint` controls when snapshots of the execution of a flow are written to a file:
+/// every `interval` if one is set, and when it is requested (e.g. by a signal handler setting
+/// the `request` flag), in which case execution then stops.
+pub struct Checkpoint<'a> {
+    file: PathBuf,
+    manifest: String,
+    arguments: Vec<String>,
+    interval: Option<Duration>,
+    request: Option<&'a AtomicBool>,
+    last: Instant,
+}
+
+impl<'a> Checkpoint<'a> {
+    /// Write snapshots of the flow with the manifest at Url `manifest`, started with `arguments`,
+    /// to `file`
+    pub fn new(file: PathBuf, manifest: &str, arguments: Vec<String>) -> Self {
+        Checkpoint {
+            file,
+            manifest: manifest.to_string(),
+            arguments,
+            interval: None,
+            request: None,
+            last: Instant::now(),
+        }
+    }
+
+    /// Write a snapshot every `interval` while the flow is executing
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = Some(interval);
+    }
+
+    /// Write a snapshot and stop execution when `request` is set
+    pub fn set_request(&mut self, request: &'a AtomicBool) {
+        self.request = Some(request);
+    }
+
+    // Has a snapshot been requested, so execution should stop once it has been written
+    pub fn requested(&self) -> bool {
+        self.request.map_or(false, |request| request.load(Ordering::SeqCst))
+    }
+
+    // Is it time to write a snapshot
+    pub fn due(&self) -> bool {
+        self.requested() || self.interval.map_or(false, |interval| self.last.elapsed() >= interval)
+    }
+
+    /*
+        Write a snapshot of the RunList to the file. It is written to a temporary file first and
+        then renamed, so a previous snapshot is not lost if writing this one is interrupted.
+    */
+    pub fn write(&mut self, run_list: &mut RunList) -> Result<(), ExecutionError> {
+        let snapshot = run_list.snapshot(&self.manifest, &self.arguments);
+        let contents = serde_json::to_string_pretty(&snapshot)?;
+
+        let mut temp_file = self.file.clone().into_os_string();
+        temp_file.push(".tmp");
+        let temp_file = PathBuf::from(temp_file);
+        File::create(&temp_file)
+            .and_then(|mut file| file.write_all(contents.as_bytes()))
+            .and_then(|_| fs::rename(&temp_file, &self.file))
+            .map_err(|e| ExecutionError::Write { path: self.file.clone(), source: e })?;
+
+        info!("Snapshot of flow execution written to '{}'", self.file.display());
+        self.last = Instant::now();
+        Ok(())
+    }
+}
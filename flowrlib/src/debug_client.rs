@@ -0,0 +1,12 @@
+use std::io;
+
+/// A `DebugClient` is how the `Debugger` interacts with the user, so that the runtime is not tied
+/// to any particular way of doing that (e.g. a terminal, or a UI).
+pub trait DebugClient {
+    // Display some output to the user
+    fn display(&self, output: &str);
+
+    // Read a line of input (a command) from the user into 'input'. Ok(0) means there is no more
+    // input available.
+    fn read_input(&self, input: &mut String) -> io::Result<usize>;
+}
@@ -0,0 +1,393 @@
+use debug_client::DebugClient;
+use runlist::RunList;
+use serde_json::Value as JsonValue;
+use std::collections::HashSet;
+
+const HELP: &str = "Debugger commands:
+    b, break <process>                   - break before <process> is dispatched
+    b, break <process> input <number>    - break when a value is sent to input <number> of <process>
+    b, break <process> output [<route>]  - break when <process> sends on output <route> (default output if none)
+    l, list                              - list breakpoints
+    d, delete                            - delete all breakpoints
+    c, continue                          - continue execution until the next breakpoint
+    s, step [<count>]                    - dispatch the next <count> (default 1) processs then stop again
+    i, inspect <process>                 - show the state of <process>, including its input queues
+    r, runlist                           - show the 'Can Run', 'Blocking', 'Will Run' and 'Running' lists
+    q, quit                              - stop execution of the flow
+    h, help                              - show this help
+  <process> is a process id or name. An empty command repeats 'step'.
+";
+
+/*
+    The Debugger is called by the execution loop before each process is dispatched and before the
+    output of a process is sent, and stops to take commands from the user when stepping or when
+    a breakpoint is hit.
+*/
+pub struct Debugger<'a> {
+    client: &'a dyn DebugClient,
+    // None when continuing, Some(n) when stepping and it will stop in 'n' dispatches
+    steps: Option<usize>,
+    process_breakpoints: HashSet<usize>,
+    // (process id, input number)
+    input_breakpoints: HashSet<(usize, usize)>,
+    // (process id, output route)
+    output_breakpoints: HashSet<(usize, String)>,
+    quit: bool,
+}
+
+impl<'a> Debugger<'a> {
+    pub fn new(client: &'a dyn DebugClient) -> Self {
+        Debugger {
+            client,
+            steps: Some(1),
+            process_breakpoints: HashSet::<usize>::new(),
+            input_breakpoints: HashSet::<(usize, usize)>::new(),
+            output_breakpoints: HashSet::<(usize, String)>::new(),
+            quit: false,
+        }
+    }
+
+    /*
+        Called before the process 'id' is dispatched. Returns false if the user has asked to
+        stop execution of the flow.
+    */
+    pub fn check_dispatch(&mut self, run_list: &RunList, id: usize) -> bool {
+        if self.quit {
+            return false;
+        }
+
+        let at_breakpoint = self.process_breakpoints.contains(&id);
+        let stop = match self.steps {
+            Some(steps) if steps > 1 => {
+                self.steps = Some(steps - 1);
+                false
+            }
+            Some(_) => true,
+            None => false
+        };
+
+        if at_breakpoint || stop {
+            if at_breakpoint {
+                self.client.display(&format!("Breakpoint on dispatch of Process #{}\n", id));
+            }
+            self.client.display(&format!("Next to be dispatched:\n{}",
+                                         *run_list.get(id).lock().unwrap()));
+            self.command_loop(run_list);
+        }
+
+        !self.quit
+    }
+
+    /*
+        Called before the 'output' of the process 'id' is sent to its destinations, stopping if
+        there is a breakpoint on any of the output routes or destination inputs it will be sent on
+    */
+    pub fn check_output(&mut self, run_list: &RunList, id: usize, output: &JsonValue) {
+        if self.quit {
+            return;
+        }
+
+        let destinations = run_list.get(id).lock().unwrap().output_destinations().clone();
+        let mut hit = false;
+        for (route, destination_id, io_number) in destinations {
+            if self.output_breakpoints.contains(&(id, route.clone())) {
+                self.client.display(&format!("Breakpoint on Process #{} output '{}': {}\n",
+                                             id, route, output.pointer(&route).unwrap_or(output)));
+                hit = true;
+            }
+            if self.input_breakpoints.contains(&(destination_id, io_number)) {
+                self.client.display(&format!("Breakpoint on Process #{} input #{}: sent {} by Process #{}\n",
+                                             destination_id, io_number,
+                                             output.pointer(&route).unwrap_or(output), id));
+                hit = true;
+            }
+        }
+
+        if hit {
+            self.command_loop(run_list);
+        }
+    }
+
+    /*
+        Take commands from the user until one of them causes execution to continue
+    */
+    fn command_loop(&mut self, run_list: &RunList) {
+        loop {
+            self.client.display("Debug> ");
+            let mut input = String::new();
+            match self.client.read_input(&mut input) {
+                Ok(0) | Err(_) => {
+                    // No more commands can be read so just let the flow run to the end
+                    self.steps = None;
+                    return;
+                }
+                Ok(_) => {}
+            }
+
+            let parts: Vec<&str> = input.split_whitespace().collect();
+            let (command, args) = match parts.split_first() {
+                Some((command, args)) => (*command, args),
+                None => ("step", &parts[0..0])
+            };
+
+            match command {
+                "b" | "break" => self.add_breakpoint(run_list, args),
+                "l" | "list" => self.list_breakpoints(),
+                "d" | "delete" => {
+                    self.process_breakpoints.clear();
+                    self.input_breakpoints.clear();
+                    self.output_breakpoints.clear();
+                    self.client.display("All breakpoints deleted\n");
+                }
+                "c" | "continue" => {
+                    self.steps = None;
+                    return;
+                }
+                "s" | "step" => {
+                    match args.get(0).map(|count| count.parse::<usize>()) {
+                        None => self.steps = Some(1),
+                        Some(Ok(count)) if count > 0 => self.steps = Some(count),
+                        Some(_) => {
+                            self.client.display(&format!("Invalid step count '{}'\n", args[0]));
+                            continue;
+                        }
+                    }
+                    return;
+                }
+                "i" | "inspect" => {
+                    match args.get(0).map(|process| Self::find_process(run_list, process)) {
+                        Some(Ok(id)) => self.client.display(&format!("{}", *run_list.get(id).lock().unwrap())),
+                        Some(Err(message)) => self.client.display(&message),
+                        None => self.client.display("'inspect' needs a process id or name\n")
+                    }
+                }
+                "r" | "runlist" => self.client.display(&format!("{}", run_list)),
+                "q" | "quit" => {
+                    self.quit = true;
+                    return;
+                }
+                "h" | "help" => self.client.display(HELP),
+                _ => self.client.display(&format!("Unknown command '{}', 'help' lists commands\n", command))
+            }
+        }
+    }
+
+    fn add_breakpoint(&mut self, run_list: &RunList, args: &[&str]) {
+        let id = match args.get(0) {
+            Some(process) => match Self::find_process(run_list, process) {
+                Ok(id) => id,
+                Err(message) => return self.client.display(&message)
+            },
+            None => return self.client.display("'break' needs a process id or name\n")
+        };
+
+        match (args.get(1), args.get(2)) {
+            (None, _) => {
+                self.process_breakpoints.insert(id);
+                self.client.display(&format!("Breakpoint set on dispatch of Process #{}\n", id));
+            }
+            (Some(&"input"), Some(number)) => {
+                match number.parse::<usize>() {
+                    Ok(io_number) => {
+                        self.input_breakpoints.insert((id, io_number));
+                        self.client.display(&format!("Breakpoint set on Process #{} input #{}\n", id, io_number));
+                    }
+                    Err(_) => self.client.display(&format!("Invalid input number '{}'\n", number))
+                }
+            }
+            (Some(&"output"), route) => {
+                let route = route.map(|route| route.to_string()).unwrap_or("".to_string());
+                self.client.display(&format!("Breakpoint set on Process #{} output '{}'\n", id, route));
+                self.output_breakpoints.insert((id, route));
+            }
+            _ => self.client.display("Invalid breakpoint, 'help' shows how to set breakpoints\n")
+        }
+    }
+
+    fn list_breakpoints(&self) {
+        let mut output = String::new();
+        for id in &self.process_breakpoints {
+            output.push_str(&format!("\tDispatch of Process #{}\n", id));
+        }
+        for &(id, io_number) in &self.input_breakpoints {
+            output.push_str(&format!("\tProcess #{} input #{}\n", id, io_number));
+        }
+        for &(id, ref route) in &self.output_breakpoints {
+            output.push_str(&format!("\tProcess #{} output '{}'\n", id, route));
+        }
+
+        if output.is_empty() {
+            self.client.display("No breakpoints set\n");
+        } else {
+            self.client.display(&format!("Breakpoints:\n{}", output));
+        }
+    }
+
+    // Find the id of a process from its id or its name
+    fn find_process(run_list: &RunList, process: &str) -> Result<usize, String> {
+        if let Ok(id) = process.parse::<usize>() {
+            if id < run_list.processs().len() {
+                return Ok(id);
+            }
+        }
+
+        for process_arc in run_list.processs() {
+            let candidate = process_arc.lock().unwrap();
+            if candidate.name() == process {
+                return Ok(candidate.id());
+            }
+        }
+
+        Err(format!("No process with id or name '{}' was found\n", process))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use std::io;
+    use std::sync::{Arc, Mutex};
+
+    use debug_client::DebugClient;
+    use process::Process;
+    use runlist::RunList;
+
+    use super::Debugger;
+
+    struct TestClient {
+        commands: RefCell<Vec<&'static str>>,
+        output: RefCell<String>,
+    }
+
+    impl TestClient {
+        fn new(commands: Vec<&'static str>) -> Self {
+            TestClient { commands: RefCell::new(commands), output: RefCell::new(String::new()) }
+        }
+    }
+
+    impl DebugClient for TestClient {
+        fn display(&self, output: &str) {
+            self.output.borrow_mut().push_str(output);
+        }
+
+        fn read_input(&self, input: &mut String) -> io::Result<usize> {
+            let mut commands = self.commands.borrow_mut();
+            if commands.is_empty() {
+                return Ok(0);
+            }
+            let command = commands.remove(0);
+            input.push_str(command);
+            input.push('\n');
+            Ok(command.len() + 1)
+        }
+    }
+
+    fn test_run_list() -> RunList {
+        let p0 = Process::new("p0", false, "/test".to_string(), vec!(), 0, None,
+                              vec!(("/sub".to_string(), 1, 0)));
+        let p1 = Process::new("p1", false, "/test".to_string(), vec!(1), 1, None, vec!());
+        let mut run_list = RunList::new();
+        run_list.set_processs(vec!(Arc::new(Mutex::new(p0)), Arc::new(Mutex::new(p1))));
+        run_list
+    }
+
+    #[test]
+    fn stops_before_first_dispatch() {
+        let client = TestClient::new(vec!("step"));
+        let mut debugger = Debugger::new(&client);
+        let run_list = test_run_list();
+
+        assert!(debugger.check_dispatch(&run_list, 0));
+        assert!(client.output.borrow().contains("Process #0 'p0'"));
+        assert!(client.commands.borrow().is_empty());
+    }
+
+    #[test]
+    fn continue_runs_to_breakpoint_on_name() {
+        let client = TestClient::new(vec!("break p1", "continue"));
+        let mut debugger = Debugger::new(&client);
+        let run_list = test_run_list();
+
+        assert!(debugger.check_dispatch(&run_list, 0));
+        *client.output.borrow_mut() = String::new();
+
+        // No breakpoint on process 0 so it continues without stopping
+        assert!(debugger.check_dispatch(&run_list, 0));
+        assert!(client.output.borrow().is_empty());
+
+        *client.commands.borrow_mut() = vec!("continue");
+        assert!(debugger.check_dispatch(&run_list, 1));
+        assert!(client.output.borrow().contains("Breakpoint on dispatch of Process #1"));
+    }
+
+    #[test]
+    fn step_count() {
+        let client = TestClient::new(vec!("step 2"));
+        let mut debugger = Debugger::new(&client);
+        let run_list = test_run_list();
+
+        assert!(debugger.check_dispatch(&run_list, 0));
+        *client.commands.borrow_mut() = vec!("quit");
+
+        // first dispatch after the step command does not stop, the second does
+        assert!(debugger.check_dispatch(&run_list, 0));
+        assert_eq!(client.commands.borrow().len(), 1);
+        assert!(!debugger.check_dispatch(&run_list, 0));
+        assert!(client.commands.borrow().is_empty());
+    }
+
+    #[test]
+    fn breakpoint_on_input() {
+        let client = TestClient::new(vec!("break 1 input 0", "continue"));
+        let mut debugger = Debugger::new(&client);
+        let run_list = test_run_list();
+
+        assert!(debugger.check_dispatch(&run_list, 0));
+        *client.commands.borrow_mut() = vec!("continue");
+        debugger.check_output(&run_list, 0, &json!({"sub": 42}));
+        assert!(client.output.borrow().contains("Breakpoint on Process #1 input #0: sent 42 by Process #0"));
+        assert!(client.commands.borrow().is_empty());
+    }
+
+    #[test]
+    fn breakpoint_on_output_route() {
+        let client = TestClient::new(vec!("break p0 output /other", "continue"));
+        let mut debugger = Debugger::new(&client);
+        let run_list = test_run_list();
+
+        assert!(debugger.check_dispatch(&run_list, 0));
+
+        // breakpoint is on a different route, so no stop
+        debugger.check_output(&run_list, 0, &json!({"sub": 42}));
+        assert!(!client.output.borrow().contains("Breakpoint on Process #0 output"));
+
+        let client = TestClient::new(vec!("break p0 output /sub", "continue"));
+        let mut debugger = Debugger::new(&client);
+        assert!(debugger.check_dispatch(&run_list, 0));
+        *client.commands.borrow_mut() = vec!("continue");
+        debugger.check_output(&run_list, 0, &json!({"sub": 42}));
+        assert!(client.output.borrow().contains("Breakpoint on Process #0 output '/sub': 42"));
+    }
+
+    #[test]
+    fn inspect_shows_inputs() {
+        let client = TestClient::new(vec!("inspect p1", "runlist", "quit"));
+        let mut debugger = Debugger::new(&client);
+        let run_list = test_run_list();
+        run_list.get(1).lock().unwrap().write_input(0, json!("hello"));
+
+        assert!(!debugger.check_dispatch(&run_list, 0));
+        assert!(client.output.borrow().contains("Input #0: 1/1 values received: [\"hello\"]"));
+        assert!(client.output.borrow().contains("Will Run"));
+    }
+
+    #[test]
+    fn unknown_process() {
+        let client = TestClient::new(vec!("break nothing", "quit"));
+        let mut debugger = Debugger::new(&client);
+        let run_list = test_run_list();
+
+        assert!(!debugger.check_dispatch(&run_list, 0));
+        assert!(client.output.borrow().contains("No process with id or name 'nothing' was found"));
+    }
+}
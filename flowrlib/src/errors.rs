@@ -0,0 +1,236 @@
+//! Errors that can occur when loading and executing a flow.
+//!
+//! Each error describes what failed, and where there is an underlying error that caused it (e.g.
+//! an `io::Error` or a `serde_json::Error`) it is available via `Error::source()`.
+use serde_json;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// An error from a `Provider` trying to find or fetch content
+#[derive(Debug)]
+pub enum ProviderError {
+    /// The Url is not valid for the provider, or could not be converted to where the content is
+    InvalidUrl { url: String, reason: String },
+    /// There is no content at the Url
+    NotFound { url: String },
+    /// There was an error reading the content
+    Io { url: String, source: io::Error },
+    /// The content could not be fetched from a remote server
+    Fetch { url: String, reason: String },
+}
+
+impl fmt::Display for ProviderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ProviderError::InvalidUrl { ref url, ref reason } =>
+                write!(f, "Invalid url '{}': {}", url, reason),
+            ProviderError::NotFound { ref url } =>
+                write!(f, "Could not find content at '{}'", url),
+            ProviderError::Io { ref url, ref source } =>
+                write!(f, "Could not read content from '{}' ({})", url, source),
+            ProviderError::Fetch { ref url, ref reason } =>
+                write!(f, "Could not fetch content from '{}' ({})", url, reason),
+        }
+    }
+}
+
+impl Error for ProviderError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            ProviderError::Io { ref source, .. } => Some(source),
+            _ => None
+        }
+    }
+}
+
+/// An error loading a flow's manifest, a library, or the implementations of its processs
+#[derive(Debug)]
+pub enum LoadError {
+    /// The content could not be found or read
+    Provider(ProviderError),
+    /// The content was read but is not valid (e.g. a malformed manifest)
+    Parse { url: String, source: serde_json::Error },
+    /// A Url in the content is not valid
+    InvalidUrl { url: String, reason: String },
+    /// A wasm implementation could not be loaded
+    Wasm { url: String, reason: String },
+    /// A process's implementation was not found where it was expected
+    Implementation { source: String, reason: String },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LoadError::Provider(ref e) => write!(f, "{}", e),
+            LoadError::Parse { ref url, ref source } =>
+                write!(f, "Could not read '{}' ({})", url, source),
+            LoadError::InvalidUrl { ref url, ref reason } =>
+                write!(f, "Invalid url '{}': {}", url, reason),
+            LoadError::Wasm { ref url, ref reason } =>
+                write!(f, "Could not load wasm implementation from '{}' ({})", url, reason),
+            LoadError::Implementation { ref source, ref reason } =>
+                write!(f, "Implementation '{}': {}", source, reason),
+        }
+    }
+}
+
+impl Error for LoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            LoadError::Provider(ref e) => Some(e),
+            LoadError::Parse { ref source, .. } => Some(source),
+            _ => None
+        }
+    }
+}
+
+impl From<ProviderError> for LoadError {
+    fn from(error: ProviderError) -> Self {
+        LoadError::Provider(error)
+    }
+}
+
+/// An error returned by an `Implementation` when it is run and cannot produce its output
+#[derive(Debug)]
+pub enum ImplementationError {
+    /// An input value is not valid for the implementation (e.g. the wrong type)
+    InvalidInput(String),
+    /// There was an I/O error (e.g. writing a file)
+    Io(io::Error),
+    /// The implementation failed for some other reason
+    Failed(String),
+    /// The output of the implementation has no value at the route of a connection from it, so
+    /// it does not have the parts its definition says it has
+    MissingOutput { route: String, output: serde_json::Value },
+    /// The implementation panicked while running, with the message it panicked with
+    Panicked(String),
+}
+
+impl ImplementationError {
+    /// A short name for the kind of error, used when the error is sent as a value
+    pub fn kind(&self) -> &'static str {
+        match *self {
+            ImplementationError::InvalidInput(_) => "InvalidInput",
+            ImplementationError::Io(_) => "Io",
+            ImplementationError::Failed(_) => "Failed",
+            ImplementationError::MissingOutput { .. } => "MissingOutput",
+            ImplementationError::Panicked(_) => "Panicked",
+        }
+    }
+}
+
+impl fmt::Display for ImplementationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ImplementationError::InvalidInput(ref reason) => write!(f, "Invalid input: {}", reason),
+            ImplementationError::Io(ref e) => write!(f, "I/O error: {}", e),
+            ImplementationError::Failed(ref reason) => write!(f, "{}", reason),
+            ImplementationError::MissingOutput { ref route, ref output } =>
+                write!(f, "Output has no value at route '{}' to send to a connection from it: {}", route, output),
+            ImplementationError::Panicked(ref message) => write!(f, "Panicked: {}", message),
+        }
+    }
+}
+
+impl Error for ImplementationError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            ImplementationError::Io(ref e) => Some(e),
+            _ => None
+        }
+    }
+}
+
+impl From<io::Error> for ImplementationError {
+    fn from(error: io::Error) -> Self {
+        ImplementationError::Io(error)
+    }
+}
+
+/// An error that ended the execution of a flow
+#[derive(Debug)]
+pub enum ExecutionError {
+    /// A process failed and the failure policy is to abort the flow
+    Process { id: usize, name: String, source: ImplementationError },
+    /// The flow ended with processs that have all their inputs satisfied but were not run
+    Stalled(Vec<String>),
+    /// Information gathered during execution (e.g. metrics) could not be written to a file
+    Write { path: PathBuf, source: io::Error },
+    /// Information gathered during execution could not be serialized
+    Serialize(serde_json::Error),
+    /// A snapshot cannot be used to resume the flow being executed (e.g. it is of a different flow)
+    Resume(String),
+}
+
+impl fmt::Display for ExecutionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ExecutionError::Process { id, ref name, ref source } =>
+                write!(f, "Process #{} '{}' failed: {}", id, name, source),
+            ExecutionError::Stalled(ref stalled) =>
+                write!(f, "Flow execution ended with {} processs stalled:\n{}", stalled.len(),
+                       stalled.join("\n")),
+            ExecutionError::Write { ref path, ref source } =>
+                write!(f, "Could not write file '{}' ({})", path.display(), source),
+            ExecutionError::Serialize(ref e) => write!(f, "Could not serialize: {}", e),
+            ExecutionError::Resume(ref reason) => write!(f, "Could not resume from snapshot: {}", reason),
+        }
+    }
+}
+
+impl Error for ExecutionError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            ExecutionError::Process { ref source, .. } => Some(source),
+            ExecutionError::Write { ref source, .. } => Some(source),
+            ExecutionError::Serialize(ref e) => Some(e),
+            ExecutionError::Stalled(_) | ExecutionError::Resume(_) => None
+        }
+    }
+}
+
+impl From<serde_json::Error> for ExecutionError {
+    fn from(error: serde_json::Error) -> Self {
+        ExecutionError::Serialize(error)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+    use std::io;
+
+    use super::{LoadError, ProviderError};
+
+    #[test]
+    fn missing_and_malformed_are_different() {
+        let missing: LoadError = ProviderError::NotFound { url: "file:///flow.json".to_string() }.into();
+        let malformed = LoadError::Parse {
+            url: "file:///flow.json".to_string(),
+            source: ::serde_json::from_str::<::serde_json::Value>("{").unwrap_err(),
+        };
+
+        match missing {
+            LoadError::Provider(ProviderError::NotFound { .. }) => {}
+            _ => panic!("Expected a NotFound error")
+        }
+        match malformed {
+            LoadError::Parse { .. } => {}
+            _ => panic!("Expected a Parse error")
+        }
+    }
+
+    #[test]
+    fn source_is_chained() {
+        let error: LoadError = ProviderError::Io {
+            url: "file:///flow.json".to_string(),
+            source: io::Error::new(io::ErrorKind::PermissionDenied, "denied"),
+        }.into();
+
+        let provider_error = error.source().expect("LoadError should have a source");
+        let io_error = provider_error.source().expect("ProviderError should have a source");
+        assert_eq!(io_error.to_string(), "denied");
+    }
+}
@@ -1,7 +1,85 @@
+use checkpoint::{Checkpoint, Snapshot};
+use debugger::Debugger;
+use errors::{ExecutionError, ImplementationError};
+use implementation::Implementation;
+use implementation::RunResult;
 use process::Process;
 use runlist::RunList;
+use serde_json::Value as JsonValue;
+use std::fs::File;
+use std::io::Write;
 use std::panic;
+use std::path::PathBuf;
+use std::str::FromStr;
 use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// What the runtime does when the implementation of a process returns an error, and nothing is
+/// connected to the process's `error` output to handle it. If something is connected, the error is
+/// always sent to it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FailurePolicy {
+    /// Stop executing the flow, and return the error from `execute`
+    Abort,
+    /// Log the error and continue, as if the process had run and produced no output
+    Skip,
+}
+
+impl FromStr for FailurePolicy {
+    type Err = String;
+
+    fn from_str(policy: &str) -> Result<Self, Self::Err> {
+        match policy {
+            "abort" => Ok(FailurePolicy::Abort),
+            "skip" => Ok(FailurePolicy::Skip),
+            _ => Err(format!("Unknown failure policy '{}', expected one of 'abort' or 'skip'", policy))
+        }
+    }
+}
+
+/// Options that control how a flow is executed
+pub struct ExecutionOptions<'a> {
+    /// The maximum number of processs that will be run at the same time. If it is 1 then
+    /// processs are run one at a time on the calling thread, otherwise they are run on a pool of
+    /// `jobs` worker threads.
+    pub jobs: usize,
+    /// If a `Debugger` is supplied then it is called before each process is dispatched and before
+    /// each output is sent, so the user can step through the execution of the flow and set
+    /// breakpoints.
+    pub debugger: Option<Debugger<'a>>,
+    /// If supplied, the metrics gathered during execution are written to this file as JSON
+    pub metrics_file: Option<PathBuf>,
+    /// If supplied, the metrics gathered during execution are written to this file in the
+    /// Prometheus text exposition format
+    pub prometheus_file: Option<PathBuf>,
+    /// If supplied, a trace of the execution (processs being dispatched, sending output and being
+    /// blocked and unblocked) is written to this file in the Chrome trace event format
+    pub trace_file: Option<PathBuf>,
+    /// What to do when the implementation of a process returns an error
+    pub failure_policy: FailurePolicy,
+    /// If supplied, snapshots of the state of execution are written as it controls, so that
+    /// execution can be resumed from them later
+    pub checkpoint: Option<Checkpoint<'a>>,
+    /// If supplied, execution resumes from this snapshot instead of starting from the beginning
+    pub resume: Option<Snapshot>,
+}
+
+impl<'a> Default for ExecutionOptions<'a> {
+    fn default() -> Self {
+        ExecutionOptions {
+            jobs: 1,
+            debugger: None,
+            metrics_file: None,
+            prometheus_file: None,
+            trace_file: None,
+            failure_policy: FailurePolicy::Abort,
+            checkpoint: None,
+            resume: None,
+        }
+    }
+}
 
 /// The generated code for a flow consists of values and functions formed into a list of Processs.
 ///
@@ -11,60 +89,358 @@ use std::sync::{Arc, Mutex};
 /// You should not have to write code to call `execute` yourself, it will be called from the
 /// generated code in the `main` method.
 ///
-/// On completion of the execution of the flow it will return and `main` will call `exit`
+/// How the flow is executed (number of jobs, debugger, where to write metrics, what to do when a
+/// process fails, writing snapshots and resuming from one) is controlled by `options`, see
+/// `ExecutionOptions`.
+///
+/// On completion of the execution of the flow it will return and `main` will call `exit`.
+/// If a process failed and the failure policy is `FailurePolicy::Abort` it returns that error.
+/// If a snapshot was requested via the `Checkpoint` then execution stops once it is written.
+/// If when it completes there are processs left with all their inputs satisfied that were not run
+/// (i.e. data was not processed, for example because they were deadlocked) it returns an error
+/// describing each of them.
 ///
 /// # Example
 /// ```
 /// use std::sync::{Arc, Mutex};
 /// use flowrlib::process::Process;
-/// use flowrlib::execution::execute;
+/// use flowrlib::execution::{execute, ExecutionOptions};
 /// use std::process::exit;
 ///
 /// let mut processs = Vec::<Arc<Mutex<Process>>>::new();
 ///
-/// execute(processs);
+/// execute(processs, ExecutionOptions::default()).unwrap();
 ///
 /// exit(0);
 /// ```
-pub fn execute(processs: Vec<Arc<Mutex<Process>>>) {
+pub fn execute(processs: Vec<Arc<Mutex<Process>>>, options: ExecutionOptions) -> Result<(), ExecutionError> {
+    let ExecutionOptions { jobs, mut debugger, metrics_file, prometheus_file, trace_file, failure_policy,
+        mut checkpoint, resume } = options;
     set_panic_hook();
-    let mut run_list = init(processs);
+    let mut run_list = init(processs, trace_file.is_some(), resume)?;
 
-    debug!("Starting execution loop");
+    debug!("Starting execution loop with a maximum of {} jobs", jobs);
     debug!("-----------------------------------------------------------------");
     run_list.debug();
-    while let Some(id) = run_list.next() {
-        dispatch(&mut run_list, id);
-        run_list.debug();
+    let mut stopped = false;
+    let mut failure = None;
+    if jobs > 1 {
+        let (parallel_stopped, parallel_failure) =
+            execute_parallel(&mut run_list, jobs, &mut debugger, failure_policy, &mut checkpoint);
+        stopped = parallel_stopped;
+        failure = parallel_failure;
+    } else {
+        loop {
+            match write_checkpoint(&mut checkpoint, &mut run_list) {
+                Ok(true) => {
+                    stopped = true;
+                    break;
+                }
+                Ok(false) => {}
+                Err(e) => {
+                    failure = Some(e);
+                    break;
+                }
+            }
+            let id = match run_list.next() {
+                Some(id) => id,
+                None => break
+            };
+            if !check_dispatch(&mut debugger, &run_list, id) {
+                stopped = true;
+                break;
+            }
+            let (id, result, start, run_time) = run_job(dispatch(&mut run_list, id));
+            let completed = complete(&mut run_list, id, result, start, run_time, &mut debugger,
+                                     failure_policy);
+            run_list.debug();
+            if let Err(e) = completed {
+                failure = Some(e);
+                break;
+            }
+        }
     }
     debug!("Ended execution loop");
 
     run_list.end();
+
+    if let Some(path) = metrics_file {
+        write_file(&path, &run_list.metrics().to_json()?)?;
+    }
+
+    if let Some(path) = prometheus_file {
+        write_file(&path, &run_list.metrics().to_prometheus())?;
+    }
+
+    if let (Some(path), Some(tracer)) = (trace_file, run_list.tracer()) {
+        write_file(&path, &tracer.to_json()?)?;
+    }
+
+    if let Some(e) = failure {
+        return Err(e);
+    }
+
+    // If the user stopped execution (or it stopped for a snapshot) then the flow did not run to the
+    // end, so don't check it
+    if stopped {
+        return Ok(());
+    }
+
+    let stalled = run_list.stalled();
+    if stalled.is_empty() {
+        Ok(())
+    } else {
+        Err(ExecutionError::Stalled(stalled))
+    }
 }
 
 /*
-    Given a process id, start running it
+    A Job is the work sent to a worker thread to be run: the implementation of a process and the
+    input values to run it with.
 */
-fn dispatch(run_list: &mut RunList, id: usize) {
+struct Job {
+    id: usize,
+    implementation: Arc<dyn Implementation>,
+    inputs: Vec<Vec<JsonValue>>,
+}
+
+// The id of the process run, the result of running it, and when it started and how long it ran for
+type JobResult = (usize, RunResult, Instant, Duration);
+
+/*
+    Run the flow on a pool of 'jobs' worker threads.
+
+    The RunList is only ever used from this thread, which dispatches processs to the workers while
+    there are processs ready to run and less than 'jobs' running, then waits for one to complete and
+    sends its output.
+
+    Returns true if execution was stopped by the debugger or for a snapshot, and the error that
+    ended execution if a process failed and the failure policy is to abort. In either case processs
+    already running are allowed to complete, but no more are dispatched.
+
+    When a snapshot is due no more processs are dispatched until those running have completed,
+    and then it is written.
+*/
+fn execute_parallel(run_list: &mut RunList, jobs: usize, debugger: &mut Option<Debugger>,
+                    failure_policy: FailurePolicy, checkpoint: &mut Option<Checkpoint>)
+                    -> (bool, Option<ExecutionError>) {
+    let (job_tx, job_rx) = mpsc::channel::<Job>();
+    let job_rx = Arc::new(Mutex::new(job_rx));
+    let (result_tx, result_rx) = mpsc::channel::<JobResult>();
+
+    thread::scope(|scope| {
+        let job_tx = job_tx;
+
+        for _ in 0..jobs {
+            let job_rx = Arc::clone(&job_rx);
+            let result_tx = result_tx.clone();
+            scope.spawn(move || worker(job_rx, result_tx));
+        }
+
+        let mut stopped = false;
+        let mut failure = None;
+        loop {
+            let checkpoint_due = checkpoint.as_ref().map_or(false, |checkpoint| checkpoint.due());
+            while !stopped && failure.is_none() && !checkpoint_due && run_list.number_running() < jobs {
+                match run_list.next() {
+                    Some(id) => {
+                        if !check_dispatch(debugger, run_list, id) {
+                            // Let the processs already running complete, but dispatch no more
+                            stopped = true;
+                            break;
+                        }
+                        let job = dispatch(run_list, id);
+                        job_tx.send(job).expect("Could not send job to worker threads");
+                    }
+                    None => break
+                }
+            }
+
+            if run_list.number_running() == 0 {
+                if checkpoint_due && !stopped && failure.is_none() {
+                    match write_checkpoint(checkpoint, run_list) {
+                        Ok(true) => stopped = true,
+                        Ok(false) => continue,
+                        Err(e) => failure = Some(e)
+                    }
+                }
+                break;
+            }
+
+            let (id, result, start, run_time) = result_rx.recv().expect("Could not receive result from worker threads");
+            if let Err(e) = complete(run_list, id, result, start, run_time, debugger, failure_policy) {
+                failure.get_or_insert(e);
+            }
+            run_list.debug();
+        }
+
+        // dropping job_tx here ends the workers' loops, so the scope can join them
+        (stopped, failure)
+    })
+}
+
+/*
+    Take jobs and run them until there are no more
+*/
+fn worker(job_rx: Arc<Mutex<Receiver<Job>>>, result_tx: Sender<JobResult>) {
+    loop {
+        let job = match job_rx.lock().expect("Job receiver lock poisoned").recv() {
+            Ok(job) => job,
+            Err(_) => return
+        };
+
+        if result_tx.send(run_job(job)).is_err() {
+            return;
+        }
+    }
+}
+
+/*
+    Run a job, on whichever thread is executing the flow. A panic in the implementation is caught
+    and returned as an error, so it is handled like any other failure of the process.
+*/
+fn run_job(job: Job) -> JobResult {
+    let Job { id, implementation, inputs } = job;
+    let start = Instant::now();
+    let result = panic::catch_unwind(move || implementation.run_process(id, inputs))
+        .unwrap_or_else(|cause| {
+            let message = match cause.downcast_ref::<&str>() {
+                Some(message) => message.to_string(),
+                None => cause.downcast_ref::<String>().cloned()
+                    .unwrap_or_else(|| "unknown cause".to_string())
+            };
+            Err(ImplementationError::Panicked(message))
+        });
+    (id, result, start, start.elapsed())
+}
+
+/*
+    If a snapshot is due, write it. This must only be called when no processs are running.
+    Returns true if execution should stop, as the snapshot was requested.
+*/
+fn write_checkpoint(checkpoint: &mut Option<Checkpoint>, run_list: &mut RunList) -> Result<bool, ExecutionError> {
+    match *checkpoint {
+        Some(ref mut checkpoint) if checkpoint.due() => {
+            checkpoint.write(run_list)?;
+            Ok(checkpoint.requested())
+        }
+        _ => Ok(false)
+    }
+}
+
+/*
+    If debugging, let the debugger check if it should stop before dispatching process 'id'.
+    Returns false if execution should stop.
+*/
+fn check_dispatch(debugger: &mut Option<Debugger>, run_list: &RunList, id: usize) -> bool {
+    match *debugger {
+        Some(ref mut debugger) => debugger.check_dispatch(run_list, id),
+        None => true
+    }
+}
+
+/*
+    Given a process id, start running it: take its input values and update the RunList to reflect
+    that its inputs have been consumed and it is running
+*/
+fn dispatch(run_list: &mut RunList, id: usize) -> Job {
     let process_arc = run_list.get(id);
     let process: &mut Process = &mut *process_arc.lock().unwrap();
     debug!("Process #{} '{}' dispatched", id, process.name());
 
-    let input_values = process.get_input_values();
+    let inputs = process.get_input_values();
+    run_list.start(id);
     run_list.inputs_consumed(id);
     run_list.unblock_senders_to(id);
-    debug!("\tProcess #{} '{}' running with inputs: {:?}", id, process.name(), input_values);
+    debug!("\tProcess #{} '{}' running with inputs: {:?}", id, process.name(), inputs);
 
-    let implementation = process.get_implementation();
+    Job { id, implementation: process.get_implementation(), inputs }
+}
 
-    // when a process ends, it can express whether it can run again or not
-    let run_again = implementation.run(process, input_values, run_list);
+/*
+    A process has completed running: send its output (if any) and if it wants to run again and
+    it can (inputs ready) then add back to the Can Run list.
+
+    If it failed, or it's output is missing a part that is connected to, then what happens depends
+    on the failure policy. If it is to abort, the error is returned, otherwise the process is
+    treated as having produced no output (other than the error if it is routed to the process's
+    error output) and it can run again.
+*/
+fn complete(run_list: &mut RunList, id: usize, result: RunResult, start: Instant, run_time: Duration,
+            debugger: &mut Option<Debugger>, failure_policy: FailurePolicy) -> Result<(), ExecutionError> {
+    let (output, run_again) = match result {
+        Ok((output, run_again)) => (output, run_again),
+        Err(e) => {
+            run_list.done(id, start, run_time);
+            return fail(run_list, id, e, failure_policy);
+        }
+    };
+
+    if let (&Some(ref value), &mut Some(ref mut debugger)) = (&output, debugger) {
+        debugger.check_output(run_list, id, value);
+    }
+
+    let process_arc = run_list.get(id);
+    let sent = match output {
+        Some(value) => run_list.send_output(&*process_arc.lock().unwrap(), value),
+        None => Ok(())
+    };
+
+    // An output that doesn't have the parts that are connected to is a failure of the process
+    if let Err(e) = sent {
+        run_list.done(id, start, run_time);
+        return fail(run_list, id, e, failure_policy);
+    }
+
+    let process: &Process = &*process_arc.lock().unwrap();
+    run_list.done(id, start, run_time);
 
-    // if it wants to run again and it can (inputs ready) then add back to the Can Run list
     if run_again && process.can_run() {
-        run_list.can_run(process.id());
+        run_list.can_run(id);
     }
     debug!("\tProcess #{} '{}' completed", id, process.name());
+    Ok(())
+}
+
+/*
+    A process returned an error when it was run. If the flow handles its errors by connecting to
+    its error output then send it there, otherwise apply the failure policy
+*/
+fn fail(run_list: &mut RunList, id: usize, error: ImplementationError, failure_policy: FailurePolicy)
+        -> Result<(), ExecutionError> {
+    let process_arc = run_list.get(id);
+    let process: &Process = &*process_arc.lock().unwrap();
+
+    if process.error_output_connected() {
+        debug!("\tProcess #{} '{}' failed, sending the error to its error output: {}", id,
+               process.name(), error);
+        let error_value = json!({"kind": error.kind(), "message": error.to_string(),
+                                 "process": process.name()});
+        run_list.send_error(process, error_value);
+    } else {
+        match failure_policy {
+            FailurePolicy::Abort => {
+                return Err(ExecutionError::Process { id, name: process.name().to_string(), source: error });
+            }
+            FailurePolicy::Skip => {
+                error!("Process #{} '{}' failed, skipping: {}", id, process.name(), error);
+            }
+        }
+    }
+
+    if process.can_run() {
+        run_list.can_run(id);
+    }
+    Ok(())
+}
+
+/*
+    Write information gathered during execution of the flow (e.g. metrics) to a file
+*/
+fn write_file(path: &PathBuf, contents: &str) -> Result<(), ExecutionError> {
+    File::create(path)
+        .and_then(|mut file| file.write_all(contents.as_bytes()))
+        .map_err(|e| ExecutionError::Write { path: path.clone(), source: e })
 }
 
 /*
@@ -89,9 +465,23 @@ fn set_panic_hook() {
     the Process to be executed.
 
     Once all processs have been initialized, the list of processs is stored in the RunList
+
+    When resuming from a snapshot the processs are not initialized, and the state of the processs
+    and the RunList are restored from the snapshot instead.
 */
-fn init(processs: Vec<Arc<Mutex<Process>>>) -> RunList {
+fn init(processs: Vec<Arc<Mutex<Process>>>, trace: bool, resume: Option<Snapshot>)
+        -> Result<RunList, ExecutionError> {
     let mut run_list = RunList::new();
+    if trace {
+        run_list.trace();
+    }
+
+    if let Some(snapshot) = resume {
+        debug!("Restoring all processs from snapshot");
+        run_list.set_processs(processs);
+        run_list.restore(snapshot)?;
+        return Ok(run_list);
+    }
 
     debug!("Initializing all processs");
     for process_arc in &processs {
@@ -103,5 +493,191 @@ fn init(processs: Vec<Arc<Mutex<Process>>>) -> RunList {
     }
 
     run_list.set_processs(processs);
-    run_list
-}
\ No newline at end of file
+    Ok(run_list)
+}
+#[cfg(test)]
+mod test {
+    use std::env;
+    use std::fs;
+    use std::sync::{Arc, Mutex};
+    use std::sync::atomic::AtomicBool;
+
+    use checkpoint::{Checkpoint, Snapshot};
+    use errors::{ExecutionError, ImplementationError};
+    use implementation::{Implementation, RunResult};
+    use process::Process;
+    use serde_json::Value as JsonValue;
+
+    use super::{execute, ExecutionOptions, FailurePolicy};
+
+    /*
+        Process #0 has no implementation so it fails when run. Process #1 is connected to its output
+        on 'route', and has a second input that nothing sends to, so it never runs.
+    */
+    fn run_failing_flow(route: &str, failure_policy: FailurePolicy)
+                        -> (Result<(), ExecutionError>, Arc<Mutex<Process>>) {
+        let failing = Process::new("failing", false, "/test".to_string(), vec!(1), 0,
+                                   Some(json!(1)), vec!((route.to_string(), 1, 0)));
+        let handler = Process::new("handler", false, "/test".to_string(), vec!(1, 1), 1,
+                                   None, vec!());
+        let handler = Arc::new(Mutex::new(handler));
+        let processs = vec!(Arc::new(Mutex::new(failing)), handler.clone());
+        let options = ExecutionOptions { failure_policy, ..Default::default() };
+        (execute(processs, options), handler)
+    }
+
+    #[test]
+    fn abort_returns_error() {
+        match run_failing_flow("", FailurePolicy::Abort).0 {
+            Err(ExecutionError::Process { id, .. }) => assert_eq!(id, 0),
+            _ => panic!("Expected the process failure to be returned")
+        }
+    }
+
+    #[test]
+    fn skip_continues() {
+        let (result, handler) = run_failing_flow("", FailurePolicy::Skip);
+        assert!(result.is_ok());
+        assert_eq!(handler.lock().unwrap().input_count(0), 0);
+    }
+
+    #[test]
+    fn connected_error_output_gets_error() {
+        let (result, handler) = run_failing_flow("/error", FailurePolicy::Abort);
+        assert!(result.is_ok());
+        let handler = handler.lock().unwrap();
+        assert_eq!(handler.input_count(0), 1);
+        assert!(format!("{}", handler).contains("\"kind\":\"Failed\""));
+    }
+
+    struct PassThrough;
+
+    impl Implementation for PassThrough {
+        fn run(&self, mut inputs: Vec<Vec<JsonValue>>) -> RunResult {
+            Ok((Some(inputs.remove(0).remove(0)), false))
+        }
+    }
+
+    /*
+        Process #0 passes its initial value through to Process #1, which never runs as nothing
+        sends to its second input.
+    */
+    fn pass_through_flow(initial_value: JsonValue) -> Vec<Arc<Mutex<Process>>> {
+        let mut source = Process::new("source", false, "/test".to_string(), vec!(1), 0,
+                                      Some(initial_value), vec!(("".to_string(), 1, 0)));
+        source.set_implementation(Arc::new(PassThrough));
+        let sink = Process::new("sink", false, "/test".to_string(), vec!(1, 1), 1, None, vec!());
+        vec!(Arc::new(Mutex::new(source)), Arc::new(Mutex::new(sink)))
+    }
+
+    struct Panicking;
+
+    impl Implementation for Panicking {
+        fn run(&self, _inputs: Vec<Vec<JsonValue>>) -> RunResult {
+            panic!("always panics")
+        }
+    }
+
+    /*
+        Process #0 panics when run and Process #1 is connected to its output, so never gets a value
+    */
+    fn run_panicking_flow(jobs: usize, failure_policy: FailurePolicy) -> Result<(), ExecutionError> {
+        let mut panicking = Process::new("panicking", false, "/test".to_string(), vec!(1), 0,
+                                         Some(json!(1)), vec!(("".to_string(), 1, 0)));
+        panicking.set_implementation(Arc::new(Panicking));
+        let sink = Process::new("sink", false, "/test".to_string(), vec!(1), 1, None, vec!());
+        let processs = vec!(Arc::new(Mutex::new(panicking)), Arc::new(Mutex::new(sink)));
+        let options = ExecutionOptions { jobs, failure_policy, ..Default::default() };
+        execute(processs, options)
+    }
+
+    fn panic_returns_error(jobs: usize) {
+        match run_panicking_flow(jobs, FailurePolicy::Abort) {
+            Err(ExecutionError::Process { id, source: ImplementationError::Panicked(message), .. }) => {
+                assert_eq!(id, 0);
+                assert_eq!(message, "always panics");
+            }
+            _ => panic!("Expected the panic to be returned as an error")
+        }
+        assert!(run_panicking_flow(jobs, FailurePolicy::Skip).is_ok());
+    }
+
+    #[test]
+    fn panic_returns_error_sequential() {
+        panic_returns_error(1);
+    }
+
+    #[test]
+    fn panic_returns_error_parallel() {
+        panic_returns_error(2);
+    }
+
+    /*
+        Process #0 outputs a number but is connected from a part of it's output that doesn't exist
+    */
+    fn missing_output_flow(failure_policy: FailurePolicy) -> Result<(), ExecutionError> {
+        let mut source = Process::new("source", false, "/test".to_string(), vec!(1), 0,
+                                      Some(json!(1)), vec!(("/missing".to_string(), 1, 0)));
+        source.set_implementation(Arc::new(PassThrough));
+        let sink = Process::new("sink", false, "/test".to_string(), vec!(1), 1, None, vec!());
+        let processs = vec!(Arc::new(Mutex::new(source)), Arc::new(Mutex::new(sink)));
+        let options = ExecutionOptions { failure_policy, ..Default::default() };
+        execute(processs, options)
+    }
+
+    #[test]
+    fn missing_output_returns_error() {
+        match missing_output_flow(FailurePolicy::Abort) {
+            Err(ExecutionError::Process { id, source: ImplementationError::MissingOutput { route, .. }, .. }) => {
+                assert_eq!(id, 0);
+                assert_eq!(route, "/missing");
+            }
+            _ => panic!("Expected a missing output error to be returned")
+        }
+    }
+
+    #[test]
+    fn missing_output_skipped() {
+        assert!(missing_output_flow(FailurePolicy::Skip).is_ok());
+    }
+
+    fn snapshot_and_resume(jobs: usize) {
+        let snapshot_file = env::temp_dir().join(format!("flowrlib_snapshot_and_resume_{}.json", jobs));
+        let requested = AtomicBool::new(true);
+        let mut checkpoint = Checkpoint::new(snapshot_file.clone(), "file:///test/manifest.json", vec!());
+        checkpoint.set_request(&requested);
+
+        // execution stops before anything runs, as a snapshot has been requested
+        let processs = pass_through_flow(json!(1));
+        let options = ExecutionOptions { jobs, checkpoint: Some(checkpoint), ..Default::default() };
+        execute(processs.clone(), options).unwrap();
+        assert_eq!(processs[1].lock().unwrap().input_count(0), 0);
+
+        let snapshot: Snapshot = ::serde_json::from_str(&fs::read_to_string(&snapshot_file).unwrap()).unwrap();
+        fs::remove_file(&snapshot_file).unwrap();
+        assert_eq!(snapshot.will_run, vec!(0));
+        assert_eq!(snapshot.processs[0].inputs, vec!(vec!(json!(1))));
+
+        // the processs are not initialized when resuming, so the initial value is not used
+        let processs = pass_through_flow(json!(2));
+        let options = ExecutionOptions { jobs, resume: Some(snapshot), ..Default::default() };
+        execute(processs.clone(), options).unwrap();
+        assert_eq!(processs[1].lock().unwrap().queued_input_values()[0], vec!(json!(1)));
+    }
+
+    #[test]
+    fn snapshot_and_resume_sequential() {
+        snapshot_and_resume(1);
+    }
+
+    #[test]
+    fn snapshot_and_resume_parallel() {
+        snapshot_and_resume(2);
+    }
+
+    #[test]
+    fn parse_policy() {
+        assert_eq!("skip".parse::<FailurePolicy>(), Ok(FailurePolicy::Skip));
+        assert!("ignore".parse::<FailurePolicy>().is_err());
+    }
+}
@@ -1,5 +1,4 @@
-use process::Process;
-use runlist::RunList;
+use errors::ImplementationError;
 use serde_json::Value as JsonValue;
 use std::panic::RefUnwindSafe;
 use std::panic::UnwindSafe;
@@ -8,9 +7,27 @@ pub type RunAgain = bool;
 pub const RUN_AGAIN: RunAgain = true;
 pub const DONT_RUN_AGAIN: RunAgain = false;
 
-pub trait Implementation : RefUnwindSafe + UnwindSafe + Sync {
-    // An implementation can be run, with an array of inputs, it can use methods of run_list
-    // to send output values and then it eventually returns and indicates with return value whether
-    // it should be ran again
-    fn run(&self, process: &Process, inputs: Vec<Vec<JsonValue>>, run_list: &mut RunList) -> RunAgain;
-}
\ No newline at end of file
+/// The name of the output of every function that errors returned by its implementation are sent
+/// on, so that flows can handle them by connecting to it like any other output
+pub const ERROR_OUTPUT: &str = "error";
+
+/// The result of running an implementation: the value (if any) to send on its output and whether
+/// it should be run again, or the error that stopped it producing an output
+pub type RunResult = Result<(Option<JsonValue>, RunAgain), ImplementationError>;
+
+pub trait Implementation : RefUnwindSafe + UnwindSafe + Sync + Send {
+    // An implementation can be run, with an array of inputs and it returns the value (if any) to
+    // send on its output, and indicates whether it should be ran again. If it cannot produce an
+    // output from the inputs it returns an error instead of panicking, and the runtime's failure
+    // policy decides what happens to the flow.
+    // It does not have access to the process or the run list, so it can be run on any thread
+    // and the runtime takes care of sending the output.
+    fn run(&self, inputs: Vec<Vec<JsonValue>>) -> RunResult;
+
+    // Run the implementation for the process with id 'process_id', as `run` does. This is what the
+    // runtime calls, so an implementation that keeps state for each process that uses it (e.g.
+    // the instance of a wasm module) can find it. Other implementations just run.
+    fn run_process(&self, _process_id: usize, inputs: Vec<Vec<JsonValue>>) -> RunResult {
+        self.run(inputs)
+    }
+}
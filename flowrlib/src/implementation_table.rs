@@ -1,5 +1,7 @@
+use errors::LoadError;
 use implementation::Implementation;
 use std::collections::HashMap;
+use std::sync::Arc;
 use provider::Provider;
 use url::Url;
 
@@ -10,9 +12,9 @@ use url::Url;
 */
 #[derive(Deserialize, Serialize)]
 #[serde(untagged)]
-pub enum ImplementationLocator<'a> {
+pub enum ImplementationLocator {
     #[serde(skip_deserializing, skip_serializing)]
-    Native(&'a dyn Implementation),
+    Native(Arc<dyn Implementation>),
     Wasm(String),
 }
 
@@ -20,24 +22,23 @@ pub enum ImplementationLocator<'a> {
     Provided by libraries to help load and/or find implementations of processes
 */
 #[derive(Deserialize, Serialize)]
-pub struct ImplementationLocatorTable<'a> {
-    pub locators: HashMap<String, ImplementationLocator<'a>>
+pub struct ImplementationLocatorTable {
+    pub locators: HashMap<String, ImplementationLocator>
 }
 
-impl<'a> ImplementationLocatorTable<'a> {
+impl ImplementationLocatorTable {
     pub fn new() -> Self {
         ImplementationLocatorTable {
-            locators: HashMap::<String, ImplementationLocator<'a>>::new()
+            locators: HashMap::<String, ImplementationLocator>::new()
         }
     }
 
-    pub fn load(provider: &Provider, url: &Url) -> Result<ImplementationLocatorTable<'a>, String> {
+    pub fn load(provider: &Provider, url: &Url) -> Result<ImplementationLocatorTable, LoadError> {
         let (resolved_url, _) = provider.resolve(url)?;
         let content = provider.get(&resolved_url)?;
 
-        serde_json::from_str(&content)
-            .map_err(|e| format!("Could not read ILT from '{}'\nError = '{}'",
-                                 url, e))
+        serde_json::from_slice(&content)
+            .map_err(|e| LoadError::Parse { url: url.to_string(), source: e })
     }
 }
 
@@ -50,6 +51,7 @@ mod test {
     use implementation_table::ImplementationLocatorTable;
     use implementation_table::ImplementationLocator;
     use implementation_table::ImplementationLocator::Wasm;
+    use errors::ProviderError;
     use provider::Provider;
 
     pub struct TestProvider {
@@ -57,12 +59,12 @@ mod test {
     }
 
     impl Provider for TestProvider {
-        fn resolve(&self, url: &Url) -> Result<(Url, Option<String>), String> {
+        fn resolve(&self, url: &Url) -> Result<(Url, Option<String>), ProviderError> {
             Ok((url.clone(), None))
         }
 
-        fn get(&self, _url: &Url) -> Result<String, String> {
-            Ok(self.test_content.to_string())
+        fn get(&self, _url: &Url) -> Result<Vec<u8>, ProviderError> {
+            Ok(self.test_content.as_bytes().to_owned())
         }
     }
 
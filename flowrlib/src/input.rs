@@ -1,10 +1,24 @@
 use serde_json::Value as JsonValue;
+use std::fmt;
 use std::mem::replace;
 
+/// A value that an input is given without it being sent to it
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub enum InputInitializer {
+    /// The input is given the value once, when the flow starts
+    #[serde(rename = "once")]
+    Once(JsonValue),
+    /// The input always has the value, so it is never sent to or emptied
+    #[serde(rename = "constant")]
+    Constant(JsonValue),
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct Input {
     #[serde(default = "default_depth", skip_serializing_if = "is_default")]
     depth: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    initializer: Option<InputInitializer>,
     #[serde(skip)]
     received: Vec<JsonValue>,
 }
@@ -21,15 +35,40 @@ impl Input {
     pub fn new(depth: usize) -> Self {
         Input {
             depth,
+            initializer: None,
             received: Vec::with_capacity(depth)
         }
     }
 
+    pub fn set_initializer(&mut self, initializer: InputInitializer) {
+        self.initializer = Some(initializer);
+    }
+
+    // Give the input the value it is initialized with once, if it has one
+    pub fn init(&mut self) {
+        if let Some(InputInitializer::Once(ref value)) = self.initializer {
+            self.received.push(value.clone());
+        }
+    }
+
+    pub fn constant(&self) -> Option<&JsonValue> {
+        match self.initializer {
+            Some(InputInitializer::Constant(ref constant)) => Some(constant),
+            _ => None
+        }
+    }
+
     pub fn read(&mut self) -> Vec<JsonValue> {
-        self.received.clone()
+        match self.constant() {
+            Some(constant) => vec![constant.clone(); self.depth],
+            None => self.received.clone()
+        }
     }
 
     pub fn take(&mut self) -> Vec<JsonValue> {
+        if let Some(constant) = self.constant() {
+            return vec![constant.clone(); self.depth];
+        }
         replace(&mut self.received, Vec::with_capacity(self.depth))
     }
 
@@ -42,6 +81,38 @@ impl Input {
     }
 
     pub fn full(&self) -> bool {
-        self.received.len() == self.depth
+        self.constant().is_some() || self.received.len() == self.depth
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.received.is_empty()
+    }
+
+    pub fn count(&self) -> usize {
+        self.received.len()
+    }
+
+    pub fn values(&self) -> &Vec<JsonValue> {
+        &self.received
+    }
+
+    // Replace the values received, with ones restored from a snapshot
+    pub fn restore(&mut self, values: Vec<JsonValue>) -> Result<(), String> {
+        if values.len() > self.depth {
+            return Err(format!("{} values cannot be restored to an input of depth {}",
+                               values.len(), self.depth));
+        }
+        self.received = values;
+        Ok(())
+    }
+}
+
+impl fmt::Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(constant) = self.constant() {
+            return write!(f, "constant value: {}", constant);
+        }
+        write!(f, "{}/{} values received: {}", self.received.len(), self.depth,
+               JsonValue::Array(self.received.clone()))
     }
 }
\ No newline at end of file
@@ -2,11 +2,8 @@
 //! to enable it to be compiled and ran as a native program.
 #[macro_use]
 extern crate log;
-#[cfg(test)]
 #[macro_use]
 extern crate serde_json;
-#[cfg(not(test))]
-extern crate serde_json;
 extern crate simplog;
 extern crate serde;
 #[macro_use]
@@ -15,8 +12,14 @@ extern crate wasmi;
 extern crate url;
 
 pub mod info;
+pub mod errors;
 pub mod execution;
+pub mod debugger;
+pub mod debug_client;
 pub mod runlist;
+pub mod metrics;
+pub mod checkpoint;
+pub mod tracer;
 pub mod implementation;
 pub mod implementation_table;
 pub mod process;
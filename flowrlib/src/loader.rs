@@ -1,5 +1,6 @@
 use std::sync::{Arc, Mutex};
 
+use errors::LoadError;
 use super::implementation_table::ImplementationLocatorTable;
 use super::implementation_table::ImplementationLocator::Native;
 use super::implementation_table::ImplementationLocator::Wasm;
@@ -9,11 +10,11 @@ use wasm_implementation::WasmImplementation;
 use provider::Provider;
 use url::Url;
 
-pub struct Loader<'a> {
-    global_lib_table: ImplementationLocatorTable<'a>
+pub struct Loader {
+    global_lib_table: ImplementationLocatorTable
 }
 
-impl<'a> Loader<'a> {
+impl Loader {
     pub fn new() -> Self {
         Loader {
             global_lib_table: ImplementationLocatorTable::new(),
@@ -21,26 +22,33 @@ impl<'a> Loader<'a> {
     }
 
     pub fn load_flow(&self, provider: &Provider, manifest_url: &Url)
-                     -> Result<Vec<Arc<Mutex<Process<'a>>>>, String> {
+                     -> Result<Vec<Arc<Mutex<Process>>>, LoadError> {
         let manifest = Manifest::load(provider, manifest_url)?;
         let mut runnables = Vec::<Arc<Mutex<Process>>>::new();
 
         // find in the library, or load the implementation required - as specified by the source
         for mut process in manifest.processes {
             let source_url = Url::parse(process.implementation_source())
-                .map_err(|_| format!("Could not convert process implementation source '{}' to a valid Url",
-                                     process.implementation_source()))?;
+                .map_err(|e| LoadError::InvalidUrl {
+                    url: process.implementation_source().to_string(),
+                    reason: e.to_string(),
+                })?;
             match source_url.scheme() {
                 "lib" => {
                     // Try and find the implementation referenced in the libraries already loaded
                     if let Some(ref locator) = self.global_lib_table.locators.get(process.implementation_source()) {
                         match locator {
-                            Native(implementation) => process.set_implementation(*implementation),
+                            Native(implementation) => process.set_implementation(Arc::clone(implementation)),
                             _ => {
-                                return Err(format!("Did not find Native wrapper for Wasm implementation '{}'",
-                                                   process.implementation_source()));
+                                return Err(LoadError::Implementation {
+                                    source: process.implementation_source().to_string(),
+                                    reason: "Did not find Native wrapper for Wasm implementation".to_string(),
+                                });
                             }
                         }
+                    } else {
+                        warn!("Implementation '{}' used by process #{} '{}' was not found in any loaded library",
+                              process.implementation_source(), process.id(), process.name());
                     }
                 }
                 "" => { // Assume file with a relative path to the route of the flow's manifest
@@ -49,19 +57,23 @@ impl<'a> Loader<'a> {
                     let relative_path = process.implementation_source();
                     let full_path = &manifest_url.clone().join(relative_path).unwrap(); */
                     let full_path = &Url::parse(process.implementation_source())
-                        .map_err(|_| format!("Could not convert the implementation path '{}' to a Url",
-                                 process.implementation_source()))?;
+                        .map_err(|e| LoadError::InvalidUrl {
+                            url: process.implementation_source().to_string(),
+                            reason: e.to_string(),
+                        })?;
                     // TODO optimize so we don't load the implementation multiple times?
                     process.set_implementation(
-                        WasmImplementation::load(provider, full_path).unwrap());
+                        WasmImplementation::load(provider, full_path)?);
                 }
                 "http" | "https" | "file" => {
                     // TODO optimize so we don't load the implementation multiple times?
                     process.set_implementation(
-                        WasmImplementation::load(provider, &source_url).unwrap());
+                        WasmImplementation::load(provider, &source_url)?);
                 }
-                _ => return Err(format!("Unexpected Url scheme for implemenation source: '{}'",
-                                        process.implementation_source()))
+                _ => return Err(LoadError::InvalidUrl {
+                    url: process.implementation_source().to_string(),
+                    reason: "Unexpected Url scheme for implementation source".to_string(),
+                })
             };
 
             runnables.push(Arc::new(Mutex::new(process)));
@@ -74,21 +86,22 @@ impl<'a> Loader<'a> {
     // table for this runtime, so that then when we try to load a flow that references functions
     // in the library, they can be found.
     pub fn add_lib(&mut self, provider: &Provider,
-                   lib_manifest: ImplementationLocatorTable<'a>,
+                   lib_manifest: ImplementationLocatorTable,
                    ilt_url: &Url)
-                   -> Result<(), String> {
-        self.global_lib_table.locators.extend(lib_manifest.locators.into_iter()
-            .map(|(route, locator)| {
-                match locator {
-                    Wasm(ref source) => {
-                        // Reference to a wasm implementation being added. Wrap it with the Wasm
-                        // Native Implementation and return that for use later on execution.
-                        let wasm_url = ilt_url.clone().join(source).unwrap();
-                        (route, Native(WasmImplementation::load(provider, &wasm_url).unwrap()))
-                    }
-                    _ => (route, locator), // Reference to Native implementation being added
+                   -> Result<(), LoadError> {
+        for (route, locator) in lib_manifest.locators {
+            let locator = match locator {
+                Wasm(ref source) => {
+                    // Reference to a wasm implementation being added. Load the module now and
+                    // share it between all the processes that use it.
+                    let wasm_url = ilt_url.join(source)
+                        .map_err(|e| LoadError::InvalidUrl { url: source.to_string(), reason: e.to_string() })?;
+                    Native(WasmImplementation::load(provider, &wasm_url)?)
                 }
-            }));
+                _ => locator, // Reference to Native implementation being added
+            };
+            self.global_lib_table.locators.insert(route, locator);
+        }
 
         Ok(())
     }
@@ -1,27 +1,27 @@
+use errors::LoadError;
 use process::Process;
 use provider::Provider;
 use url::Url;
 
 #[derive(Deserialize, Serialize)]
-pub struct Manifest<'a> {
-    pub processes: Vec<Process<'a>>
+pub struct Manifest {
+    pub processes: Vec<Process>
 }
 
-impl<'a> Manifest<'a> {
+impl Manifest {
     pub fn new() -> Self {
-        let processes=  Vec::<Process<'a>>::new();
+        let processes=  Vec::<Process>::new();
 
         Manifest {
             processes
         }
     }
 
-    pub fn load(provider: &Provider, url: &Url) -> Result<Manifest<'a>, String> {
+    pub fn load(provider: &Provider, url: &Url) -> Result<Manifest, LoadError> {
         let (resolved_url, _) = provider.resolve(url)?;
         let content = provider.get(&resolved_url)?;
 
-        serde_json::from_str(&content)
-            .map_err(|e| format!("Could not read manifest from '{}'\nError = '{}'",
-                                 url, e))
+        serde_json::from_slice(&content)
+            .map_err(|e| LoadError::Parse { url: url.to_string(), source: e })
     }
 }
\ No newline at end of file
@@ -0,0 +1,267 @@
+use serde::{Deserialize, Deserializer, Serializer};
+use serde_json;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/*
+    Durations are exported as a number of seconds
+*/
+fn seconds(duration: &Duration) -> f64 {
+    duration.as_secs() as f64 + duration.subsec_nanos() as f64 * 1e-9
+}
+
+fn serialize_seconds<S>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+    serializer.serialize_f64(seconds(duration))
+}
+
+fn deserialize_seconds<'de, D>(deserializer: D) -> Result<Duration, D::Error> where D: Deserializer<'de> {
+    let seconds = f64::deserialize(deserializer)?;
+    Ok(Duration::new(seconds.trunc() as u64, (seconds.fract() * 1e9).round() as u32))
+}
+
+/// Metrics gathered for one process during the execution of a flow
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct ProcessMetrics {
+    id: usize,
+    name: String,
+    invocations: u32,
+    outputs_sent: u32,
+    #[serde(serialize_with = "serialize_seconds", deserialize_with = "deserialize_seconds")]
+    total_run_time: Duration,
+    #[serde(serialize_with = "serialize_seconds", deserialize_with = "deserialize_seconds")]
+    max_run_time: Duration,
+    #[serde(serialize_with = "serialize_seconds", deserialize_with = "deserialize_seconds")]
+    blocked_time: Duration,
+    peak_input_queue: usize,
+    #[serde(skip)]
+    blocked_since: Option<Instant>,
+}
+
+/// Metrics gathered during the execution of a flow, for the whole flow and for each process
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Metrics {
+    num_processs: usize,
+    invocations: u32,
+    outputs_sent: u32,
+    #[serde(serialize_with = "serialize_seconds", deserialize_with = "deserialize_seconds")]
+    elapsed_time: Duration,
+    #[serde(skip, default = "Instant::now")]
+    start_time: Instant,
+    processs: Vec<ProcessMetrics>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Metrics {
+            num_processs: 0,
+            invocations: 0,
+            outputs_sent: 0,
+            elapsed_time: Duration::new(0, 0),
+            start_time: Instant::now(),
+            processs: vec!(),
+        }
+    }
+
+    // Set the processs to gather metrics for, with their names in order of their ids
+    pub fn set_processs(&mut self, names: Vec<String>) {
+        self.num_processs = names.len();
+        self.processs = names.into_iter().enumerate()
+            .map(|(id, name)| ProcessMetrics { id, name, ..Default::default() }).collect();
+    }
+
+    pub fn invocations(&self) -> u32 {
+        self.invocations
+    }
+
+    pub fn dispatched(&mut self, id: usize) {
+        self.invocations += 1;
+        self.processs[id].invocations += 1;
+    }
+
+    pub fn ran(&mut self, id: usize, run_time: Duration) {
+        let process = &mut self.processs[id];
+        process.total_run_time += run_time;
+        if run_time > process.max_run_time {
+            process.max_run_time = run_time;
+        }
+    }
+
+    pub fn output_sent(&mut self, id: usize) {
+        self.outputs_sent += 1;
+        self.processs[id].outputs_sent += 1;
+    }
+
+    pub fn input_queue(&mut self, id: usize, length: usize) {
+        let process = &mut self.processs[id];
+        if length > process.peak_input_queue {
+            process.peak_input_queue = length;
+        }
+    }
+
+    pub fn blocked(&mut self, id: usize) {
+        self.processs[id].blocked_since = Some(Instant::now());
+    }
+
+    pub fn unblocked(&mut self, id: usize) {
+        let process = &mut self.processs[id];
+        if let Some(since) = process.blocked_since.take() {
+            process.blocked_time += since.elapsed();
+        }
+    }
+
+    // Execution has ended, so stop the clocks
+    pub fn end(&mut self) {
+        for id in 0..self.processs.len() {
+            self.unblocked(id);
+        }
+        self.elapsed_time = self.start_time.elapsed();
+    }
+
+    // Bring the elapsed and blocked times up to date, so the metrics can be saved in a snapshot
+    pub fn checkpoint(&mut self) {
+        for process in &mut self.processs {
+            if let Some(since) = process.blocked_since {
+                let now = Instant::now();
+                process.blocked_time += now - since;
+                process.blocked_since = Some(now);
+            }
+        }
+        self.elapsed_time = self.start_time.elapsed();
+    }
+
+    // Continue gathering metrics restored from a snapshot, counting the time already elapsed
+    pub fn resume(&mut self) {
+        let now = Instant::now();
+        self.start_time = now.checked_sub(self.elapsed_time).unwrap_or(now);
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Format the metrics in the Prometheus text exposition format
+    pub fn to_prometheus(&self) -> String {
+        let mut output = String::new();
+
+        Self::flow_metric(&mut output, "flow_processs", "gauge",
+                          "Number of processs in the flow", self.num_processs as f64);
+        Self::flow_metric(&mut output, "flow_invocations_total", "counter",
+                          "Number of process invocations", self.invocations as f64);
+        Self::flow_metric(&mut output, "flow_outputs_sent_total", "counter",
+                          "Number of outputs sent between processs", self.outputs_sent as f64);
+        Self::flow_metric(&mut output, "flow_elapsed_seconds", "gauge",
+                          "Time taken to execute the flow", seconds(&self.elapsed_time));
+
+        self.process_metric(&mut output, "flow_process_invocations_total", "counter",
+                            "Number of times the process was run", |p| p.invocations as f64);
+        self.process_metric(&mut output, "flow_process_outputs_sent_total", "counter",
+                            "Number of outputs sent by the process", |p| p.outputs_sent as f64);
+        self.process_metric(&mut output, "flow_process_run_seconds_total", "counter",
+                            "Total time spent running the process", |p| seconds(&p.total_run_time));
+        self.process_metric(&mut output, "flow_process_run_seconds_max", "gauge",
+                            "Longest time taken by one run of the process", |p| seconds(&p.max_run_time));
+        self.process_metric(&mut output, "flow_process_blocked_seconds_total", "counter",
+                            "Total time the process was blocked sending its output", |p| seconds(&p.blocked_time));
+        self.process_metric(&mut output, "flow_process_input_queue_peak", "gauge",
+                            "Largest number of values waiting on one input of the process", |p| p.peak_input_queue as f64);
+
+        output
+    }
+
+    fn flow_metric(output: &mut String, name: &str, metric_type: &str, help: &str, value: f64) {
+        output.push_str(&format!("# HELP {} {}\n# TYPE {} {}\n{} {}\n", name, help, name, metric_type, name, value));
+    }
+
+    fn process_metric<F>(&self, output: &mut String, name: &str, metric_type: &str, help: &str, value: F)
+        where F: Fn(&ProcessMetrics) -> f64 {
+        output.push_str(&format!("# HELP {} {}\n# TYPE {} {}\n", name, help, name, metric_type));
+        for process in &self.processs {
+            output.push_str(&format!("{}{{id=\"{}\",name=\"{}\"}} {}\n", name, process.id,
+                                     escape_label(&process.name), value(process)));
+        }
+    }
+}
+
+// Label values in the Prometheus format must have '\', '"' and new lines escaped
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+impl fmt::Display for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let elapsed = self.start_time.elapsed();
+        write!(f, "\t\tNumber of Processs: \t{}\n", self.num_processs)?;
+        write!(f, "\t\tProcess invocations: \t{}\n", self.invocations)?;
+        write!(f, "\t\tOutputs sent: \t\t{}\n", self.outputs_sent)?;
+        write!(f, "\t\tElapsed time(s): \t{:.*}\n", 9, seconds(&elapsed))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::Metrics;
+
+    fn test_metrics() -> Metrics {
+        let mut metrics = Metrics::new();
+        metrics.set_processs(vec!("add".to_string(), "print \"it\"".to_string()));
+        metrics.dispatched(0);
+        metrics.ran(0, Duration::from_millis(20));
+        metrics.dispatched(0);
+        metrics.ran(0, Duration::from_millis(10));
+        metrics.output_sent(0);
+        metrics.input_queue(1, 2);
+        metrics.input_queue(1, 1);
+        metrics.end();
+        metrics
+    }
+
+    #[test]
+    fn process_counts() {
+        let metrics = test_metrics();
+        let json: ::serde_json::Value = ::serde_json::from_str(&metrics.to_json().unwrap()).unwrap();
+
+        assert_eq!(json["invocations"], json!(2));
+        assert_eq!(json["processs"][0]["invocations"], json!(2));
+        assert_eq!(json["processs"][0]["outputs_sent"], json!(1));
+        assert!((json["processs"][0]["total_run_time"].as_f64().unwrap() - 0.03).abs() < 1e-9);
+        assert!((json["processs"][0]["max_run_time"].as_f64().unwrap() - 0.02).abs() < 1e-9);
+        assert_eq!(json["processs"][1]["peak_input_queue"], json!(2));
+    }
+
+    #[test]
+    fn blocked_time() {
+        let mut metrics = Metrics::new();
+        metrics.set_processs(vec!("blocked".to_string()));
+        metrics.blocked(0);
+        ::std::thread::sleep(Duration::from_millis(10));
+        metrics.unblocked(0);
+
+        assert!(metrics.processs[0].blocked_time >= Duration::from_millis(10));
+    }
+
+    #[test]
+    fn prometheus_format() {
+        let prometheus = test_metrics().to_prometheus();
+
+        assert!(prometheus.contains("# TYPE flow_process_invocations_total counter\n"));
+        assert!(prometheus.contains("flow_process_invocations_total{id=\"0\",name=\"add\"} 2\n"));
+        assert!(prometheus.contains("flow_process_input_queue_peak{id=\"1\",name=\"print \\\"it\\\"\"} 2\n"));
+    }
+
+    #[test]
+    fn resume_from_json() {
+        let mut metrics = test_metrics();
+        metrics.checkpoint();
+
+        let mut resumed: Metrics = ::serde_json::from_str(&metrics.to_json().unwrap()).unwrap();
+        resumed.resume();
+        resumed.dispatched(0);
+        resumed.end();
+
+        assert_eq!(resumed.invocations(), 3);
+        assert_eq!(resumed.processs[0].max_run_time, Duration::from_millis(20));
+        assert!(resumed.elapsed_time >= metrics.elapsed_time);
+    }
+}
@@ -1,11 +1,15 @@
+use errors::ImplementationError;
+use implementation::ERROR_OUTPUT;
 use implementation::Implementation;
-use implementation::RunAgain;
+use implementation::RunResult;
 use input::Input;
+use input::InputInitializer;
 use serde_json::Value as JsonValue;
-use runlist::RunList;
+use std::fmt;
+use std::sync::Arc;
 
 #[derive(Deserialize, Serialize)]
-pub struct Process<'a> {
+pub struct Process {
     #[serde(default, skip_serializing_if = "String::is_empty")]
     name: String,
     id: usize,
@@ -23,37 +27,37 @@ pub struct Process<'a> {
 
     #[serde(skip)]
     #[serde(default = "default_implementation")]
-    implementation: &'a dyn Implementation,
+    implementation: Arc<dyn Implementation>,
 }
 
 fn not_static(is_static: &bool) -> bool { *is_static == false }
 
+/// Is the route (of an output of a process) from its error output, or from part of it
+pub fn is_error_route(route: &str) -> bool {
+    let mut segments = route.split('/');
+    segments.next() == Some("") && segments.next() == Some(ERROR_OUTPUT)
+}
+
 struct ImplementationNotFound {}
 
 impl Implementation for ImplementationNotFound {
-    fn run(&self, process: &Process, _inputs: Vec<Vec<JsonValue>>, _run_list: &mut RunList)
-           -> RunAgain {
-        error!("Process '{}' called the implementation '{}', but it was not found",
-               process.name(),
-               process.implementation_source());
-        false
+    fn run(&self, _inputs: Vec<Vec<JsonValue>>) -> RunResult {
+        Err(ImplementationError::Failed("An implementation was called, but it was not found".to_string()))
     }
 }
 
-const NOT_FOUND: &Implementation = &ImplementationNotFound {};
-
-fn default_implementation() -> &'static Implementation {
-    NOT_FOUND
+fn default_implementation() -> Arc<dyn Implementation> {
+    Arc::new(ImplementationNotFound {})
 }
 
-impl<'a> Process<'a> {
+impl Process {
     pub fn new(name: &str,
                is_static: bool,
                implementation_source: String,
                input_depths: Vec<usize>,
                id: usize,
                initial_value: Option<JsonValue>,
-               output_routes: Vec<(String, usize, usize)>) -> Process<'a> {
+               output_routes: Vec<(String, usize, usize)>) -> Process {
         let implementation = default_implementation();
 
         let mut process = Process {
@@ -79,6 +83,11 @@ impl<'a> Process<'a> {
         }
     }
 
+    // Give an input a value without it being sent to it, once or always (as a constant)
+    pub fn set_input_initializer(&mut self, input_number: usize, initializer: InputInitializer) {
+        self.inputs[input_number].set_initializer(initializer);
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
@@ -87,11 +96,20 @@ impl<'a> Process<'a> {
         self.id
     }
 
+    pub fn is_static(&self) -> bool {
+        self.is_static
+    }
+
     /*
-        If an initial value is defined then write it to the current value.
-        Return true if ready to run as all inputs (single in this case) are satisfied.
+        If an initial value is defined then write it to the current value, and give inputs that
+        are initialized once their value.
+        Return true if ready to run as all inputs are satisfied.
     */
     pub fn init(&mut self) -> bool {
+        for input in &mut self.inputs {
+            input.init();
+        }
+
         let value = self.initial_value.clone();
         if let Some(v) = value {
             debug!("\t\tValue initialized by writing '{:?}' to input #0", &v);
@@ -105,7 +123,10 @@ impl<'a> Process<'a> {
     }
 
     pub fn write_input(&mut self, input_number: usize, input_value: JsonValue) {
-        if !self.inputs[input_number].full() {
+        if self.inputs[input_number].constant().is_some() {
+            error!("\t\t\tProcess #{} '{}' Input number {} is a constant and cannot be sent to",
+                   self.id(), self.name(), input_number);
+        } else if !self.inputs[input_number].full() {
             self.inputs[input_number].push(input_value);
         } else {
             // a static value is never emptied when run, so allow it to be overwritten when full
@@ -121,11 +142,16 @@ impl<'a> Process<'a> {
         &self.output_routes
     }
 
-    pub fn get_implementation(&self) -> &Implementation {
-        self.implementation
+    // Is anything connected to the error output of this process, to handle errors it produces
+    pub fn error_output_connected(&self) -> bool {
+        self.output_routes.iter().any(|&(ref route, _, _)| is_error_route(route))
+    }
+
+    pub fn get_implementation(&self) -> Arc<dyn Implementation> {
+        Arc::clone(&self.implementation)
     }
 
-    pub fn set_implementation(&mut self, implementation: &'a dyn Implementation) {
+    pub fn set_implementation(&mut self, implementation: Arc<dyn Implementation>) {
         self.implementation = implementation;
     }
 
@@ -133,6 +159,11 @@ impl<'a> Process<'a> {
         self.inputs[input_number].full()
     }
 
+    // the number of values waiting to be consumed on an input
+    pub fn input_count(&self, input_number: usize) -> usize {
+        self.inputs[input_number].count()
+    }
+
     // responds true if all inputs have been satisfied and this process can be run - false otherwise
     pub fn can_run(&self) -> bool {
         for input in &self.inputs {
@@ -144,6 +175,37 @@ impl<'a> Process<'a> {
         return true;
     }
 
+    /*
+        Return the inputs that have values waiting on them, with their input number. A static value
+        always has a value on its input, so it never has values pending.
+    */
+    pub fn pending_inputs(&self) -> Vec<(usize, &Input)> {
+        if self.is_static {
+            return vec!();
+        }
+
+        self.inputs.iter().enumerate().filter(|&(_, input)| !input.is_empty()).collect()
+    }
+
+    // the values waiting to be consumed on each input, without consuming them
+    pub fn queued_input_values(&self) -> Vec<Vec<JsonValue>> {
+        self.inputs.iter().map(|input| input.values().clone()).collect()
+    }
+
+    // Replace the values on the inputs with ones restored from a snapshot
+    pub fn restore_input_values(&mut self, values: Vec<Vec<JsonValue>>) -> Result<(), String> {
+        if values.len() != self.inputs.len() {
+            return Err(format!("Process #{} '{}' has {} inputs but {} were restored", self.id,
+                               self.name, self.inputs.len(), values.len()));
+        }
+        let (id, name) = (self.id, &self.name);
+        for (input, input_values) in self.inputs.iter_mut().zip(values) {
+            input.restore(input_values)
+                .map_err(|e| format!("Process #{} '{}': {}", id, name, e))?;
+        }
+        Ok(())
+    }
+
     pub fn get_input_values(&mut self) -> Vec<Vec<JsonValue>> {
         let mut input_values: Vec<Vec<JsonValue>> = Vec::new();
         for input_value in &mut self.inputs {
@@ -157,10 +219,24 @@ impl<'a> Process<'a> {
     }
 }
 
+impl fmt::Display for Process {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Process #{} '{}' ({})\n", self.id, self.name, self.implementation_source)?;
+        for (number, input) in self.inputs.iter().enumerate() {
+            write!(f, "\tInput #{}: {}\n", number, input)?;
+        }
+        for &(ref route, destination_id, io_number) in &self.output_routes {
+            write!(f, "\tOutput '{}' --> Process #{} input #{}\n", route, destination_id, io_number)?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use serde_json::value::Value as JsonValue;
-    use super::Process;
+    use input::InputInitializer;
+    use super::{is_error_route, Process};
 
     #[test]
     fn destructure_output_base_route() {
@@ -219,6 +295,26 @@ mod test {
         assert_eq!(process.get_input_values().remove(0).remove(0), json!(2));
     }
 
+    #[test]
+    fn pending_inputs() {
+        let mut process = Process::new("test", false, "/test".to_string(), vec!(1, 2), 0,
+                                       None, vec!());
+        process.init();
+        assert!(process.pending_inputs().is_empty());
+        process.write_input(1, json!(1));
+        let pending = process.pending_inputs();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].0, 1);
+    }
+
+    #[test]
+    fn static_has_no_pending_inputs() {
+        let mut process = Process::new("test", true, "/test".to_string(), vec!(1), 0,
+                                       Some(json!(1)), vec!());
+        process.init();
+        assert!(process.pending_inputs().is_empty());
+    }
+
     #[test]
     fn cannot_send_input_if_full_and_not_static() {
         let mut process = Process::new("test", false, "/test".to_string(), vec!(1), 0,
@@ -228,4 +324,38 @@ mod test {
         process.write_input(0, json!(2)); // fail
         assert_eq!(process.get_input_values().remove(0).remove(0), json!(1));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn constant_input_always_full() {
+        let mut process = Process::new("test", false, "/test".to_string(), vec!(1, 2), 0,
+                                       None, vec!());
+        process.set_input_initializer(1, InputInitializer::Constant(json!(10)));
+        process.init();
+        assert!(process.pending_inputs().is_empty());
+        assert!(!process.can_run());
+        process.write_input(0, json!(1));
+        assert!(process.can_run());
+        assert_eq!(process.get_input_values(), vec!(vec!(json!(1)), vec!(json!(10), json!(10))));
+        process.write_input(0, json!(2));
+        assert_eq!(process.get_input_values(), vec!(vec!(json!(2)), vec!(json!(10), json!(10))));
+    }
+
+    #[test]
+    fn input_initialized_once() {
+        let mut process = Process::new("test", false, "/test".to_string(), vec!(1), 0,
+                                       None, vec!());
+        process.set_input_initializer(0, InputInitializer::Once(json!(5)));
+        assert!(process.init());
+        assert_eq!(process.get_input_values(), vec!(vec!(json!(5))));
+        assert!(!process.can_run());
+    }
+
+    #[test]
+    fn error_routes() {
+        assert!(is_error_route("/error"));
+        assert!(is_error_route("/error/message"));
+        assert!(!is_error_route(""));
+        assert!(!is_error_route("/errors"));
+        assert!(!is_error_route("/result/error"));
+    }
+}
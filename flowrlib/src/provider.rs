@@ -1,3 +1,4 @@
+use errors::ProviderError;
 use url::Url;
 
 pub trait Provider {
@@ -6,9 +7,9 @@ pub trait Provider {
     /// directory (a file provider) or a server path (an http provider), or it may involve
     /// translating a virtual Url into a real on where content can be found (lib provider).
     /// It also returns an optional String which is a library reference in case that applies.
-    fn resolve(&self, url: &Url) -> Result<(Url, Option<String>), String>;
+    fn resolve(&self, url: &Url) -> Result<(Url, Option<String>), ProviderError>;
 
     /// 'get' fetches content from a url. It resolves the url internally before attempting to
-    /// fetch actual content
-    fn get(&self, url: &Url) -> Result<String, String>;
+    /// fetch actual content. Content is returned as raw bytes, as it maybe binary (e.g. WASM)
+    fn get(&self, url: &Url) -> Result<Vec<u8>, ProviderError>;
 }
\ No newline at end of file
@@ -1,40 +1,19 @@
+use checkpoint::{ProcessSnapshot, Snapshot};
+use errors::ExecutionError;
+use errors::ImplementationError;
+use implementation::ERROR_OUTPUT;
+use metrics::Metrics;
+use process::is_error_route;
 use process::Process;
 use serde_json::Value as JsonValue;
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fmt;
 use std::panic::RefUnwindSafe;
 use std::panic::UnwindSafe;
 use std::sync::{Arc, Mutex};
-use std::time::Instant;
-
-pub struct Metrics {
-    num_processs: usize,
-    invocations: u32,
-    outputs_sent: u32,
-    start_time: Instant,
-}
-
-impl Metrics {
-    fn new() -> Self {
-        let now = Instant::now();
-        Metrics {
-            num_processs: 0,
-            invocations: 0,
-            outputs_sent: 0,
-            start_time: now,
-        }
-    }
-}
-
-impl fmt::Display for Metrics {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let elapsed = self.start_time.elapsed();
-        write!(f, "\t\tNumber of Processs: \t{}\n", self.num_processs)?;
-        write!(f, "\t\tProcess invocations: \t{}\n", self.invocations)?;
-        write!(f, "\t\tOutputs sent: \t\t{}\n", self.outputs_sent)?;
-        write!(f, "\t\tElapsed time(s): \t{:.*}\n", 9, elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 * 1e-9)
-    }
-}
+use std::time::{Duration, Instant};
+use tracer::Tracer;
 
 /*
     RunList is a structure that maintains the state of all the processs in the currently
@@ -56,62 +35,183 @@ impl fmt::Display for Metrics {
     ready:
     A list of Processs who are ready to be run, they have their inputs satisfied and they are not
     blocked on the output (so their output can be produced).
+
+    running:
+    A list of processs that have been dispatched and have not completed yet. A process is not made
+    ready again until it has completed, so only one invocation of a process runs at a time.
 */
-pub struct RunList<'a> {
-    processs: Vec<Arc<Mutex<Process<'a>>>>,
+pub struct RunList {
+    processs: Vec<Arc<Mutex<Process>>>,
     can_run: HashSet<usize>,
     // process_id
     blocking: Vec<(usize, usize)>,
     // blocking_id, blocked_id
     will_run: Vec<usize>,
     // process_id
+    running: HashSet<usize>,
+    // process_id
     metrics: Metrics,
+    tracer: Option<Tracer>,
 }
 
-impl<'a> RefUnwindSafe for RunList<'a> {}
+impl RefUnwindSafe for RunList {}
 
-impl<'a> UnwindSafe for RunList<'a> {}
+impl UnwindSafe for RunList {}
 
-impl<'a> RunList<'a> {
+impl RunList {
     pub fn new() -> Self {
         RunList {
             processs: Vec::<Arc<Mutex<Process>>>::new(),
             can_run: HashSet::<usize>::new(),
             blocking: Vec::<(usize, usize)>::new(),
             will_run: Vec::<usize>::new(),
+            running: HashSet::<usize>::new(),
             metrics: Metrics::new(),
+            tracer: None,
         }
     }
 
     pub fn debug(&self) {
-        debug!("Dispatch count: {}", self.metrics.invocations);
+        debug!("Dispatch count: {}", self.metrics.invocations());
         debug!("       Can Run: {:?}", self.can_run);
         debug!("      Blocking: {:?}", self.blocking);
         debug!("      Will Run: {:?}", self.will_run);
+        debug!("       Running: {:?}", self.running);
         debug!("-------------------------------------");
     }
 
-    pub fn end(&self) {
+    pub fn end(&mut self) {
+        self.metrics.end();
         debug!("Metrics: \n {}", self.metrics);
+
+        // close the spans of processs still blocked, so they are shown up to the end of the trace
+        if let Some(ref mut tracer) = self.tracer {
+            let mut still_blocked = vec!();
+            for &(_blocking_id, blocked_id) in &self.blocking {
+                if !still_blocked.contains(&blocked_id) {
+                    still_blocked.push(blocked_id);
+                    tracer.unblocked(blocked_id);
+                }
+            }
+        }
+    }
+
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    // Record a trace of the execution of the flow. This should be called before 'set_processs'
+    pub fn trace(&mut self) {
+        self.tracer = Some(Tracer::new());
     }
 
-    pub fn set_processs(&mut self, processs: Vec<Arc<Mutex<Process<'a>>>>) {
+    pub fn tracer(&self) -> Option<&Tracer> {
+        self.tracer.as_ref()
+    }
+
+    pub fn set_processs(&mut self, processs: Vec<Arc<Mutex<Process>>>) {
+        let names: Vec<String> = processs.iter()
+            .map(|process| process.lock().unwrap().name().to_string()).collect();
+        if let Some(ref mut tracer) = self.tracer {
+            tracer.set_processs(names.clone());
+        }
+        self.metrics.set_processs(names);
         self.processs = processs;
-        self.metrics.num_processs = self.processs.len();
     }
 
-    pub fn get(&self, id: usize) -> Arc<Mutex<Process<'a>>> {
+    /*
+        Take a snapshot of the state of execution. This should only be done when no processs are
+        running, as the state of a running process is not part of the snapshot.
+    */
+    pub fn snapshot(&mut self, manifest: &str, arguments: &[String]) -> Snapshot {
+        self.metrics.checkpoint();
+        let mut can_run: Vec<usize> = self.can_run.iter().cloned().collect();
+        can_run.sort();
+
+        Snapshot {
+            manifest: manifest.to_string(),
+            arguments: arguments.to_vec(),
+            processs: self.processs.iter().map(|process_arc| {
+                let process = process_arc.lock().unwrap();
+                ProcessSnapshot {
+                    id: process.id(),
+                    name: process.name().to_string(),
+                    inputs: process.queued_input_values(),
+                }
+            }).collect(),
+            can_run,
+            blocking: self.blocking.clone(),
+            will_run: self.will_run.clone(),
+            metrics: self.metrics.clone(),
+        }
+    }
+
+    /*
+        Restore the state of execution from a snapshot, instead of initializing the processs. It
+        must be a snapshot of the same flow, with the same processs, which should be set first.
+    */
+    pub fn restore(&mut self, snapshot: Snapshot) -> Result<(), ExecutionError> {
+        if snapshot.processs.len() != self.processs.len() {
+            return Err(ExecutionError::Resume(format!("the snapshot has {} processs but the flow has {}",
+                                                      snapshot.processs.len(), self.processs.len())));
+        }
+
+        for (process_arc, process_snapshot) in self.processs.iter().zip(snapshot.processs) {
+            let mut process = process_arc.lock().unwrap();
+            if process.id() != process_snapshot.id || process.name() != process_snapshot.name {
+                return Err(ExecutionError::Resume(
+                    format!("Process #{} '{}' in the snapshot does not match Process #{} '{}' of the flow",
+                            process_snapshot.id, process_snapshot.name, process.id(), process.name())));
+            }
+            process.restore_input_values(process_snapshot.inputs).map_err(ExecutionError::Resume)?;
+        }
+
+        let mut ids = snapshot.can_run.clone();
+        ids.extend(&snapshot.will_run);
+        for &(blocking_id, blocked_id) in &snapshot.blocking {
+            ids.push(blocking_id);
+            ids.push(blocked_id);
+        }
+        if let Some(id) = ids.into_iter().find(|&id| id >= self.processs.len()) {
+            return Err(ExecutionError::Resume(format!("the snapshot refers to Process #{} that is not in the flow", id)));
+        }
+
+        self.can_run = snapshot.can_run.into_iter().collect();
+        self.blocking = snapshot.blocking;
+        self.will_run = snapshot.will_run;
+        self.metrics = snapshot.metrics;
+        self.metrics.resume();
+        let mut blocked = vec!();
+        for &(blocking_id, blocked_id) in &self.blocking {
+            if !blocked.contains(&blocked_id) {
+                blocked.push(blocked_id);
+                self.metrics.blocked(blocked_id);
+                if let Some(ref mut tracer) = self.tracer {
+                    tracer.blocked(blocked_id, blocking_id);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn get(&self, id: usize) -> Arc<Mutex<Process>> {
         self.processs[id].clone()
     }
 
+    pub fn processs(&self) -> &Vec<Arc<Mutex<Process>>> {
+        &self.processs
+    }
+
     // Return the id of the next process ready to be run, if there is one
     pub fn next(&mut self) -> Option<usize> {
         if self.will_run.is_empty() {
             return None;
         }
 
-        self.metrics.invocations += 1;
-        Some(self.will_run.remove(0))
+        let id = self.will_run.remove(0);
+        self.metrics.dispatched(id);
+        Some(id)
     }
 
     // save the fact that a particular Process's inputs are now satisfied and so it maybe ready
@@ -119,8 +219,40 @@ impl<'a> RunList<'a> {
     pub fn can_run(&mut self, id: usize) {
         debug!("\t\t\tProcess #{} inputs are ready", id);
         self.can_run.insert(id);
+        self.make_ready(id);
+    }
+
+    // save the fact that a process has been dispatched and is now running
+    pub fn start(&mut self, id: usize) {
+        debug!("\tProcess #{} is running", id);
+        if let Some(ref mut tracer) = self.tracer {
+            tracer.dispatched(id);
+        }
+        self.running.insert(id);
+    }
 
-        if !self.is_blocked(id) {
+    // save the fact that a process has completed, and when it started running and how long it ran
+    // for. If it's inputs were satisfied again while it was running then it maybe ready to run again
+    pub fn done(&mut self, id: usize, start: Instant, run_time: Duration) {
+        debug!("\tProcess #{} completed", id);
+        self.metrics.ran(id, run_time);
+        if let Some(ref mut tracer) = self.tracer {
+            tracer.ran(id, start, run_time);
+        }
+        self.running.remove(&id);
+        self.make_ready(id);
+    }
+
+    // Return the number of processs currently running
+    pub fn number_running(&self) -> usize {
+        self.running.len()
+    }
+
+    // add a process with it's inputs satisfied to the end of the 'Will Run' list, unless it is blocked
+    // on output, still running or is already on the list
+    fn make_ready(&mut self, id: usize) {
+        if self.can_run.contains(&id) && !self.is_blocked(id) && !self.running.contains(&id)
+            && !self.will_run.contains(&id) {
             debug!("\t\t\tProcess #{} not blocked on output, so added to end of 'Will Run' list", id);
             self.will_run.push(id);
         }
@@ -140,32 +272,82 @@ impl<'a> RunList<'a> {
         Then take the output and send it to all destination IOs on different processs it should be
         sent to, marking the source process as blocked because those others must consume the output
         if those other processs have all their inputs, then mark them accordingly.
+
+        Connections from the process's error output are not sent normal output values.
+
+        If the output does not have a value at the route of one of the connections from it then
+        none of it is sent, and an error is returned.
     */
-    pub fn send_output(&mut self, process: &Process, output: JsonValue) {
+    pub fn send_output(&mut self, process: &Process, output: JsonValue) -> Result<(), ImplementationError> {
+        let mut output_values = vec!();
         for &(ref output_route, destination_id, io_number) in process.output_destinations() {
-            let destination_arc = Arc::clone(&self.processs[destination_id]);
-            let mut destination = destination_arc.lock().unwrap();
-            let output_value = output.pointer(&output_route).unwrap();
-            debug!("\t\tProcess #{} '{}{}' sending output '{}' to Process #{} '{}' input #{}",
-                   process.id(), process.name(), output_route, output_value, &destination_id,
-                   destination.name(), &io_number);
-            destination.write_input(io_number, output_value.clone());
-            self.metrics.outputs_sent += 1;
-            if destination.input_full(io_number) {
-                self.blocked_by(destination_id, process.id());
+            if !is_error_route(output_route) {
+                match output.pointer(output_route) {
+                    Some(output_value) => output_values.push((output_route, output_value, destination_id, io_number)),
+                    None => return Err(ImplementationError::MissingOutput {
+                        route: output_route.clone(),
+                        output: output.clone(),
+                    })
+                }
             }
+        }
+
+        for (output_route, output_value, destination_id, io_number) in output_values {
+            self.send(process, output_value, output_route, destination_id, io_number);
+        }
+        Ok(())
+    }
 
-            if destination.can_run() {
-                self.can_run(destination_id);
+    /*
+        Send an error produced by a process to the destinations connected to its error output.
+        A connection from part of the error that it doesn't have is not sent anything.
+    */
+    pub fn send_error(&mut self, process: &Process, error: JsonValue) {
+        let output = json!({ERROR_OUTPUT: error});
+        for &(ref output_route, destination_id, io_number) in process.output_destinations() {
+            if is_error_route(output_route) {
+                match output.pointer(output_route) {
+                    Some(output_value) => self.send(process, output_value, output_route, destination_id, io_number),
+                    None => error!("Process #{} '{}' error has no value at route '{}': {}", process.id(),
+                                   process.name(), output_route, error)
+                }
             }
         }
     }
 
+    fn send(&mut self, process: &Process, output_value: &JsonValue, output_route: &str, destination_id: usize,
+            io_number: usize) {
+        let destination_arc = Arc::clone(&self.processs[destination_id]);
+        let mut destination = destination_arc.lock().unwrap();
+        debug!("\t\tProcess #{} '{}{}' sending output '{}' to Process #{} '{}' input #{}",
+               process.id(), process.name(), output_route, output_value, &destination_id,
+               destination.name(), &io_number);
+        destination.write_input(io_number, output_value.clone());
+        self.metrics.output_sent(process.id());
+        self.metrics.input_queue(destination_id, destination.input_count(io_number));
+        if let Some(ref mut tracer) = self.tracer {
+            tracer.output_sent(process.id(), output_route, destination_id, io_number, output_value);
+        }
+        if destination.input_full(io_number) {
+            self.blocked_by(destination_id, process.id());
+        }
+
+        if destination.can_run() {
+            self.can_run(destination_id);
+        }
+    }
+
     // Save the fact that the process 'blocked_id' is blocked on it's output by 'blocking_id'
     pub fn blocked_by(&mut self, blocking_id: usize, blocked_id: usize) {
         // avoid deadlocks by a process blocking itself
         if blocked_id != blocking_id {
             debug!("\t\t\tProcess #{} is now blocked on output by Process #{}", &blocked_id, &blocking_id);
+            if !self.is_blocked(blocked_id) {
+                self.metrics.blocked(blocked_id);
+                if let Some(ref mut tracer) = self.tracer {
+                    tracer.blocked(blocked_id, blocking_id);
+                }
+            }
             self.blocking.push((blocking_id, blocked_id));
         }
     }
@@ -173,13 +355,14 @@ impl<'a> RunList<'a> {
     // unblock all processs that were blocked trying to send to blocker_id by removing all entries
     // in the list where the first value (blocking_id) matches the destination_id
     // when each is unblocked on output, if it's inputs are satisfied, then it is ready to be run
-    // again, so put it on the ready queue
+    // again, so put it on the ready queue. A process sending to more than one input of blocker_id
+    // has an entry for each of them, but is only unblocked once.
     pub fn unblock_senders_to(&mut self, blocker_id: usize) {
         if !self.blocking.is_empty() {
             let mut unblocked_list = vec!();
 
             for &(blocking_id, blocked_id) in &self.blocking {
-                if blocking_id == blocker_id {
+                if blocking_id == blocker_id && !unblocked_list.contains(&blocked_id) {
                     debug!("\t\tProcess #{} <-- #{} - block removed", blocking_id, blocked_id);
                     unblocked_list.push(blocked_id);
                 }
@@ -191,14 +374,121 @@ impl<'a> RunList<'a> {
             // see if the ones unblocked should be made ready. Note, they could be blocked on others not the
             // one that unblocked.
             for unblocked in unblocked_list {
-                if self.can_run.contains(&unblocked) && !self.is_blocked(unblocked) {
-                    debug!("\t\t\tProcess #{} was unblocked and it inputs are ready, so added to end of 'Will Run' list", unblocked);
-                    self.will_run.push(unblocked);
+                if !self.is_blocked(unblocked) {
+                    self.metrics.unblocked(unblocked);
+                    if let Some(ref mut tracer) = self.tracer {
+                        tracer.unblocked(unblocked);
+                    }
                 }
+                self.make_ready(unblocked);
             }
         }
     }
 
+    /*
+        When execution has ended, a process that has all of its inputs satisfied but was not run
+        has data that will never be processed and an output that will never be produced. Return a
+        description of each process in that state, including the chains of processs that are
+        blocking it.
+
+        It is not stalled if each of those chains is held up by a process that has values on some,
+        but not all, of its inputs (e.g. a gate or the input of a loop left with a value when the
+        flow stopped producing values for its other inputs), as that is a normal end to a flow.
+        Those, and the processs left with values on only some of their inputs, are only logged as
+        warnings. Values sent by a static value are always left on the inputs they are sent to, so
+        those are ignored.
+    */
+    pub fn stalled(&self) -> Vec<String> {
+        let mut names = vec!();
+        let mut static_ids = HashSet::<usize>::new();
+        let mut waiting = HashSet::<usize>::new();
+        let mut senders = HashMap::<(usize, usize), Vec<usize>>::new();
+        for process_arc in &self.processs {
+            let process = process_arc.lock().unwrap();
+            names.push(process.name().to_string());
+            if process.is_static() {
+                static_ids.insert(process.id());
+            }
+            if !process.pending_inputs().is_empty() && !process.can_run() {
+                waiting.insert(process.id());
+            }
+            for &(_, destination_id, io_number) in process.output_destinations() {
+                senders.entry((destination_id, io_number)).or_insert(vec!()).push(process.id());
+            }
+        }
+
+        let mut stalled = vec!();
+        for (id, process_arc) in self.processs.iter().enumerate() {
+            let process = process_arc.lock().unwrap();
+            let pending: Vec<_> = process.pending_inputs().into_iter()
+                .filter(|&(number, _)| match senders.get(&(id, number)) {
+                    Some(sender_ids) => !sender_ids.iter().all(|sender| static_ids.contains(sender)),
+                    None => true
+                }).collect();
+
+            if pending.is_empty() {
+                continue;
+            }
+
+            let mut description = if process.can_run() {
+                format!("Process #{} '{}' has all its inputs satisfied but was not run:", id, names[id])
+            } else {
+                format!("Process #{} '{}' has values pending on some of its inputs:", id, names[id])
+            };
+            for (number, input) in pending {
+                description.push_str(&format!("\n\tInput #{}: {}", number, input));
+            }
+
+            let mut chains = vec!();
+            if self.is_blocked(id) {
+                description.push_str("\n\tIt is blocked sending its output (each process is blocked by the next):");
+                self.blocker_chains(&mut vec!(id), &mut chains);
+                for chain in &chains {
+                    let chain: Vec<String> = chain.iter()
+                        .map(|&chain_id| format!("#{} '{}'", chain_id, names[chain_id])).collect();
+                    description.push_str(&format!("\n\t\t{}", chain.join(" --> ")));
+                }
+            }
+
+            let held_by_waiting = !chains.is_empty() &&
+                chains.iter().all(|chain| chain[1..].iter().any(|chain_id| waiting.contains(chain_id)));
+            if process.can_run() && !held_by_waiting {
+                stalled.push(description);
+            } else {
+                warn!("{}", description);
+            }
+        }
+
+        stalled
+    }
+
+    /*
+        Follow the blocking entries from the last process in 'chain' to the processs blocking it,
+        and so on, adding each complete chain found to 'chains'. A chain that comes back to a process
+        already in it is a loop, so stops there.
+    */
+    fn blocker_chains(&self, chain: &mut Vec<usize>, chains: &mut Vec<Vec<usize>>) {
+        let id = chain[chain.len() - 1];
+        let blockers: Vec<usize> = self.blocking.iter()
+            .filter(|&&(_blocking_id, blocked_id)| blocked_id == id)
+            .map(|&(blocking_id, _blocked_id)| blocking_id).collect();
+
+        if blockers.is_empty() {
+            chains.push(chain.clone());
+        }
+
+        for blocker in blockers {
+            let is_loop = chain.contains(&blocker);
+            chain.push(blocker);
+            if is_loop {
+                chains.push(chain.clone());
+            } else {
+                self.blocker_chains(chain, chains);
+            }
+            chain.pop();
+        }
+    }
+
     // TODO ADM optimize this by also having a flag in the process?
     // Or use the blocked_id as a key to a HashSet?
     // See if there is any tuple in the vector where the second (blocked_id) is the one we're after
@@ -212,14 +502,25 @@ impl<'a> RunList<'a> {
     }
 }
 
+impl fmt::Display for RunList {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Dispatch count: {}\n", self.metrics.invocations())?;
+        write!(f, "       Can Run: {:?}\n", self.can_run)?;
+        write!(f, "      Blocking: {:?}\n", self.blocking)?;
+        write!(f, "      Will Run: {:?}\n", self.will_run)?;
+        write!(f, "       Running: {:?}\n", self.running)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
 
     use super::Process;
     use super::RunList;
 
-    fn test_processs<'a>() -> Vec<Arc<Mutex<Process<'a>>>> {
+    fn test_processs() -> Vec<Arc<Mutex<Process>>> {
         let p0 = Arc::new(Mutex::new(
             Process::new("p0", // name
                          false,// static value
@@ -350,4 +651,178 @@ mod tests {
         // Now process with id 0 should still not be ready as still blocked on 2
         assert_eq!(runs.next(), None);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn nothing_stalled() {
+        let processs = test_processs();
+        let mut runs = RunList::new();
+        runs.set_processs(processs);
+
+        assert!(runs.stalled().is_empty());
+    }
+
+    #[test]
+    fn partial_inputs_not_stalled() {
+        let mut processs = test_processs();
+        processs.push(Arc::new(Mutex::new(Process::new("p3", false, "/test".to_string(),
+                                                       vec!(1, 1), 3, None, vec!()))));
+        let mut runs = RunList::new();
+        runs.set_processs(processs);
+        runs.get(3).lock().unwrap().write_input(0, json!(1));
+
+        assert!(runs.stalled().is_empty());
+    }
+
+    #[test]
+    fn blocked_by_process_waiting_for_values_not_stalled() {
+        let mut processs = test_processs();
+        processs.push(Arc::new(Mutex::new(Process::new("gate", false, "/test".to_string(),
+                                                       vec!(1, 1), 3, None, vec!()))));
+        let mut runs = RunList::new();
+        runs.set_processs(processs);
+        runs.get(1).lock().unwrap().write_input(0, json!(1));
+        runs.get(3).lock().unwrap().write_input(0, json!(1));
+
+        // 1 is blocked by the gate, which has a value left on one of its inputs
+        runs.blocked_by(3, 1);
+
+        assert!(runs.stalled().is_empty());
+    }
+
+    #[test]
+    fn satisfied_inputs_stalled() {
+        let processs = test_processs();
+        let mut runs = RunList::new();
+        runs.set_processs(processs);
+        runs.get(1).lock().unwrap().write_input(0, json!(1));
+
+        let stalled = runs.stalled();
+        assert_eq!(stalled.len(), 1);
+        assert!(stalled[0].starts_with("Process #1 'p1' has all its inputs satisfied but was not run:"));
+    }
+
+    #[test]
+    fn values_from_static_not_stalled() {
+        let processs = vec!(
+            Arc::new(Mutex::new(Process::new("constant", true, "/test".to_string(), vec!(1), 0,
+                                             Some(json!(1)), vec!(("".to_string(), 1, 0))))),
+            Arc::new(Mutex::new(Process::new("p1", false, "/test".to_string(), vec!(1), 1,
+                                             None, vec!()))));
+        let mut runs = RunList::new();
+        runs.set_processs(processs);
+        runs.get(1).lock().unwrap().write_input(0, json!(1));
+
+        assert!(runs.stalled().is_empty());
+    }
+
+    #[test]
+    fn blocker_chain_reported() {
+        let processs = test_processs();
+        let mut runs = RunList::new();
+        runs.set_processs(processs);
+        runs.get(1).lock().unwrap().write_input(0, json!(1));
+
+        // 1 is blocked by 2, which is blocked by 0
+        runs.blocked_by(2, 1);
+        runs.blocked_by(0, 2);
+
+        let stalled = runs.stalled();
+        assert_eq!(stalled.len(), 1);
+        assert!(stalled[0].ends_with("\t\t#1 'p1' --> #2 'p2' --> #0 'p0'"));
+    }
+
+    #[test]
+    fn blocker_loop_reported() {
+        let processs = test_processs();
+        let mut runs = RunList::new();
+        runs.set_processs(processs);
+        runs.get(1).lock().unwrap().write_input(0, json!(1));
+
+        runs.blocked_by(2, 1);
+        runs.blocked_by(1, 2);
+
+        let stalled = runs.stalled();
+        assert!(stalled[0].ends_with("\t\t#1 'p1' --> #2 'p2' --> #1 'p1'"));
+    }
+
+    #[test]
+    fn sender_to_two_inputs_unblocked_once() {
+        let processs = test_processs();
+        let mut runs = RunList::new();
+        runs.trace();
+        runs.set_processs(processs);
+
+        // 0 sends to two inputs of 1, and is blocked by both
+        runs.blocked_by(1, 0);
+        runs.blocked_by(1, 0);
+        runs.unblock_senders_to(1);
+        assert!(!runs.is_blocked(0));
+
+        let trace = runs.tracer().unwrap().to_json().unwrap();
+        assert_eq!(trace.matches("\"ph\":\"b\"").count(), 1);
+        assert_eq!(trace.matches("\"ph\":\"e\"").count(), 1);
+    }
+
+    #[test]
+    fn running_is_not_ready_until_done() {
+        let processs = test_processs();
+        let mut runs = RunList::new();
+        runs.set_processs(processs);
+
+        runs.can_run(0);
+        assert_eq!(runs.next(), Some(0));
+        runs.inputs_consumed(0);
+        runs.start(0);
+
+        // Inputs of 0 are satisfied again while it is still running
+        runs.can_run(0);
+        assert_eq!(runs.next(), None);
+
+        // Now it has completed it can be run again
+        runs.done(0, Instant::now(), Duration::new(0, 0));
+        assert_eq!(runs.next(), Some(0));
+    }
+
+    #[test]
+    fn ready_only_once() {
+        let processs = test_processs();
+        let mut runs = RunList::new();
+        runs.set_processs(processs);
+
+        runs.can_run(0);
+        runs.can_run(0);
+
+        assert_eq!(runs.next(), Some(0));
+        assert_eq!(runs.next(), None);
+    }
+
+    #[test]
+    fn snapshot_restored() {
+        let mut runs = RunList::new();
+        runs.set_processs(test_processs());
+        runs.get(1).lock().unwrap().write_input(0, json!(42));
+        runs.can_run(1);
+        runs.blocked_by(1, 0);
+        let snapshot = runs.snapshot("file:///test/manifest.json", &vec!("arg".to_string()));
+
+        let mut restored = RunList::new();
+        restored.set_processs(test_processs());
+        restored.restore(snapshot).unwrap();
+
+        assert!(restored.is_blocked(0));
+        assert_eq!(restored.get(1).lock().unwrap().queued_input_values(), vec!(vec!(json!(42))));
+        assert_eq!(restored.next(), Some(1));
+    }
+
+    #[test]
+    fn snapshot_of_other_flow_not_restored() {
+        let mut runs = RunList::new();
+        runs.set_processs(test_processs());
+        let snapshot = runs.snapshot("file:///test/manifest.json", &vec!());
+
+        let mut other = RunList::new();
+        other.set_processs(vec!(Arc::new(Mutex::new(Process::new("other", false, "/test".to_string(),
+                                                                 vec!(1), 0, None, vec!())))));
+        assert!(other.restore(snapshot).is_err());
+    }
+}
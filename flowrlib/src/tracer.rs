@@ -0,0 +1,166 @@
+use serde_json;
+use serde_json::Value as JsonValue;
+use std::time::{Duration, Instant};
+
+// All events are for processs of the one flow being executed
+const PID: usize = 1;
+
+/*
+    An event in the Chrome trace event format. 'ts' is the time of the event in microseconds
+    since the start of the trace and 'tid' is the id of the process the event is for, so the
+    events of each process are shown on their own line in a trace viewer.
+*/
+#[derive(Serialize)]
+struct Event {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cat: Option<&'static str>,
+    ph: &'static str,
+    ts: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dur: Option<f64>,
+    pid: usize,
+    tid: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    s: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    args: Option<JsonValue>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Trace<'a> {
+    trace_events: &'a Vec<Event>,
+    display_time_unit: &'static str,
+}
+
+/// A `Tracer` records the events of the execution of a flow (processs being dispatched, sending
+/// output and being blocked and unblocked) with the time they happened, so a run of a flow can
+/// be viewed in a trace viewer that supports the Chrome trace event format (e.g. `chrome://tracing`
+/// or Perfetto).
+///
+/// A process being dispatched is shown as an instant event, recorded when the thread executing the
+/// flow dispatched it, and running as a span of the time it's implementation ran for on a worker
+/// thread, so the time waiting for a worker is the gap between them. Being blocked sending its
+/// output is shown as an asynchronous span and sending an output as an instant event.
+pub struct Tracer {
+    start_time: Instant,
+    events: Vec<Event>,
+    names: Vec<String>,
+}
+
+impl Tracer {
+    pub fn new() -> Self {
+        Tracer {
+            start_time: Instant::now(),
+            events: vec!(),
+            names: vec!(),
+        }
+    }
+
+    // Set the names of the processs to trace, in order of their ids
+    pub fn set_processs(&mut self, names: Vec<String>) {
+        for (id, name) in names.iter().enumerate() {
+            self.events.push(Event {
+                name: "thread_name".to_string(),
+                cat: None,
+                ph: "M",
+                ts: 0.0,
+                dur: None,
+                pid: PID,
+                tid: id,
+                id: None,
+                s: None,
+                args: Some(json!({"name": format!("#{} {}", id, name)})),
+            });
+        }
+        self.names = names;
+    }
+
+    // A process was dispatched to be run
+    pub fn dispatched(&mut self, id: usize) {
+        self.event("dispatch".to_string(), "dispatch", "i", id, None, None);
+    }
+
+    // A process ran, starting at 'start' and taking 'run_time'
+    pub fn ran(&mut self, id: usize, start: Instant, run_time: Duration) {
+        let name = self.name(id);
+        let ts = micros(start.duration_since(self.start_time));
+        let dur = Some(micros(run_time));
+        self.events.push(Event { name, cat: Some("run"), ph: "X", ts, dur, pid: PID, tid: id, id: None, s: None, args: None });
+    }
+
+    pub fn output_sent(&mut self, id: usize, route: &str, destination_id: usize, io_number: usize,
+                       value: &JsonValue) {
+        self.event("send_output".to_string(), "output", "i", id, None,
+                   Some(json!({"route": route, "destination": destination_id, "input": io_number,
+                               "value": value})));
+    }
+
+    pub fn blocked(&mut self, id: usize, blocking_id: usize) {
+        self.event("blocked".to_string(), "block", "b", id, Some(id),
+                   Some(json!({"blocked_by": blocking_id})));
+    }
+
+    pub fn unblocked(&mut self, id: usize) {
+        self.event("blocked".to_string(), "block", "e", id, Some(id), None);
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        let trace = Trace {
+            trace_events: &self.events,
+            display_time_unit: "ms",
+        };
+        serde_json::to_string(&trace)
+    }
+
+    fn name(&self, id: usize) -> String {
+        match self.names.get(id) {
+            Some(name) => name.clone(),
+            None => format!("#{}", id)
+        }
+    }
+
+    fn event(&mut self, name: String, cat: &'static str, ph: &'static str, id: usize,
+             async_id: Option<usize>, args: Option<JsonValue>) {
+        let ts = micros(self.start_time.elapsed());
+        let s = if ph == "i" { Some("t") } else { None };
+        self.events.push(Event { name, cat: Some(cat), ph, ts, dur: None, pid: PID, tid: id, id: async_id, s, args });
+    }
+}
+
+fn micros(duration: Duration) -> f64 {
+    duration.as_secs() as f64 * 1e6 + duration.subsec_nanos() as f64 * 1e-3
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::Tracer;
+
+    #[test]
+    fn trace_events() {
+        let mut tracer = Tracer::new();
+        tracer.set_processs(vec!("add".to_string(), "print".to_string()));
+        tracer.output_sent(0, "", 1, 0, &json!(42));
+        tracer.blocked(0, 1);
+        tracer.dispatched(0);
+        let start = tracer.start_time + Duration::from_millis(1);
+        tracer.ran(0, start, Duration::from_millis(2));
+        tracer.unblocked(0);
+
+        let trace: ::serde_json::Value = ::serde_json::from_str(&tracer.to_json().unwrap()).unwrap();
+        let events = trace["traceEvents"].as_array().unwrap();
+        let phases: Vec<&str> = events.iter().map(|event| event["ph"].as_str().unwrap()).collect();
+        assert_eq!(phases, vec!("M", "M", "i", "b", "i", "X", "e"));
+        assert_eq!(events[1]["args"]["name"], json!("#1 print"));
+        assert_eq!(events[2]["args"]["destination"], json!(1));
+        assert_eq!(events[3]["id"], events[6]["id"]);
+        assert_eq!(events[4]["name"], json!("dispatch"));
+        assert_eq!(events[5]["name"], json!("add"));
+        assert_eq!((events[5]["ts"].as_f64(), events[5]["dur"].as_f64()), (Some(1000.0), Some(2000.0)));
+    }
+}
@@ -1,38 +1,275 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use errors::{ImplementationError, LoadError};
 use implementation::Implementation;
 use implementation::RunAgain;
-use process::Process;
-use runlist::RunList;
+use implementation::RunResult;
 use provider::Provider;
-use wasmi::{Module};
-//use wasmi::{Module, ImportsBuilder, ModuleInstance};
 use serde_json::Value as JsonValue;
 use url::Url;
+use wasmi::{ImportsBuilder, MemoryRef, Module, ModuleInstance, ModuleRef, NopExternals, RuntimeValue};
+
+/*
+    The contract between the runtime and a function implemented in WASM is:
+
+    The module must export:
+        - "memory" - the linear memory used to pass data in and out of the function
+        - "alloc"  - a function that takes a size in bytes and returns a pointer to a buffer of that
+                     size in "memory" where the runtime can write the inputs
+        - "run"    - the entry point, that takes the pointer and length of the inputs buffer and
+                     returns a pointer to the result
+
+    The inputs are passed as the JSON serialization of the array of arrays of input values.
+
+    The result pointed to by the value returned from "run" starts with its length in bytes as
+    a little-endian u32, followed by the JSON serialization of a `WasmResult`.
+*/
+const MEMORY: &str = "memory";
+const ALLOC: &str = "alloc";
+const RUN: &str = "run";
+
+#[derive(Deserialize)]
+struct WasmResult {
+    #[serde(default)]
+    value: Option<JsonValue>,
+    #[serde(default = "default_run_again")]
+    run_again: RunAgain,
+    #[serde(default)]
+    error: Option<String>,
+}
 
+fn default_run_again() -> RunAgain {
+    true
+}
+
+// Gives each implementation loaded a different id, so the instances of it can be found
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    /*
+        The instances of modules created on this thread, by the id of the implementation and the id
+        of the process it was created for. wasmi's instances (ModuleRef) are reference counted
+        using Rc and so they are not Send, so each is kept on the thread that created it.
+    */
+    static INSTANCES: RefCell<HashMap<(usize, usize), ModuleRef>> = RefCell::new(HashMap::new());
+}
+
+/*
+    The compiled module is shared between processes and threads, as it is never changed by running
+    it. Each process that uses it gets its own instance, created the first time the process is run
+    on a thread and kept for the next times it is run there, so a function can keep state in its
+    memory between runs.
+*/
 pub struct WasmImplementation {
-//    module: ModuleInstance
+    id: usize,
+    source: String,
+    module: Module,
 }
 
 impl Implementation for WasmImplementation {
-    fn run(&self, _process: &Process, _inputs: Vec<Vec<JsonValue>>, _run_list: &mut RunList) -> RunAgain {
-        println!("Wasm implementation wrapper called");
-        true
+    // Run in an instance that is not kept, as the process running it is not known
+    fn run(&self, inputs: Vec<Vec<JsonValue>>) -> RunResult {
+        let instance = Self::instantiate(&self.module).map_err(|e| self.failed(e))?;
+        self.run_in(&instance, &inputs)
+    }
+
+    fn run_process(&self, process_id: usize, inputs: Vec<Vec<JsonValue>>) -> RunResult {
+        let instance = INSTANCES.with(|instances| -> Result<ModuleRef, String> {
+            let mut instances = instances.borrow_mut();
+            if let Some(instance) = instances.get(&(self.id, process_id)) {
+                return Ok(instance.clone());
+            }
+            let instance = Self::instantiate(&self.module)?;
+            instances.insert((self.id, process_id), instance.clone());
+            Ok(instance)
+        }).map_err(|e| self.failed(e))?;
+        self.run_in(&instance, &inputs)
     }
 }
 
 impl WasmImplementation {
-    pub fn load<'a>(provider: &Provider, source_url: &Url) -> Result<&'a Implementation, String> {
+    pub fn load(provider: &Provider, source_url: &Url) -> Result<Arc<dyn Implementation>, LoadError> {
         let (resolved_url, _) = provider.resolve(source_url)?;
         let content = provider.get(&resolved_url)?;
+        let wasm_error = |reason: String| LoadError::Wasm { url: resolved_url.to_string(), reason };
+
+        let module = Module::from_buffer(content).map_err(|e| wasm_error(e.to_string()))?;
+
+        // Check it has the exports we need before accepting it
+        let instance = Self::instantiate(&module).map_err(&wasm_error)?;
+        Self::memory(&instance).map_err(&wasm_error)?;
+
+        Ok(Arc::new(WasmImplementation {
+            id: NEXT_ID.fetch_add(1, Ordering::SeqCst),
+            source: resolved_url.to_string(),
+            module,
+        }))
+    }
+
+    fn instantiate(module: &Module) -> Result<ModuleRef, String> {
+        let instance = ModuleInstance::new(module, &ImportsBuilder::default())
+            .map_err(|e| format!("Could not instantiate wasm module ({})", e))?;
+        Ok(instance.assert_no_start())
+    }
+
+    fn memory(instance: &ModuleRef) -> Result<MemoryRef, String> {
+        instance.export_by_name(MEMORY)
+            .and_then(|export| export.as_memory().cloned())
+            .ok_or(format!("wasm module does not export '{}'", MEMORY))
+    }
+
+    fn failed(&self, reason: String) -> ImplementationError {
+        ImplementationError::Failed(format!("Failed running wasm implementation '{}': {}", self.source, reason))
+    }
+
+    fn run_in(&self, instance: &ModuleRef, inputs: &Vec<Vec<JsonValue>>) -> RunResult {
+        let result = Self::call(instance, inputs).map_err(|e| self.failed(e))?;
+        match result.error {
+            Some(error) => Err(ImplementationError::Failed(error)),
+            None => Ok((result.value, result.run_again))
+        }
+    }
+
+    fn call(instance: &ModuleRef, inputs: &Vec<Vec<JsonValue>>) -> Result<WasmResult, String> {
+        let memory = Self::memory(instance)?;
+
+        let input_bytes = serde_json::to_vec(inputs).map_err(|e| e.to_string())?;
+        let input_length = input_bytes.len() as i32;
+        let input_pointer = Self::invoke(instance, ALLOC, &[RuntimeValue::I32(input_length)])?;
+        memory.set(input_pointer, &input_bytes).map_err(|e| e.to_string())?;
 
-        let _module = Module::from_buffer(content)
+        let result_pointer = Self::invoke(instance, RUN, &[RuntimeValue::I32(input_pointer as i32),
+            RuntimeValue::I32(input_length)])?;
+        let result_length: u32 = memory.get_value(result_pointer).map_err(|e| e.to_string())?;
+        let result_bytes = memory.get(result_pointer + 4, result_length as usize)
             .map_err(|e| e.to_string())?;
 
-        Ok(&WasmImplementation {
-/*            module: ModuleInstance::new(&module,
-                                        &ImportsBuilder::default())
-                .map_err(|e| e.to_string())
-                .assert_no_start()
-                */
-        })
+        serde_json::from_slice(&result_bytes)
+            .map_err(|e| format!("Could not read result of wasm function ({})", e))
     }
-}
\ No newline at end of file
+
+    // Invoke an exported function that returns a pointer into memory
+    fn invoke(instance: &ModuleRef, name: &str, args: &[RuntimeValue]) -> Result<u32, String> {
+        match instance.invoke_export(name, args, &mut NopExternals) {
+            Ok(Some(RuntimeValue::I32(pointer))) => Ok(pointer as u32),
+            Ok(_) => Err(format!("wasm function '{}' did not return an i32 pointer", name)),
+            Err(e) => Err(format!("wasm function '{}' failed ({})", name, e))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+    use std::thread;
+
+    use url::Url;
+
+    use errors::ProviderError;
+    use provider::Provider;
+
+    use super::INSTANCES;
+    use super::WasmImplementation;
+
+    struct TestProvider {
+        test_content: Vec<u8>
+    }
+
+    impl Provider for TestProvider {
+        fn resolve(&self, url: &Url) -> Result<(Url, Option<String>), ProviderError> {
+            Ok((url.clone(), None))
+        }
+
+        fn get(&self, _url: &Url) -> Result<Vec<u8>, ProviderError> {
+            Ok(self.test_content.clone())
+        }
+    }
+
+    /*
+        A hand assembled module equivalent to:
+        (module
+          (memory (export "memory") 1)
+          (func (export "alloc") (param i32) (result i32) i32.const 0)
+          (func (export "run") (param i32 i32) (result i32) i32.const 1024)
+          (data (i32.const 1024) "<length><json>"))
+        where the result json is '{"value":42,"run_again":false}'
+    */
+    fn test_module() -> Vec<u8> {
+        let json = b"{\"value\":42,\"run_again\":false}";
+        let mut data = vec!(json.len() as u8, 0, 0, 0);
+        data.extend_from_slice(json);
+
+        let mut module = vec!(0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00);
+        // type section: (i32) -> i32 and (i32, i32) -> i32
+        module.extend_from_slice(&[0x01, 0x0c, 0x02,
+            0x60, 0x01, 0x7f, 0x01, 0x7f,
+            0x60, 0x02, 0x7f, 0x7f, 0x01, 0x7f]);
+        // function section
+        module.extend_from_slice(&[0x03, 0x03, 0x02, 0x00, 0x01]);
+        // memory section: one memory with a minimum of one page
+        module.extend_from_slice(&[0x05, 0x03, 0x01, 0x00, 0x01]);
+        // export section
+        module.extend_from_slice(&[0x07, 0x18, 0x03,
+            0x06, b'm', b'e', b'm', b'o', b'r', b'y', 0x02, 0x00,
+            0x05, b'a', b'l', b'l', b'o', b'c', 0x00, 0x00,
+            0x03, b'r', b'u', b'n', 0x00, 0x01]);
+        // code section
+        module.extend_from_slice(&[0x0a, 0x0c, 0x02,
+            0x04, 0x00, 0x41, 0x00, 0x0b,
+            0x05, 0x00, 0x41, 0x80, 0x08, 0x0b]);
+        // data section
+        module.extend_from_slice(&[0x0b, (data.len() + 7) as u8, 0x01,
+            0x00, 0x41, 0x80, 0x08, 0x0b, data.len() as u8]);
+        module.extend_from_slice(&data);
+        module
+    }
+
+    #[test]
+    fn load_invalid_module_fails() {
+        let provider = TestProvider { test_content: b"not wasm".to_vec() };
+        let url = Url::parse("file:///test/fake.wasm").unwrap();
+        assert!(WasmImplementation::load(&provider, &url).is_err());
+    }
+
+    #[test]
+    fn run_returns_output() {
+        let provider = TestProvider { test_content: test_module() };
+        let url = Url::parse("file:///test/fake.wasm").unwrap();
+        let implementation = WasmImplementation::load(&provider, &url).unwrap();
+
+        let (value, run_again) = implementation.run(vec!(vec!(json!(1)))).unwrap();
+        assert_eq!(value, Some(json!(42)));
+        assert_eq!(run_again, false);
+    }
+
+    #[test]
+    fn instance_kept_for_each_process() {
+        let provider = TestProvider { test_content: test_module() };
+        let url = Url::parse("file:///test/fake.wasm").unwrap();
+        let implementation = WasmImplementation::load(&provider, &url).unwrap();
+
+        for &process_id in &[0, 1, 0, 1] {
+            let (value, _) = implementation.run_process(process_id, vec!(vec!(json!(1)))).unwrap();
+            assert_eq!(value, Some(json!(42)));
+        }
+        assert_eq!(INSTANCES.with(|instances| instances.borrow().len()), 2);
+    }
+
+    #[test]
+    fn runs_in_parallel_on_other_threads() {
+        let provider = TestProvider { test_content: test_module() };
+        let url = Url::parse("file:///test/fake.wasm").unwrap();
+        let implementation = WasmImplementation::load(&provider, &url).unwrap();
+
+        let runs: Vec<_> = (0..4).map(|input| {
+            let implementation = Arc::clone(&implementation);
+            thread::spawn(move || implementation.run_process(0, vec!(vec!(json!(input)))).unwrap())
+        }).collect();
+        for run in runs {
+            assert_eq!(run.join().unwrap(), (Some(json!(42)), false));
+        }
+    }
+}
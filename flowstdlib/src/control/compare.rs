@@ -1,7 +1,6 @@
+use flowrlib::errors::ImplementationError;
 use flowrlib::implementation::Implementation;
-use flowrlib::implementation::RunAgain;
-use flowrlib::process::Process;
-use flowrlib::runlist::RunList;
+use flowrlib::implementation::RunResult;
 use serde_json::Value as JsonValue;
 
 pub struct Compare;
@@ -10,9 +9,14 @@ pub struct Compare;
     A compare operator that takes two numbers (for now) and outputs the comparisons between them
 */
 impl Implementation for Compare {
-    fn run(&self, process: &Process, mut inputs: Vec<Vec<JsonValue>>, run_list: &mut RunList) -> RunAgain {
-        let left = inputs[0].remove(0).as_i64().unwrap();
-        let right = inputs[1].remove(0).as_i64().unwrap();
+    fn run(&self, mut inputs: Vec<Vec<JsonValue>>) -> RunResult {
+        let left = inputs[0].remove(0);
+        let right = inputs[1].remove(0);
+        let (left, right) = match (left.as_i64(), right.as_i64()) {
+            (Some(left), Some(right)) => (left, right),
+            _ => return Err(ImplementationError::InvalidInput(
+                format!("'{}' and '{}' cannot both be compared as integers", left, right)))
+        };
 
         let output = json!({
                     "equal" : left == right,
@@ -21,7 +25,6 @@ impl Implementation for Compare {
                     "lte" : left <= right,
                     "gte" : left >= right,
                 });
-        run_list.send_output(process, output);
-        true
+        Ok((Some(output), true))
     }
 }
\ No newline at end of file
@@ -1,7 +1,6 @@
+use flowrlib::errors::ImplementationError;
 use flowrlib::implementation::Implementation;
-use flowrlib::implementation::RunAgain;
-use flowrlib::process::Process;
-use flowrlib::runlist::RunList;
+use flowrlib::implementation::RunResult;
 use serde_json::Value as JsonValue;
 
 pub struct Tap;
@@ -11,12 +10,15 @@ pub struct Tap;
     otherwise it does not produce any output
 */
 impl Implementation for Tap {
-    fn run(&self, process: &Process, mut inputs: Vec<Vec<JsonValue>>, run_list: &mut RunList) -> RunAgain {
+    fn run(&self, mut inputs: Vec<Vec<JsonValue>>) -> RunResult {
         let data = inputs[0].remove(0);
-        let control = inputs[1].remove(0).as_bool().unwrap();
+        let control = inputs[1].remove(0);
+        let control = control.as_bool()
+            .ok_or(ImplementationError::InvalidInput(format!("control '{}' is not a Boolean", control)))?;
         if control {
-            run_list.send_output(process, data);
+            Ok((Some(data), true))
+        } else {
+            Ok((None, true))
         }
-        true
     }
 }
\ No newline at end of file
@@ -1,26 +1,49 @@
 use flowrlib::implementation::Implementation;
-use flowrlib::implementation::RunAgain;
-use flowrlib::process::Process;
-use flowrlib::runlist::RunList;
+use flowrlib::implementation::RunResult;
 use serde_json;
 use serde_json::Value as JsonValue;
 
 pub struct ToNumber;
 
+/*
+    A String that is not a number (or an input that is not a String) is skipped, so it produces no
+    output and is not an error
+*/
 impl Implementation for ToNumber {
-    fn run(&self, process: &Process, mut inputs: Vec<Vec<JsonValue>>, run_list: &mut RunList) -> RunAgain {
+    fn run(&self, mut inputs: Vec<Vec<JsonValue>>) -> RunResult {
         let input = inputs.remove(0).remove(0);
 
         match input {
             JsonValue::String(string) => {
                 if let Ok(number) = string.parse::<i64>() {
                     let number = JsonValue::Number(serde_json::Number::from(number));
-                    run_list.send_output(process, number);
+                    return Ok((Some(number), true));
                 }
             },
             _ => {}
         };
 
-        true
+        Ok((None, true))
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test {
+    use flowrlib::implementation::Implementation;
+
+    use super::ToNumber;
+
+    #[test]
+    fn parse_number() {
+        let (output, run_again) = ToNumber.run(vec!(vec!(json!("42")))).unwrap();
+        assert_eq!(output, Some(json!(42)));
+        assert!(run_again);
+    }
+
+    #[test]
+    fn not_a_number_skipped() {
+        let (output, run_again) = ToNumber.run(vec!(vec!(json!("forty two")))).unwrap();
+        assert_eq!(output, None);
+        assert!(run_again);
+    }
+}
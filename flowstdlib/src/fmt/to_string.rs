@@ -1,32 +1,39 @@
 use flowrlib::implementation::Implementation;
-use flowrlib::implementation::RunAgain;
-use flowrlib::process::Process;
-use flowrlib::runlist::RunList;
+use flowrlib::implementation::RunResult;
 use serde_json::Value as JsonValue;
 
 pub struct ToString;
 
 impl Implementation for ToString {
-    fn run(&self, process: &Process, mut inputs: Vec<Vec<JsonValue>>, run_list: &mut RunList) -> RunAgain {
+    fn run(&self, mut inputs: Vec<Vec<JsonValue>>) -> RunResult {
         let input = inputs.remove(0).remove(0);
-        match input {
-            JsonValue::String(_) => {
-                run_list.send_output(process, input);
-            },
-            JsonValue::Bool(boolean) => {
-                run_list.send_output(process, JsonValue::String(boolean.to_string()));
-            },
-            JsonValue::Number(number) => {
-                run_list.send_output(process, JsonValue::String(number.to_string()));
-            },
-            JsonValue::Array(array) => {
-                for entry in array {
-                    run_list.send_output(process,entry);
-                }
-            },
-            _ => {}
-        };
+        Ok((to_string(input), true))
+    }
+}
+
+fn to_string(input: JsonValue) -> Option<JsonValue> {
+    match input {
+        JsonValue::String(_) => Some(input),
+        JsonValue::Bool(boolean) => Some(JsonValue::String(boolean.to_string())),
+        JsonValue::Number(number) => Some(JsonValue::String(number.to_string())),
+        // An implementation sends one output per run, so each entry is converted and the array
+        // of them is sent, leaving out those that can't be converted
+        JsonValue::Array(array) => Some(JsonValue::Array(array.into_iter().filter_map(to_string).collect())),
+        _ => None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::to_string;
+
+    #[test]
+    fn array_entries_converted() {
+        assert_eq!(to_string(json!([1, true, "three", null, [4]])), Some(json!(["1", "true", "three", ["4"]])));
+    }
 
-        true
+    #[test]
+    fn null_not_converted() {
+        assert_eq!(to_string(json!(null)), None);
     }
 }
\ No newline at end of file
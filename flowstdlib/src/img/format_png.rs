@@ -1,7 +1,6 @@
+use flowrlib::errors::ImplementationError;
 use flowrlib::implementation::Implementation;
-use flowrlib::implementation::RunAgain;
-use flowrlib::process::Process;
-use flowrlib::runlist::RunList;
+use flowrlib::implementation::RunResult;
 use image::ColorType;
 use image::png::PNGEncoder;
 use serde_json::Value as JsonValue;
@@ -10,26 +9,31 @@ use std::io::Write;
 pub struct FormatPNG;
 
 impl Implementation for FormatPNG {
-    fn run(&self, _process: &Process, mut inputs: Vec<Vec<JsonValue>>, _run_list: &mut RunList) -> RunAgain {
+    fn run(&self, mut inputs: Vec<Vec<JsonValue>>) -> RunResult {
         let bytes = inputs.remove(0).remove(0);
 
         // bounds: (usize, usize),
         let bounds = inputs.remove(0).remove(0);
-        let width = bounds["width"].as_u64().unwrap() as u32;
-        let height = bounds["height"].as_u64().unwrap() as u32;
+        let (width, height) = match (bounds["width"].as_u64(), bounds["height"].as_u64()) {
+            (Some(width), Some(height)) => (width as u32, height as u32),
+            _ => return Err(ImplementationError::InvalidInput(
+                format!("bounds '{}' do not have a numeric 'width' and 'height'", bounds)))
+        };
+        let bytes = bytes.as_str()
+            .ok_or(ImplementationError::InvalidInput(format!("bytes '{}' is not a String", bytes)))?;
 
         debug!("Writing image of width '{}' and height '{}'", width, height);
 
         let mut png_buffer = Vec::new();
         let encoder = PNGEncoder::new(png_buffer.by_ref());
-        encoder.encode(bytes.as_str().unwrap().as_bytes(), width, height, ColorType::Gray(8))
-            .expect("error encoding pixels as PNG");
+        encoder.encode(bytes.as_bytes(), width, height, ColorType::Gray(8))
+            .map_err(|e| ImplementationError::Failed(format!("Error encoding pixels as PNG: {}", e)))?;
 
 
         // TODO
 //        let string = String::from_utf8_lossy(&png_buffer).to_string();
-//        run_list.send_output(runnable, JsonValue::String(string));
+//        return Ok((Some(JsonValue::String(string)), true));
 
-        true
+        Ok((None, true))
     }
 }
@@ -1,7 +1,6 @@
+use flowrlib::errors::ImplementationError;
 use flowrlib::implementation::Implementation;
-use flowrlib::implementation::RunAgain;
-use flowrlib::process::Process;
-use flowrlib::runlist::RunList;
+use flowrlib::implementation::RunResult;
 use serde_json;
 use serde_json::Value as JsonValue;
 use serde_json::Value::Number;
@@ -9,35 +8,81 @@ use serde_json::Value::String;
 
 pub struct Add;
 
+/*
+    The error returned when the sum of 'a' and 'b' is too big for the type they are added as
+*/
+fn overflow<T: ::std::fmt::Display>(a: T, b: T) -> ImplementationError {
+    ImplementationError::Failed(format!("'{}' + '{}' is too big to be represented", a, b))
+}
+
 // TODO implementation of `std::ops::Add` might be missing for `&serde_json::Number`
 
 impl Implementation for Add {
-    fn run(&self, process: &Process, inputs: Vec<Vec<JsonValue>>, run_list: &mut RunList) -> RunAgain {
+    fn run(&self, inputs: Vec<Vec<JsonValue>>) -> RunResult {
         let input_a = inputs.get(0).unwrap();
         let input_b = inputs.get(1).unwrap();
+        let mut output = None;
         match (&input_a[0], &input_b[0]) {
             (&Number(ref a), &Number(ref b)) => {
                 // TODO mixed signed and unsigned integers
                 if a.is_i64() && b.is_i64() {
-                    let n = JsonValue::Number(serde_json::Number::from(a.as_i64().unwrap() + b.as_i64().unwrap()));
-                    run_list.send_output(process, n);
+                    match a.as_i64().unwrap().checked_add(b.as_i64().unwrap()) {
+                        Some(sum) => output = Some(JsonValue::Number(serde_json::Number::from(sum))),
+                        None => return Err(overflow(a, b))
+                    }
                 } else if a.is_u64() && b.is_u64() {
-                    let n = JsonValue::Number(serde_json::Number::from(a.as_u64().unwrap() + b.as_u64().unwrap()));
-                    run_list.send_output(process, n);
+                    match a.as_u64().unwrap().checked_add(b.as_u64().unwrap()) {
+                        Some(sum) => output = Some(JsonValue::Number(serde_json::Number::from(sum))),
+                        None => return Err(overflow(a, b))
+                    }
                 } else if a.is_f64() && b.is_f64() {
-                    let n = JsonValue::Number(serde_json::Number::from_f64(a.as_f64().unwrap() + b.as_f64().unwrap()).unwrap());
-                    run_list.send_output(process, n);
+                    // from_f64() returns None for a sum that is infinite (or NaN)
+                    match serde_json::Number::from_f64(a.as_f64().unwrap() + b.as_f64().unwrap()) {
+                        Some(sum) => output = Some(JsonValue::Number(sum)),
+                        None => return Err(overflow(a, b))
+                    }
                 }
             }
             (&String(ref a), &String(ref b)) => {
-                let i1 = a.parse::<i32>().unwrap();
-                let i2 = b.parse::<i32>().unwrap();
-                let o1 = i1 + i2;
-                run_list.send_output(process, JsonValue::String(o1.to_string()));
+                let (i1, i2) = match (a.parse::<i32>(), b.parse::<i32>()) {
+                    (Ok(i1), Ok(i2)) => (i1, i2),
+                    _ => return Err(ImplementationError::InvalidInput(
+                        format!("'{}' and '{}' cannot both be added as integers", a, b)))
+                };
+                match i1.checked_add(i2) {
+                    Some(o1) => output = Some(JsonValue::String(o1.to_string())),
+                    None => return Err(overflow(a, b))
+                }
             }
             (_, _) => {}
         }
 
-        true
+        Ok((output, true))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use flowrlib::errors::ImplementationError;
+    use flowrlib::implementation::Implementation;
+
+    use super::Add;
+
+    #[test]
+    fn add_numbers() {
+        let (output, _) = Add.run(vec!(vec!(json!(1)), vec!(json!(2)))).unwrap();
+        assert_eq!(output, Some(json!(3)));
+    }
+
+    #[test]
+    fn overflow_fails() {
+        for (a, b) in vec!((json!(::std::i64::MAX), json!(1)), (json!(::std::u64::MAX), json!(1)),
+                           (json!(::std::f64::MAX), json!(::std::f64::MAX)),
+                           (json!(::std::i32::MAX.to_string()), json!("1"))) {
+            match Add.run(vec!(vec!(a), vec!(b))) {
+                Err(ImplementationError::Failed(_)) => {}
+                other => panic!("Expected overflow to fail, got {:?}", other.map(|(output, _)| output))
+            }
+        }
     }
-}
\ No newline at end of file
+}
@@ -1,29 +1,35 @@
+use flowrlib::errors::ImplementationError;
 use flowrlib::implementation::Implementation;
-use flowrlib::implementation::RunAgain;
-use flowrlib::process::Process;
-use flowrlib::runlist::RunList;
+use flowrlib::implementation::RunResult;
 use serde_json::Value as JsonValue;
 
 pub struct Divide;
 
 impl Implementation for Divide {
-    fn run(&self, process: &Process, inputs: Vec<Vec<JsonValue>>, run_list: &mut RunList) -> RunAgain {
-        let dividend = inputs.get(0).unwrap()[0].as_f64().unwrap();
-        let divisor = inputs.get(1).unwrap()[0].as_f64().unwrap();
-
-        let output = json!({"dividend:": dividend, "divisor": divisor, "result": dividend/divisor, "remainder": dividend % divisor});
-        run_list.send_output(process, output);
-
-        true
+    fn run(&self, inputs: Vec<Vec<JsonValue>>) -> RunResult {
+        let (dividend, divisor) = match (inputs[0][0].as_f64(), inputs[1][0].as_f64()) {
+            (Some(dividend), Some(divisor)) => (dividend, divisor),
+            _ => return Err(ImplementationError::InvalidInput(
+                format!("'{}' and '{}' cannot both be divided as numbers", inputs[0][0], inputs[1][0])))
+        };
+
+        if divisor == 0.0 {
+            return Err(ImplementationError::InvalidInput(format!("cannot divide '{}' by zero", dividend)));
+        }
+
+        let output = json!({"dividend": dividend, "divisor": divisor, "result": dividend/divisor, "remainder": dividend % divisor});
+        Ok((Some(output), true))
     }
 }
 
 #[cfg(test)]
 mod test {
-    use flowrlib::process::Process;
-    use flowrlib::runlist::RunList;
+    use flowrlib::errors::ImplementationError;
+    use flowrlib::implementation::Implementation;
     use serde_json::Value as JsonValue;
 
+    use super::Divide;
+
     #[test]
     fn test_divide() {
         // Create input vector
@@ -31,10 +37,21 @@ mod test {
         let divisor = json!(3);
         let inputs: Vec<Vec<JsonValue>> = vec!(vec!(dividend), vec!(divisor));
 
-        let mut run_list = RunList::new();
-        let d = &Process::new("d",true, "".to_string(), vec!(1, 1, 1), 0, None, vec!()) as &Process;
-        let implementation = d.get_implementation();
+        let (output, run_again) = Divide.run(inputs).unwrap();
+
+        let output = output.unwrap();
+        assert_eq!(output["result"], json!(33.0));
+        assert_eq!(output["remainder"], json!(0.0));
+        assert!(run_again);
+    }
+
+    #[test]
+    fn divide_by_zero() {
+        let inputs: Vec<Vec<JsonValue>> = vec!(vec!(json!(99)), vec!(json!(0)));
 
-        implementation.run(d, inputs, &mut run_list);
+        match Divide.run(inputs) {
+            Err(ImplementationError::InvalidInput(_)) => {}
+            _ => panic!("Expected an InvalidInput error")
+        }
     }
-}
\ No newline at end of file
+}
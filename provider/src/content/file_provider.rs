@@ -4,63 +4,76 @@ use std::io;
 use std::io::ErrorKind;
 use std::path::PathBuf;
 
+use flowrlib::errors::ProviderError;
 use flowrlib::provider::Provider;
 use glob::glob;
 use url::Url;
 
 pub struct FileProvider;
 
+const DEFAULT_FILE_NAME: &str = "context";
+const DEFAULT_FILE_EXTENSIONS: &[&str] = &["toml", "yaml", "yml", "json"];
+
 impl Provider for FileProvider {
-    fn resolve(&self, url: &Url) -> Result<(Url, Option<String>), String> {
-        let mut path = url.to_file_path().unwrap();
+    fn resolve(&self, url: &Url) -> Result<(Url, Option<String>), ProviderError> {
+        let mut path = Self::file_path(url)?;
         match metadata(&path) {
             Ok(md) => {
                 if md.is_dir() {
                     info!("'{}' is a directory, so attempting to find context file in it",
                           path.display());
-                    let file = FileProvider::find_default_file(&mut path).
-                        map_err(|e| e.to_string())?;
+                    let file = FileProvider::find_default_file(&mut path)
+                        .map_err(|e| Self::io_error(url, e))?;
                     let resolved_url = Url::from_file_path(&file)
-                        .map_err(|_| format!("Could not create url from file path '{}'",
-                                              file.to_str().unwrap()))?;
+                        .map_err(|_| ProviderError::InvalidUrl {
+                            url: url.to_string(),
+                            reason: format!("Could not create url from file path '{}'", file.display()),
+                        })?;
                     Ok((resolved_url, None))
                 } else {
                     Ok((url.clone(), None))
                 }
             }
-            Err(e) => {
-                Err(format!("Error getting file metadata for path: '{}', {}", path.display(), e))
-            }
+            Err(e) => Err(Self::io_error(url, e))
         }
     }
 
-    fn get(&self, url: &Url) -> Result<String, String> {
-        let file_path = url.to_file_path().unwrap();
-        fs::read_to_string(file_path).map_err(
-            |e| format!("Could not load content from '{}' ({}", url, e))
+    fn get(&self, url: &Url) -> Result<Vec<u8>, ProviderError> {
+        let file_path = Self::file_path(url)?;
+        fs::read(file_path).map_err(|e| Self::io_error(url, e))
     }
 }
 
 impl FileProvider {
+    fn file_path(url: &Url) -> Result<PathBuf, ProviderError> {
+        url.to_file_path().map_err(|_| ProviderError::InvalidUrl {
+            url: url.to_string(),
+            reason: "It is not a valid file path".to_string(),
+        })
+    }
+
+    // A file that does not exist is reported as such, so it can be told apart from other errors
+    fn io_error(url: &Url, error: io::Error) -> ProviderError {
+        match error.kind() {
+            ErrorKind::NotFound => ProviderError::NotFound { url: url.to_string() },
+            _ => ProviderError::Io { url: url.to_string(), source: error }
+        }
+    }
+
     /*
-    Passed a path to a directory, it searches for a file in the directory matching the pattern "context.*"
-    If found, it opens the file and returns its contents as a String in the result
+    Passed a path to a directory, it searches for a context file in the directory with one of the
+    extensions of the definition formats understood (e.g. "context.toml" or "context.yaml")
+    If found, it returns the path to the file
     */
     fn find_default_file(path: &mut PathBuf) -> io::Result<PathBuf> {
-        // TODO pending more complex patterns based on implemented loaders
-        // Or iterate through the matches until a loader is found which understands that file extension
-        path.push("context.toml");
-        let pattern = path.to_str().unwrap();
-        info!("Looking for files matching: '{}'", pattern);
-
-        // Try to glob for the default file using a pattern
-        for entry in glob(pattern).expect("Failed to read glob pattern") {
-            // return first file found that matches the pattern, or error if none match
-            match entry {
-                Ok(context_file) => return Ok(context_file),
-                Err(_) => return Err(io::Error::new(ErrorKind::NotFound,
-                                             format!("No context file found matching '{}'",
-                                                     path.display())))
+        for extension in DEFAULT_FILE_EXTENSIONS {
+            let pattern = path.join(format!("{}.{}", DEFAULT_FILE_NAME, extension));
+            let pattern = pattern.to_str().unwrap();
+            info!("Looking for files matching: '{}'", pattern);
+
+            // return the first file found that matches the pattern
+            if let Some(Ok(context_file)) = glob(pattern).expect("Failed to read glob pattern").next() {
+                return Ok(context_file);
             }
         }
 
@@ -74,6 +87,7 @@ impl FileProvider {
 mod test {
     use std::path::PathBuf;
 
+    use flowrlib::errors::ProviderError;
     use flowrlib::provider::Provider;
     use url::Url;
 
@@ -92,10 +106,26 @@ mod test {
         }
     }
 
+    #[test]
+    fn get_default_yaml_sample() {
+        let mut path = PathBuf::from("../samples/hello-world-yaml");
+        let path = FileProvider::find_default_file(&mut path).unwrap();
+        assert_eq!(path.file_name().unwrap(), "context.yaml");
+    }
+
     #[test]
     #[should_panic]
     fn get_contents_file_not_found() {
         let provider: &Provider = &FileProvider;
         provider.get(&Url::parse("file:///no-such-file").unwrap()).unwrap();
     }
+
+    #[test]
+    fn file_not_found_error() {
+        let provider: &Provider = &FileProvider;
+        match provider.get(&Url::parse("file:///no-such-file").unwrap()) {
+            Err(ProviderError::NotFound { .. }) => {}
+            _ => assert!(false, "Expected a NotFound error")
+        }
+    }
 }
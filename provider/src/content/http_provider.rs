@@ -1,4 +1,5 @@
 use curl::easy::{Easy2, Handler, WriteError};
+use flowrlib::errors::ProviderError;
 use flowrlib::provider::Provider;
 use url::Url;
 
@@ -14,24 +15,27 @@ impl Handler for Collector {
 }
 
 impl Provider for HttpProvider {
-    fn resolve(&self, url: &Url) -> Result<(Url, Option<String>), String> {
+    fn resolve(&self, url: &Url) -> Result<(Url, Option<String>), ProviderError> {
         if url.path().ends_with('/') {
             info!("'{}' is a directory, so attempting to find context file in it", url);
-            Ok((HttpProvider::find_default_file(url).unwrap(), None))
+            Ok((HttpProvider::find_default_file(url)?, None))
         } else {
             Ok((url.clone(), None))
         }
     }
 
-    fn get(&self, url: &Url) -> Result<String, String> {
+    fn get(&self, url: &Url) -> Result<Vec<u8>, ProviderError> {
+        let fetch_error = |reason: String| ProviderError::Fetch { url: url.to_string(), reason };
         let mut easy = Easy2::new(Collector(Vec::new()));
-        easy.get(true).unwrap();
-        easy.url(url.as_str()).unwrap();
-        easy.perform().unwrap();
+        easy.get(true).map_err(|e| fetch_error(e.to_string()))?;
+        easy.url(url.as_str()).map_err(|e| fetch_error(e.to_string()))?;
+        easy.perform().map_err(|e| fetch_error(e.to_string()))?;
 
-        assert_eq!(easy.response_code().unwrap(), 200);
-        let contents = easy.get_ref();
-        Ok(String::from_utf8_lossy(&contents.0).to_string())
+        match easy.response_code().map_err(|e| fetch_error(e.to_string()))? {
+            200 => Ok(easy.get_ref().0.clone()),
+            404 => Err(ProviderError::NotFound { url: url.to_string() }),
+            code => Err(fetch_error(format!("Response code {}", code)))
+        }
     }
 }
 
@@ -40,8 +44,11 @@ impl HttpProvider {
         Passed a path to a directory, it searches for the first file it can find fitting the pattern
         "context.*", for known file extensions
     */
-    fn find_default_file(_url :&Url) -> Result<Url, String> {
-        Err("Not implemented yet".to_string())
+    fn find_default_file(url: &Url) -> Result<Url, ProviderError> {
+        Err(ProviderError::Fetch {
+            url: url.to_string(),
+            reason: "Finding a default file is not implemented yet".to_string(),
+        })
     }
 }
 
@@ -1,3 +1,4 @@
+use flowrlib::errors::ProviderError;
 use flowrlib::provider::Provider;
 use simpath::Simpath;
 use url::Url;
@@ -39,11 +40,14 @@ impl Provider for LibProvider {
         Also, construct a string that is a reference to that module in the library, such as:
             "flowstdlib/stdio/stdout" and return that also.
     */
-    fn resolve(&self, url: &Url) -> Result<(Url, Option<String>), String> {
-        let lib_name = url.host_str().unwrap();
+    fn resolve(&self, url: &Url) -> Result<(Url, Option<String>), ProviderError> {
+        let lib_name = url.host_str().ok_or(ProviderError::InvalidUrl {
+            url: url.to_string(),
+            reason: "It does not have a library name".to_string(),
+        })?;
         let flow_lib_search_path = Simpath::new("FLOW_LIB_PATH");
         let mut lib_path = flow_lib_search_path.find(lib_name)
-            .map_err(|e| e.to_string())?;
+            .map_err(|_| ProviderError::NotFound { url: url.to_string() })?;
         lib_path.push("src");
         lib_path.push(&url.path()[1..]); // Strip off leading '/' to concatenate to path
 
@@ -54,16 +58,20 @@ impl Provider for LibProvider {
 
         if lib_path.exists() {
             let resolved_url = Url::from_file_path(lib_path)
-                .map_err(|_e| "Could not convert file path to Url".to_string())?;
+                .map_err(|_e| ProviderError::InvalidUrl {
+                    url: url.to_string(),
+                    reason: "Could not convert file path to Url".to_string(),
+                })?;
             Ok((resolved_url, Some(lib_ref.to_string())))
         } else {
-            Err(format!("Could not locate url '{}' in libraries in 'FLOW_LIB_PATH'", url))
+            // Could not locate url in libraries in 'FLOW_LIB_PATH'
+            Err(ProviderError::NotFound { url: url.to_string() })
         }
     }
 
     // All Urls that start with "lib://" should resource to a different Url with "http(s)" or "file"
     // and so we should never get a request to get content from a Url with such a scheme
-    fn get(&self, _url: &Url) -> Result<String, String> {
+    fn get(&self, _url: &Url) -> Result<Vec<u8>, ProviderError> {
         unimplemented!();
     }
 }
@@ -1,3 +1,4 @@
+use flowrlib::errors::ProviderError;
 use flowrlib::provider::Provider;
 use url::Url;
 
@@ -13,13 +14,16 @@ pub struct MetaProvider {}
 
 impl MetaProvider {
     // Determine which specific provider should be used based on the scheme of the Url of the content
-    fn get_provider(url: &Url) -> Result<&'static Provider, String> {
+    fn get_provider(url: &Url) -> Result<&'static Provider, ProviderError> {
         match url.scheme() {
             "" => Ok(FILE_PROVIDER),
             "file" => Ok(FILE_PROVIDER),
             "lib" => Ok(LIB_PROVIDER),
             "http" | "https" => Ok(HTTP_PROVIDER),
-            _ => Err(format!("Cannot determine which provider to use for url: '{}'", url))
+            _ => Err(ProviderError::InvalidUrl {
+                url: url.to_string(),
+                reason: "Cannot determine which provider to use for the scheme".to_string(),
+            })
         }
     }
 }
@@ -32,14 +36,14 @@ impl Provider for MetaProvider {
     ///     -  a specific file or flow (that may or may not exist)
     ///     -  a directory - if exists then look for a provider specific default file
     ///     -  a file in a library, transform the reference into a Url where the content can be found
-    fn resolve(&self, url: &Url) -> Result<(Url, Option<String>), String> {
+    fn resolve(&self, url: &Url) -> Result<(Url, Option<String>), ProviderError> {
         let provider = Self::get_provider(url)?;
         provider.resolve(url)
     }
 
     /// Takes a Url with a scheme of "http", "https" or "file". Read and return the contents of the
     /// resource at that Url.
-    fn get(&self, url: &Url) -> Result<String, String> {
+    fn get(&self, url: &Url) -> Result<Vec<u8>, ProviderError> {
         let provider = Self::get_provider(&url)?;
         let content = provider.get(&url)?;
         Ok(content)
@@ -1,14 +1,12 @@
 use flowrlib::implementation::Implementation;
-use flowrlib::implementation::RunAgain;
-use flowrlib::process::Process;
-use flowrlib::runlist::RunList;
+use flowrlib::implementation::RunResult;
 use serde_json::Value as JsonValue;
 use serde_json::Value::String as JsonString;
 
 pub struct Reverse;
 
 impl Implementation for Reverse {
-    fn run(&self, process: &Process, mut inputs: Vec<Vec<JsonValue>>, run_list: &mut RunList) -> RunAgain {
+    fn run(&self, mut inputs: Vec<Vec<JsonValue>>) -> RunResult {
         let input = inputs.remove(0).remove(0);
         match input {
             JsonString(ref s) => {
@@ -16,11 +14,9 @@ impl Implementation for Reverse {
                     "reversed" : s.chars().rev().collect::<String>(),
                     "original": s
                 });
-                run_list.send_output(process, output);
+                Ok((Some(output), true))
             }
-            _ => {}
+            _ => Ok((None, true))
         }
-
-        true
     }
 }
\ No newline at end of file
@@ -0,0 +1,117 @@
+;; Source of reverse.wasm, assemble with: wat2wasm reverse.wat -o reverse.wasm
+;;
+;; Implements the 'Reverse' function using the WASM function ABI (see "WASM Implementations" in the guide).
+;; The input is the JSON of the inputs (e.g. [["Hello"]]), and the result is a JSON object with the
+;; value to output: {"value":{"reversed":"olleH","original":"Hello"}}
+;; The string is reversed by character, keeping multi-byte UTF-8 characters and escape sequences intact.
+(module
+  (memory (export "memory") 1)
+
+  (global $input i32 (i32.const 1024))
+
+  (data (i32.const 0) "{\22value\22:{\22reversed\22:\22")
+  (data (i32.const 64) "\22,\22original\22:\22")
+  (data (i32.const 128) "\22}}")
+  (data (i32.const 192) "{}")
+
+  ;; The inputs are always written at the same place, the result is written after them
+  ;; Make sure there is enough memory for both
+  (func (export "alloc") (param $size i32) (result i32)
+    (local $needed i32)
+    (local.set $needed
+      (i32.add (global.get $input) (i32.add (i32.mul (local.get $size) (i32.const 3)) (i32.const 1024))))
+    (block $done
+      (loop $grow
+        (br_if $done (i32.ge_u (i32.mul (memory.size) (i32.const 65536)) (local.get $needed)))
+        (drop (memory.grow (i32.const 1)))
+        (br $grow)))
+    (global.get $input))
+
+  ;; copy 'length' bytes from 'from' to 'to' and return the address after the last byte written
+  (func $copy (param $to i32) (param $from i32) (param $length i32) (result i32)
+    (local $i i32)
+    (block $done
+      (loop $next
+        (br_if $done (i32.ge_u (local.get $i) (local.get $length)))
+        (i32.store8 (i32.add (local.get $to) (local.get $i))
+                    (i32.load8_u (i32.add (local.get $from) (local.get $i))))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $next)))
+    (i32.add (local.get $to) (local.get $length)))
+
+  ;; the number of bytes of the character or escape sequence starting at 'at'
+  (func $unit_length (param $at i32) (result i32)
+    (local $byte i32)
+    (local.set $byte (i32.load8_u (local.get $at)))
+    (if (i32.eq (local.get $byte) (i32.const 92)) ;; '\'
+      (then
+        (if (i32.eq (i32.load8_u (i32.add (local.get $at) (i32.const 1))) (i32.const 117)) ;; 'u'
+          (then (return (i32.const 6))))
+        (return (i32.const 2))))
+    (if (i32.ge_u (local.get $byte) (i32.const 0xf0)) (then (return (i32.const 4))))
+    (if (i32.ge_u (local.get $byte) (i32.const 0xe0)) (then (return (i32.const 3))))
+    (if (i32.ge_u (local.get $byte) (i32.const 0xc0)) (then (return (i32.const 2))))
+    (i32.const 1))
+
+  (func (export "run") (param $ptr i32) (param $len i32) (result i32)
+    (local $end i32)
+    (local $start i32)
+    (local $string_end i32)
+    (local $result i32)
+    (local $out i32)
+    (local $i i32)
+    (local $unit i32)
+    (local.set $end (i32.add (local.get $ptr) (local.get $len)))
+    ;; result goes after the input, aligned to 4 bytes
+    (local.set $result (i32.and (i32.add (local.get $end) (i32.const 7)) (i32.const -4)))
+
+    ;; find the start of the first string in the input
+    (local.set $i (local.get $ptr))
+    (block $found
+      (loop $search
+        (if (i32.ge_u (local.get $i) (local.get $end))
+          (then
+            ;; no string in the input, so no output
+            (i32.store (local.get $result) (i32.const 2))
+            (drop (call $copy (i32.add (local.get $result) (i32.const 4)) (i32.const 192) (i32.const 2)))
+            (return (local.get $result))))
+        (br_if $found (i32.eq (i32.load8_u (local.get $i)) (i32.const 34))) ;; '"'
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $search)))
+    (local.set $start (i32.add (local.get $i) (i32.const 1)))
+
+    ;; find the end of the string, skipping over escape sequences
+    (local.set $i (local.get $start))
+    (block $found_end
+      (loop $search_end
+        (br_if $found_end (i32.ge_u (local.get $i) (local.get $end)))
+        (br_if $found_end (i32.eq (i32.load8_u (local.get $i)) (i32.const 34)))
+        (local.set $i (i32.add (local.get $i) (call $unit_length (local.get $i))))
+        (br $search_end)))
+    (local.set $string_end (local.get $i))
+
+    (local.set $out (call $copy (i32.add (local.get $result) (i32.const 4)) (i32.const 0) (i32.const 22)))
+
+    ;; write each character of the string at the mirrored position in the output
+    (local.set $i (local.get $start))
+    (block $reversed
+      (loop $reverse
+        (br_if $reversed (i32.ge_u (local.get $i) (local.get $string_end)))
+        (local.set $unit (call $unit_length (local.get $i)))
+        (drop (call $copy
+          (i32.add (local.get $out)
+                   (i32.sub (i32.sub (local.get $string_end) (local.get $i)) (local.get $unit)))
+          (local.get $i) (local.get $unit)))
+        (local.set $i (i32.add (local.get $i) (local.get $unit)))
+        (br $reverse)))
+    (local.set $out (i32.add (local.get $out) (i32.sub (local.get $string_end) (local.get $start))))
+
+    (local.set $out (call $copy (local.get $out) (i32.const 64) (i32.const 14)))
+    (local.set $out (call $copy (local.get $out) (local.get $start)
+                          (i32.sub (local.get $string_end) (local.get $start))))
+    (local.set $out (call $copy (local.get $out) (i32.const 128) (i32.const 3)))
+
+    (i32.store (local.get $result)
+               (i32.sub (i32.sub (local.get $out) (local.get $result)) (i32.const 4)))
+    (local.get $result))
+)
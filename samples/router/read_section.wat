@@ -0,0 +1,112 @@
+;; Source of read_section.wasm, assemble with: wat2wasm read_section.wat -o read_section.wasm
+;;
+;; Implements the 'ReadSection' function using the WASM function ABI (see "WASM Implementations" in the guide).
+;; The input is the JSON of the inputs, one input with three strings (e.g. [["1","2","3"]]) and if all
+;; three are numbers then the result outputs them as an array of numbers: {"value":[1,2,3]}
+(module
+  (memory (export "memory") 1)
+
+  (global $input i32 (i32.const 1024))
+
+  (data (i32.const 0) "{\22value\22:[")
+  (data (i32.const 64) "]}")
+  (data (i32.const 128) "{}")
+
+  ;; The inputs are always written at the same place, the result is written after them
+  ;; Make sure there is enough memory for both
+  (func (export "alloc") (param $size i32) (result i32)
+    (local $needed i32)
+    (local.set $needed
+      (i32.add (global.get $input) (i32.add (i32.mul (local.get $size) (i32.const 2)) (i32.const 1024))))
+    (block $done
+      (loop $grow
+        (br_if $done (i32.ge_u (i32.mul (memory.size) (i32.const 65536)) (local.get $needed)))
+        (drop (memory.grow (i32.const 1)))
+        (br $grow)))
+    (global.get $input))
+
+  ;; copy 'length' bytes from 'from' to 'to' and return the address after the last byte written
+  (func $copy (param $to i32) (param $from i32) (param $length i32) (result i32)
+    (local $i i32)
+    (block $done
+      (loop $next
+        (br_if $done (i32.ge_u (local.get $i) (local.get $length)))
+        (i32.store8 (i32.add (local.get $to) (local.get $i))
+                    (i32.load8_u (i32.add (local.get $from) (local.get $i))))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $next)))
+    (i32.add (local.get $to) (local.get $length)))
+
+  ;; write '{}' (no output) as the result
+  (func $no_output (param $result i32) (result i32)
+    (i32.store (local.get $result) (i32.const 2))
+    (drop (call $copy (i32.add (local.get $result) (i32.const 4)) (i32.const 128) (i32.const 2)))
+    (local.get $result))
+
+  (func (export "run") (param $ptr i32) (param $len i32) (result i32)
+    (local $end i32)
+    (local $result i32)
+    (local $out i32)
+    (local $i i32)
+    (local $byte i32)
+    (local $count i32)
+    (local $digits i32)
+    (local.set $end (i32.add (local.get $ptr) (local.get $len)))
+    ;; result goes after the input, aligned to 4 bytes
+    (local.set $result (i32.and (i32.add (local.get $end) (i32.const 7)) (i32.const -4)))
+    (local.set $out (call $copy (i32.add (local.get $result) (i32.const 4)) (i32.const 0) (i32.const 10)))
+
+    (local.set $i (local.get $ptr))
+    (block $all_read
+      (loop $next_string
+        (br_if $all_read (i32.eq (local.get $count) (i32.const 3)))
+
+        ;; find the start of the next string
+        (block $found
+          (loop $search
+            (if (i32.ge_u (local.get $i) (local.get $end))
+              (then (return (call $no_output (local.get $result)))))
+            (br_if $found (i32.eq (i32.load8_u (local.get $i)) (i32.const 34))) ;; '"'
+            (local.set $i (i32.add (local.get $i) (i32.const 1)))
+            (br $search)))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+
+        (if (local.get $count)
+          (then
+            (i32.store8 (local.get $out) (i32.const 44)) ;; ','
+            (local.set $out (i32.add (local.get $out) (i32.const 1)))))
+
+        ;; copy the digits up to the end of the string, skipping leading zeros
+        (local.set $digits (i32.const 0))
+        (block $string_end
+          (loop $next_digit
+            (if (i32.ge_u (local.get $i) (local.get $end))
+              (then (return (call $no_output (local.get $result)))))
+            (local.set $byte (i32.load8_u (local.get $i)))
+            (local.set $i (i32.add (local.get $i) (i32.const 1)))
+            (br_if $string_end (i32.eq (local.get $byte) (i32.const 34)))
+            (if (i32.or (i32.lt_u (local.get $byte) (i32.const 48)) (i32.gt_u (local.get $byte) (i32.const 57)))
+              (then (return (call $no_output (local.get $result)))))
+            (if (i32.or (local.get $digits) (i32.ne (local.get $byte) (i32.const 48)))
+              (then
+                (i32.store8 (local.get $out) (local.get $byte))
+                (local.set $out (i32.add (local.get $out) (i32.const 1)))
+                (local.set $digits (i32.add (local.get $digits) (i32.const 1)))))
+            (br $next_digit)))
+
+        (if (i32.eqz (local.get $digits))
+          (then
+            ;; an empty string is not a number, but "0" (or "00" etc) is
+            (if (i32.eq (i32.load8_u (i32.sub (local.get $i) (i32.const 2))) (i32.const 34))
+              (then (return (call $no_output (local.get $result)))))
+            (i32.store8 (local.get $out) (i32.const 48))
+            (local.set $out (i32.add (local.get $out) (i32.const 1)))))
+
+        (local.set $count (i32.add (local.get $count) (i32.const 1)))
+        (br $next_string)))
+
+    (local.set $out (call $copy (local.get $out) (i32.const 64) (i32.const 2)))
+    (i32.store (local.get $result)
+               (i32.sub (i32.sub (local.get $out) (local.get $result)) (i32.const 4)))
+    (local.get $result))
+)
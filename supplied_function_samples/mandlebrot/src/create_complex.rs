@@ -1,7 +1,5 @@
 use flowrlib::implementation::Implementation;
-use flowrlib::implementation::RunAgain;
-use flowrlib::process::Process;
-use flowrlib::runlist::RunList;
+use flowrlib::implementation::RunResult;
 use num::Complex;
 use serde_json::Value as JsonValue;
 
@@ -17,19 +15,17 @@ pub struct CreateComplex;
     plane designating the area our image covers.
 */
 impl Implementation for CreateComplex {
-    fn run(&self, process: &Process, mut inputs: Vec<Vec<JsonValue>>, run_list: &mut RunList) -> RunAgain {
+    fn run(&self, mut inputs: Vec<Vec<JsonValue>>) -> RunResult {
         let arg1 = inputs.remove(0).remove(0);
         let arg2 = inputs.remove(0).remove(0);
 
         match (arg1, arg2) {
             (JsonValue::Number(re), JsonValue::Number(im)) => {
                 let output = json!({ "re" : re, "im": im });
-                run_list.send_output(process, output);
+                Ok((Some(output), true))
             },
-            _  => {}
+            _  => Ok((None, true))
         }
-
-        true
     }
 }
 
@@ -40,8 +36,7 @@ pub fn create_complex(re: f64, im: f64) -> Complex<f64> {
 
 #[cfg(test)]
 mod tests {
-    use flowrlib::process::Process;
-    use flowrlib::runlist::RunList;
+    use flowrlib::implementation::Implementation;
     use serde_json::Value as JsonValue;
 
     use super::CreateComplex;
@@ -54,11 +49,9 @@ mod tests {
 
         let inputs: Vec<Vec<JsonValue>> = vec!(vec!(arg1), vec!(arg2));
 
-        let mut run_list = RunList::new();
-        let pc = &Function::new("pc", 2, true, vec!(1, 1, 1), 0, Box::new(CreateComplex), None, vec!()) as &Process;
-        let implementation = pc.implementation();
+        let (output, _) = CreateComplex.run(inputs).unwrap();
 
-        implementation.run(pc, inputs, &mut run_list);
+        assert_eq!(output, Some(json!({ "re" : 1.5, "im": 1.6 })));
     }
 }
 
@@ -1,7 +1,5 @@
 use flowrlib::implementation::Implementation;
-use flowrlib::implementation::RunAgain;
-use flowrlib::process::Process;
-use flowrlib::runlist::RunList;
+use flowrlib::implementation::RunResult;
 use num::Complex;
 use serde_json::Value as JsonValue;
 
@@ -15,7 +13,7 @@ pub struct Escapes;
     able to prove that 'c' is not a member) return 'None'
 */
 impl Implementation for Escapes {
-    fn run(&self, process: &Process, mut inputs: Vec<Vec<JsonValue>>, run_list: &mut RunList) -> RunAgain {
+    fn run(&self, mut inputs: Vec<Vec<JsonValue>>) -> RunResult {
         let point = inputs.remove(0).remove(0);
         // pixel_bounds: (usize, usize),
         let re = point["re"].as_f64().unwrap();
@@ -24,9 +22,7 @@ impl Implementation for Escapes {
 
         let limit = inputs.remove(0).remove(0).as_u64().unwrap();
 
-        run_list.send_output(process, json!(escapes(complex_point, limit)));
-
-        true
+        Ok((Some(json!(escapes(complex_point, limit))), true))
     }
 }
 
@@ -54,8 +50,7 @@ pub fn escapes(c: Complex<f64>, limit: u64) -> u64 {
 
 #[cfg(test)]
 mod tests {
-    use flowrlib::process::Process;
-    use flowrlib::runlist::RunList;
+    use flowrlib::implementation::Implementation;
     use num::Complex;
     use serde_json::Value as JsonValue;
     use test::Bencher;
@@ -70,11 +65,9 @@ mod tests {
         let limit = json!(100);
         let inputs: Vec<Vec<JsonValue>> = vec!(vec!(point), vec!(limit));
 
-        let mut run_list = RunList::new();
-        let escapes = &Function::new("escapes", 2, true, vec!(1,1), 0, Box::new(Escapes), None, vec!()) as &Process;
-        let implementation = escapes.implementation();
+        let (output, _) = Escapes.run(inputs).unwrap();
 
-        implementation.run(escapes, inputs, &mut run_list);
+        assert!(output.is_some());
     }
 
     #[bench]
@@ -1,7 +1,5 @@
 use flowrlib::implementation::Implementation;
-use flowrlib::implementation::RunAgain;
-use flowrlib::process::Process;
-use flowrlib::runlist::RunList;
+use flowrlib::implementation::RunResult;
 use serde_json::Value as JsonValue;
 use std::str::FromStr;
 
@@ -17,7 +15,7 @@ pub struct ParsePair;
     plane designating the area our image covers.
 */
 impl Implementation for ParsePair {
-    fn run(&self, process: &Process, mut inputs: Vec<Vec<JsonValue>>, run_list: &mut RunList) -> RunAgain {
+    fn run(&self, mut inputs: Vec<Vec<JsonValue>>) -> RunResult {
         let string = inputs.remove(0).remove(0);
         let separator = inputs.remove(0).remove(0);
 
@@ -29,13 +27,13 @@ impl Implementation for ParsePair {
                 // send output as Json
                 if let Some(pair) = split {
                     let output = json!({ "first" : pair.0, "second": pair.1 });
-                    run_list.send_output(process, output);
+                    return Ok((Some(output), true));
                 }
             }
             _ => {}
         }
 
-        true
+        Ok((None, true))
     }
 }
 
@@ -59,8 +57,7 @@ pub fn parse_pair<T: FromStr>(s: &str, separator: &str) -> Option<(T, T)> {
 
 #[cfg(test)]
 mod tests {
-    use flowrlib::process::Process;
-    use flowrlib::runlist::RunList;
+    use flowrlib::implementation::Implementation;
     use serde_json::Value as JsonValue;
 
     use super::parse_pair;
@@ -74,15 +71,9 @@ mod tests {
 
         let inputs: Vec<Vec<JsonValue>> = vec!(vec!(string), vec!(separator));
 
-        let mut run_list = RunList::new();
-        let pp = &Process::new("pp", 2, true, vec!(1, 1, 1),
-                                0,
-                                Box::new(ParsePair),
-                                None,
-                                vec!()) as &Process;
-        let implementation = pp.implementation();
+        let (output, _) = ParsePair.run(inputs).unwrap();
 
-        implementation.run(pp, inputs, &mut run_list);
+        assert_eq!(output, Some(json!({ "first" : 100.0, "second": 200.0 })));
     }
 
     #[test]
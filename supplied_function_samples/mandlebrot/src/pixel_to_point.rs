@@ -1,7 +1,5 @@
 use flowrlib::implementation::Implementation;
-use flowrlib::implementation::RunAgain;
-use flowrlib::process::Process;
-use flowrlib::runlist::RunList;
+use flowrlib::implementation::RunResult;
 use num::Complex;
 use serde_json::Value as JsonValue;
 
@@ -17,7 +15,7 @@ pub struct PixelToPoint;
     plane designating the area our image covers.
 */
 impl Implementation for PixelToPoint {
-    fn run(&self, process: &Process, mut inputs: Vec<Vec<JsonValue>>, run_list: &mut RunList) -> RunAgain {
+    fn run(&self, mut inputs: Vec<Vec<JsonValue>>) -> RunResult {
         let pixel_bounds = inputs.remove(0).remove(0);
         // pixel_bounds: (usize, usize),
         let pixel_bounds_x = pixel_bounds["x"].as_u64().unwrap() as usize;
@@ -49,9 +47,7 @@ impl Implementation for PixelToPoint {
 
         // output: Complex<f64>
         let output = json!({ "re" : complex_point.re, "im": complex_point.im });
-        run_list.send_output(process, output);
-
-        true
+        Ok((Some(output), true))
     }
 }
 
@@ -80,8 +76,7 @@ pub fn pixel_to_point(bounds: (usize, usize), pixel: (usize, usize),
 
 #[cfg(test)]
 mod tests {
-    use flowrlib::process::Process;
-    use flowrlib::runlist::RunList;
+    use flowrlib::implementation::Implementation;
     use num::Complex;
     use serde_json::Value as JsonValue;
 
@@ -106,11 +101,9 @@ mod tests {
         let pixel = json!({"x": 50, "y": 50 });
         let inputs: Vec<Vec<JsonValue>> = vec!(vec!(pixel_bounds), vec!(complex_bounds), vec!(pixel));
 
-        let mut run_list = RunList::new();
-        let p2p = &Function::new("p2p", 3, true, vec!(1, 1, 1), 0, Box::new(PixelToPoint), None, vec!()) as &Process;
-        let implementation = p2p.implementation();
+        let (output, _) = PixelToPoint.run(inputs).unwrap();
 
-        implementation.run(p2p, inputs, &mut run_list);
+        assert_eq!(output, Some(json!({ "re" : 0.5, "im": -0.5 })));
     }
 }
 